@@ -0,0 +1,382 @@
+use geo::algorithm::area::Area;
+use geo::algorithm::coordinate_position::{CoordPos, CoordinatePosition};
+use geo::algorithm::orient::{Direction, Orient};
+use geo_types::{
+    Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPolygon, Polygon,
+};
+
+// Following RFC 7946 (section 3.1.9), a segment which spans more than 180
+// degrees of longitude is taken to cross the antimeridian rather than the
+// rest of the world. Segments with an end on the antimeridian itself, like
+// the edges of a box covering the whole world, don't count as crossing.
+fn crossing(a: Coord<f64>, b: Coord<f64>) -> bool {
+    (b.x - a.x).abs() > 180.0 && a.x.abs() < 180.0 && b.x.abs() < 180.0
+}
+
+fn line_crosses(coords: &[Coord<f64>]) -> bool {
+    coords.windows(2).any(|pair| crossing(pair[0], pair[1]))
+}
+
+fn polygon_crosses(poly: &Polygon<f64>) -> bool {
+    line_crosses(&poly.exterior().0) || poly.interiors().iter().any(|r| line_crosses(&r.0))
+}
+
+pub fn crosses(geom: &Geometry<f64>) -> bool {
+    match geom {
+        Geometry::Line(l) => crossing(l.start, l.end),
+        Geometry::LineString(ls) => line_crosses(&ls.0),
+        Geometry::Polygon(poly) => polygon_crosses(poly),
+        Geometry::MultiLineString(mls) => mls.0.iter().any(|ls| line_crosses(&ls.0)),
+        Geometry::MultiPolygon(mp) => mp.0.iter().any(polygon_crosses),
+        Geometry::GeometryCollection(gc) => gc.0.iter().any(crosses),
+        Geometry::Triangle(t) => polygon_crosses(&t.to_polygon()),
+        Geometry::Point(_) | Geometry::MultiPoint(_) | Geometry::Rect(_) => false,
+    }
+}
+
+// Make longitudes continuous across the antimeridian, so that
+// a line from 170 to -170 continues on to 190 instead
+fn unwrap(coords: &[Coord<f64>]) -> Vec<Coord<f64>> {
+    let mut out: Vec<Coord<f64>> = Vec::with_capacity(coords.len());
+    let mut offset = 0.0;
+    for (i, c) in coords.iter().enumerate() {
+        if i > 0 && crossing(coords[i - 1], *c) {
+            offset += if c.x < coords[i - 1].x { 360.0 } else { -360.0 };
+        }
+        out.push(Coord {
+            x: c.x + offset,
+            y: c.y,
+        });
+    }
+    out
+}
+
+fn x_range(coords: &[Coord<f64>]) -> (f64, f64) {
+    coords
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), c| {
+            (min.min(c.x), max.max(c.x))
+        })
+}
+
+// Antimeridians (180 + 360n) within a range of unwrapped longitudes
+fn meridians(min_x: f64, max_x: f64) -> Vec<f64> {
+    let mut m = 180.0 + 360.0 * ((min_x - 180.0) / 360.0).floor();
+    while m <= min_x {
+        m += 360.0;
+    }
+    let mut out = Vec::new();
+    while m < max_x {
+        out.push(m);
+        m += 360.0;
+    }
+    out
+}
+
+// Offset which moves unwrapped coordinates back into -180..180
+fn wrap_offset(coords: &[Coord<f64>]) -> f64 {
+    let (min, max) = x_range(coords);
+    -360.0 * (((min + max) / 2.0 + 180.0) / 360.0).floor()
+}
+
+fn shifted(coords: &[Coord<f64>], offset: f64) -> LineString<f64> {
+    LineString(
+        coords
+            .iter()
+            .map(|c| Coord {
+                x: c.x + offset,
+                y: c.y,
+            })
+            .collect(),
+    )
+}
+
+fn push_unique(coords: &mut Vec<Coord<f64>>, c: Coord<f64>) {
+    if coords.last() != Some(&c) {
+        coords.push(c);
+    }
+}
+
+// Coordinates on the meridian count as being west of it
+fn west(c: &Coord<f64>, meridian: f64) -> bool {
+    c.x <= meridian
+}
+
+fn cut_point(a: Coord<f64>, b: Coord<f64>, meridian: f64) -> Coord<f64> {
+    let t = (meridian - a.x) / (b.x - a.x);
+    Coord {
+        x: meridian,
+        y: a.y + t * (b.y - a.y),
+    }
+}
+
+// Split a line into parts on either side of a meridian.
+// Each part starts or ends with the point where it was cut.
+fn cut_line(coords: &[Coord<f64>], meridian: f64) -> Vec<Vec<Coord<f64>>> {
+    let mut parts = Vec::new();
+    let mut part: Vec<Coord<f64>> = Vec::new();
+    for (i, c) in coords.iter().enumerate() {
+        if i > 0 && west(&coords[i - 1], meridian) != west(c, meridian) {
+            let cut = cut_point(coords[i - 1], *c, meridian);
+            push_unique(&mut part, cut);
+            parts.push(part);
+            part = vec![cut];
+        }
+        push_unique(&mut part, *c);
+    }
+    parts.push(part);
+    parts.into_iter().filter(|p| p.len() > 1).collect()
+}
+
+fn split_line(ls: &LineString<f64>) -> Vec<LineString<f64>> {
+    if !line_crosses(&ls.0) {
+        return vec![ls.clone()];
+    }
+    let unwrapped = unwrap(&ls.0);
+    let (min, max) = x_range(&unwrapped);
+    let mut parts = vec![unwrapped];
+    for m in meridians(min, max) {
+        parts = parts.iter().flat_map(|p| cut_line(p, m)).collect();
+    }
+    parts.iter().map(|p| shifted(p, wrap_offset(p))).collect()
+}
+
+// Pieces of a ring which was cut at a meridian. Each arc starts
+// and ends on the meridian, and lies on one side of it.
+fn ring_arcs(ring: &[Coord<f64>], meridian: f64) -> Vec<Vec<Coord<f64>>> {
+    let mut parts = cut_line(ring, meridian);
+    // The ring's first and last parts are the same arc
+    if parts.len() > 1 && ring.first() == ring.last() {
+        let last = parts.pop().unwrap();
+        let first = parts.remove(0);
+        let mut joined = last;
+        first.into_iter().for_each(|c| push_unique(&mut joined, c));
+        parts.push(joined);
+    }
+    parts
+}
+
+// Join arcs from one side of a meridian into rings. Polygons are oriented
+// with their interior to the left of each ring, so a ring continues north
+// along the meridian from the end of an arc on the west side, and south from
+// an arc on the east side, to the start of the next arc.
+fn join_arcs(mut arcs: Vec<Vec<Coord<f64>>>, west_side: bool) -> Vec<LineString<f64>> {
+    let mut rings = Vec::new();
+    while !arcs.is_empty() {
+        let mut ring = arcs.remove(0);
+        let start = ring[0];
+        loop {
+            let end = *ring.last().unwrap();
+            let gap = |c: &Coord<f64>| if west_side { c.y - end.y } else { end.y - c.y };
+            let next = arcs
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| gap(&a[0]) >= 0.0)
+                .min_by(|(_, a), (_, b)| gap(&a[0]).partial_cmp(&gap(&b[0])).unwrap())
+                .map(|(i, a)| (i, gap(&a[0])));
+            match next {
+                Some((i, g)) if g < gap(&start) || gap(&start) < 0.0 => {
+                    let arc = arcs.remove(i);
+                    arc.into_iter().for_each(|c| push_unique(&mut ring, c));
+                }
+                _ => {
+                    push_unique(&mut ring, start);
+                    break;
+                }
+            }
+        }
+        if ring.len() > 3 {
+            rings.push(LineString(ring));
+        }
+    }
+    rings
+}
+
+// Cut an (oriented) polygon at a meridian into pieces on either side of it
+fn cut_polygon(poly: &Polygon<f64>, meridian: f64) -> Vec<Polygon<f64>> {
+    let crosses_meridian = |ring: &LineString<f64>| {
+        let sides: Vec<bool> = ring.0.iter().map(|c| west(c, meridian)).collect();
+        sides.contains(&true) && sides.contains(&false)
+    };
+    if !crosses_meridian(poly.exterior()) {
+        return vec![poly.clone()];
+    }
+
+    let mut west_arcs = Vec::new();
+    let mut east_arcs = Vec::new();
+    let mut whole_holes = Vec::new();
+    let rings = std::iter::once(poly.exterior()).chain(poly.interiors().iter());
+    for (i, ring) in rings.enumerate() {
+        if i > 0 && !crosses_meridian(ring) {
+            whole_holes.push(ring.clone());
+            continue;
+        }
+        for arc in ring_arcs(&ring.0, meridian) {
+            if arc.iter().all(|c| west(c, meridian)) {
+                west_arcs.push(arc);
+            } else {
+                east_arcs.push(arc);
+            }
+        }
+    }
+
+    let mut shells = join_arcs(west_arcs, true);
+    shells.extend(join_arcs(east_arcs, false));
+    shells
+        .into_iter()
+        .map(|shell| {
+            let outer = Polygon::new(shell.clone(), vec![]);
+            let holes = whole_holes
+                .iter()
+                .filter(|h| {
+                    h.0.iter()
+                        .any(|c| outer.coordinate_position(c) == CoordPos::Inside)
+                })
+                .cloned()
+                .collect();
+            Polygon::new(shell, holes)
+        })
+        .filter(|p| p.unsigned_area() > 0.0)
+        .collect()
+}
+
+fn split_polygon(poly: &Polygon<f64>) -> Vec<Polygon<f64>> {
+    if !polygon_crosses(poly) {
+        return vec![poly.clone()];
+    }
+    let exterior = unwrap(&poly.exterior().0);
+    // A ring which doesn't end up back where it started goes around a pole
+    if exterior.first().map(|c| c.x) != exterior.last().map(|c| c.x) {
+        return vec![poly.clone()];
+    }
+    let (min, max) = x_range(&exterior);
+    let mid = (min + max) / 2.0;
+    let holes = poly
+        .interiors()
+        .iter()
+        .map(|ring| {
+            // Holes are moved next to their (unwrapped) shell
+            let hole = unwrap(&ring.0);
+            let (h_min, h_max) = x_range(&hole);
+            let offset = 360.0 * ((mid - (h_min + h_max) / 2.0) / 360.0).round();
+            shifted(&hole, offset)
+        })
+        .collect();
+
+    let mut pieces = vec![Polygon::new(LineString(exterior), holes).orient(Direction::Default)];
+    for m in meridians(min, max) {
+        pieces = pieces.iter().flat_map(|p| cut_polygon(p, m)).collect();
+    }
+    pieces
+        .into_iter()
+        .map(|p| {
+            let offset = wrap_offset(&p.exterior().0);
+            Polygon::new(
+                shifted(&p.exterior().0, offset),
+                p.interiors()
+                    .iter()
+                    .map(|r| shifted(&r.0, offset))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+// Split a geometry which crosses the antimeridian into parts on either side of it,
+// as recommended by RFC 7946. Polygons become MultiPolygons and LineStrings become
+// MultiLineStrings when they're split, and other geometries are left as they are.
+//
+// Polygon rings which go around a pole can't be split this way, and are left as they are.
+pub fn split(geom: &Geometry<f64>) -> Geometry<f64> {
+    if !crosses(geom) {
+        return geom.clone();
+    }
+    match geom {
+        Geometry::Line(l) => split(&Geometry::LineString(LineString(vec![l.start, l.end]))),
+        Geometry::LineString(ls) => {
+            let mut parts = split_line(ls);
+            if parts.len() == 1 {
+                Geometry::LineString(parts.remove(0))
+            } else {
+                Geometry::MultiLineString(MultiLineString(parts))
+            }
+        }
+        Geometry::MultiLineString(mls) => {
+            Geometry::MultiLineString(MultiLineString(mls.0.iter().flat_map(split_line).collect()))
+        }
+        Geometry::Polygon(poly) => {
+            let mut pieces = split_polygon(poly);
+            if pieces.len() == 1 {
+                Geometry::Polygon(pieces.remove(0))
+            } else {
+                Geometry::MultiPolygon(MultiPolygon(pieces))
+            }
+        }
+        Geometry::MultiPolygon(mp) => {
+            Geometry::MultiPolygon(MultiPolygon(mp.0.iter().flat_map(split_polygon).collect()))
+        }
+        Geometry::Triangle(t) => split(&Geometry::Polygon(t.to_polygon())),
+        Geometry::GeometryCollection(gc) => {
+            Geometry::GeometryCollection(GeometryCollection(gc.0.iter().map(split).collect()))
+        }
+        _ => geom.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crosses, split};
+    use crate::geoq::entity::wkt_geom;
+    use geo_types::Geometry;
+
+    #[test]
+    fn test_detecting_crossings() {
+        assert!(crosses(&wkt_geom("LINESTRING(170 0,-170 10)")));
+        assert!(crosses(&wkt_geom("LINESTRING(-170 0,170 10)")));
+        assert!(!crosses(&wkt_geom("LINESTRING(-170 0,0 0,170 10)")));
+        assert!(!crosses(&wkt_geom(
+            "POLYGON((-180 -90,180 -90,180 90,-180 90,-180 -90))"
+        )));
+        assert!(crosses(&wkt_geom(
+            "POLYGON((170 -10,-170 -10,-170 10,170 10,170 -10))"
+        )));
+    }
+
+    #[test]
+    fn test_splitting_lines() {
+        assert_eq!(
+            wkt_geom("MULTILINESTRING((170 0,180 5),(-180 5,-170 10))"),
+            split(&wkt_geom("LINESTRING(170 0,-170 10)"))
+        );
+        assert_eq!(
+            wkt_geom("MULTILINESTRING((-170 0,-180 5),(180 5,170 10,160 10))"),
+            split(&wkt_geom("LINESTRING(-170 0,170 10,160 10)"))
+        );
+        assert_eq!(
+            wkt_geom("LINESTRING(-170 0,0 0,170 10)"),
+            split(&wkt_geom("LINESTRING(-170 0,0 0,170 10)"))
+        );
+    }
+
+    #[test]
+    fn test_splitting_polygons() {
+        assert_eq!(
+            wkt_geom("MULTIPOLYGON(((180 10,170 10,170 -10,180 -10,180 10)),((-180 -10,-170 -10,-170 10,-180 10,-180 -10)))"),
+            split(&wkt_geom("POLYGON((170 -10,-170 -10,-170 10,170 10,170 -10))"))
+        );
+
+        // A 'C' shape which crosses the antimeridian twice,
+        // with a hole which stays on the east side
+        let c_shape = wkt_geom("POLYGON((170 0,-170 0,-170 2,175 2,175 8,-170 8,-170 10,170 10,170 0),(171 4,172 4,172 5,171 4))");
+        match split(&c_shape) {
+            Geometry::MultiPolygon(mp) => {
+                assert_eq!(3, mp.0.len());
+                assert_eq!(1, mp.0.iter().map(|p| p.interiors().len()).sum::<usize>());
+                for p in mp.0.iter() {
+                    assert!(!crosses(&Geometry::Polygon(p.clone())));
+                }
+            }
+            other => assert!(false, "Expected MultiPolygon, got {:?}", other),
+        }
+    }
+}
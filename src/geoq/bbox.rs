@@ -1,3 +1,4 @@
+use crate::geoq::{antimeridian, error::Error};
 use geo_types::*;
 use std::cmp::Ordering;
 use std::fmt::Debug;
@@ -70,6 +71,147 @@ pub fn bbox(geom: &Geometry<f64>) -> geo::Rect<f64> {
     }
 }
 
+// The smallest range of longitudes covering all the given ranges. Ranges go
+// east from their first value to their second, so they wrap around the
+// antimeridian when the first is greater, e.g. (170, -170).
+fn covering_range(ranges: &[(f64, f64)]) -> (f64, f64) {
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    for &(west, east) in ranges {
+        if west <= east {
+            intervals.push((west, east));
+        } else {
+            intervals.push((west, 180.0));
+            intervals.push((-180.0, east));
+        }
+    }
+    intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = max(last.1, end),
+            _ => merged.push((start, end)),
+        }
+    }
+    if merged.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    // The range is everything outside of the largest gap between intervals,
+    // which may be the one across the antimeridian
+    let first = merged[0];
+    let last = merged[merged.len() - 1];
+    let mut gap = first.0 + 360.0 - last.1;
+    let mut range = (first.0, last.1);
+    for pair in merged.windows(2) {
+        if pair[1].0 - pair[0].1 > gap {
+            gap = pair[1].0 - pair[0].1;
+            range = (pair[1].0, pair[0].1);
+        }
+    }
+    range
+}
+
+fn segment_ranges(coords: &[Coord<f64>], ranges: &mut Vec<(f64, f64)>) {
+    if coords.len() == 1 {
+        ranges.push((coords[0].x, coords[0].x));
+    }
+    for pair in coords.windows(2) {
+        let (a, b) = (pair[0].x, pair[1].x);
+        if antimeridian::crosses(&Geometry::Line(Line::new(pair[0], pair[1]))) {
+            ranges.push((max(a, b), min(a, b)));
+        } else {
+            ranges.push((min(a, b), max(a, b)));
+        }
+    }
+}
+
+// Bounding box as [west, south, east, north], as in GeoJSON. For geometries
+// which cross the antimeridian, west will be greater than east (RFC 7946, section 5.2).
+pub fn antimeridian_bbox(geom: &Geometry<f64>) -> [f64; 4] {
+    use geo::algorithm::coords_iter::CoordsIter;
+    let mut ranges = Vec::new();
+    match geom {
+        Geometry::Line(l) => segment_ranges(&[l.start, l.end], &mut ranges),
+        Geometry::LineString(ls) => segment_ranges(&ls.0, &mut ranges),
+        Geometry::Polygon(poly) => segment_ranges(&poly.exterior().0, &mut ranges),
+        Geometry::MultiLineString(mls) => mls
+            .0
+            .iter()
+            .for_each(|ls| segment_ranges(&ls.0, &mut ranges)),
+        Geometry::MultiPolygon(mp) => {
+            mp.0.iter()
+                .for_each(|poly| segment_ranges(&poly.exterior().0, &mut ranges))
+        }
+        Geometry::GeometryCollection(gc) => {
+            let boxes: Vec<[f64; 4]> = gc.0.iter().map(antimeridian_bbox).collect();
+            return boxes
+                .iter()
+                .skip(1)
+                .fold(boxes.first().cloned().unwrap_or([0.0; 4]), |a, b| {
+                    merge_antimeridian(&a, b)
+                });
+        }
+        _ => geom.coords_iter().for_each(|c| ranges.push((c.x, c.x))),
+    }
+    let (west, east) = covering_range(&ranges);
+    let rect = bbox(geom);
+    [west, rect.min().y, east, rect.max().y]
+}
+
+pub fn merge_antimeridian(a: &[f64; 4], b: &[f64; 4]) -> [f64; 4] {
+    let (west, east) = covering_range(&[(a[0], a[2]), (b[0], b[2])]);
+    [west, min(a[1], b[1]), east, max(a[3], b[3])]
+}
+
+// Polygon for a [west, south, east, north] box. Boxes crossing the
+// antimeridian are given as a MultiPolygon with a part on either side.
+pub fn antimeridian_bbox_geometry(b: &[f64; 4]) -> Geometry<f64> {
+    let rect = |west: f64, east: f64| {
+        geo::Rect::new(Coord { x: west, y: b[1] }, Coord { x: east, y: b[3] }).to_polygon_geoq()
+    };
+    if b[0] <= b[2] {
+        Geometry::Polygon(rect(b[0], b[2]))
+    } else {
+        Geometry::MultiPolygon(MultiPolygon(vec![rect(b[0], 180.0), rect(-180.0, b[2])]))
+    }
+}
+
+// Parses a box given as "minX,minY,maxX,maxY", as accepted by --bbox options
+pub fn parse(bbox: &str) -> Result<geo::Rect<f64>, Error> {
+    let invalid = || {
+        Error::InvalidInput(format!(
+            "Invalid bounding box format: {}. Should be 4 comma-separated numbers: minX,minY,maxX,maxY.",
+            bbox
+        ))
+    };
+    let parts = bbox
+        .split(',')
+        .map(|num| num.trim().parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<f64>, Error>>()?;
+    match parts.as_slice() {
+        &[min_x, min_y, max_x, max_y] if min_x <= max_x && min_y <= max_y => Ok(geo::Rect::new(
+            Coord { x: min_x, y: min_y },
+            Coord { x: max_x, y: max_y },
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+pub fn intersects(a: &geo::Rect<f64>, b: &geo::Rect<f64>) -> bool {
+    a.min().x <= b.max().x
+        && b.min().x <= a.max().x
+        && a.min().y <= b.max().y
+        && b.min().y <= a.max().y
+}
+
+pub fn contains(outer: &geo::Rect<f64>, inner: &geo::Rect<f64>) -> bool {
+    outer.min().x <= inner.min().x
+        && inner.max().x <= outer.max().x
+        && outer.min().y <= inner.min().y
+        && inner.max().y <= outer.max().y
+}
+
 pub trait BBoxToPoly {
     fn to_polygon_geoq(&self) -> geo_types::Polygon<f64>;
 }
@@ -88,3 +230,46 @@ impl BBoxToPoly for geo::Rect<f64> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{antimeridian_bbox, contains, intersects, merge_antimeridian, parse};
+    use geo_types::{Geometry, LineString, Point};
+
+    #[test]
+    fn test_antimeridian_bboxes() {
+        let crossing = Geometry::LineString(LineString::from(vec![(170.0, 0.0), (-170.0, 10.0)]));
+        assert_eq!([170.0, 0.0, -170.0, 10.0], antimeridian_bbox(&crossing));
+
+        let spanning = Geometry::LineString(LineString::from(vec![
+            (-170.0, 0.0),
+            (0.0, 5.0),
+            (170.0, 10.0),
+        ]));
+        assert_eq!([-170.0, 0.0, 170.0, 10.0], antimeridian_bbox(&spanning));
+
+        let point = antimeridian_bbox(&Geometry::Point(Point::new(-175.0, 20.0)));
+        assert_eq!([-175.0, 20.0, -175.0, 20.0], point);
+        assert_eq!(
+            [170.0, 0.0, -170.0, 20.0],
+            merge_antimeridian(&antimeridian_bbox(&crossing), &point)
+        );
+    }
+
+    #[test]
+    fn test_parsing_and_comparing_boxes() {
+        let b = parse("-10,-5,10, 5").unwrap();
+        assert_eq!((-10.0, -5.0), b.min().x_y());
+        assert_eq!((10.0, 5.0), b.max().x_y());
+        assert!(parse("1,2,3").is_err());
+        assert!(parse("1,2,3,x").is_err());
+        assert!(parse("10,0,-10,5").is_err());
+
+        let inside = parse("0,0,1,1").unwrap();
+        let edge = parse("10,5,20,20").unwrap();
+        let outside = parse("11,0,12,1").unwrap();
+        assert!(intersects(&b, &inside) && contains(&b, &inside));
+        assert!(intersects(&b, &edge) && !contains(&b, &edge));
+        assert!(!intersects(&b, &outside) && !contains(&b, &outside));
+    }
+}
@@ -0,0 +1,408 @@
+use crate::geoq::{
+    antimeridian, bbox,
+    error::Error,
+    geodesic::Projection,
+    overlay::{self, Operation},
+};
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon,
+};
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+// Furthest a mitred corner can reach, as a multiple of the buffer
+// distance, before it's cut off with a bevel instead
+const MITRE_LIMIT: f64 = 5.0;
+
+// Shape of the buffer around the ends of lines
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cap {
+    Round,
+    // Stop square at the end of the line
+    Flat,
+    // Extend past the end of the line by the buffer distance
+    Square,
+}
+
+pub const CAPS: [&str; 3] = ["round", "flat", "square"];
+
+impl FromStr for Cap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round" => Ok(Cap::Round),
+            "flat" => Ok(Cap::Flat),
+            "square" => Ok(Cap::Square),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --cap value: {}. Expected one of: {}.",
+                s,
+                CAPS.join(", ")
+            ))),
+        }
+    }
+}
+
+// Shape of the buffer around the outside of corners
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Join {
+    Round,
+    // Extend the sides until they meet in a point
+    Mitre,
+    // Cut the corner off with a straight edge
+    Bevel,
+}
+
+pub const JOINS: [&str; 3] = ["round", "mitre", "bevel"];
+
+impl FromStr for Join {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round" => Ok(Join::Round),
+            "mitre" => Ok(Join::Mitre),
+            "bevel" => Ok(Join::Bevel),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --join value: {}. Expected one of: {}.",
+                s,
+                JOINS.join(", ")
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Style {
+    // Segments used to draw each quarter of a circle
+    pub segments: usize,
+    pub cap: Cap,
+    pub join: Join,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            segments: 8,
+            cap: Cap::Round,
+            join: Join::Round,
+        }
+    }
+}
+
+fn polygon(coords: Vec<Coord<f64>>) -> MultiPolygon<f64> {
+    MultiPolygon(vec![Polygon::new(LineString(coords), vec![])])
+}
+
+fn circle(center: Coord<f64>, radius: f64, segments: usize) -> MultiPolygon<f64> {
+    let n = 4 * segments.max(1);
+    let coords = (0..=n)
+        .map(|i| {
+            let angle = 2.0 * PI * (i % n) as f64 / n as f64;
+            Coord {
+                x: center.x + radius * angle.cos(),
+                y: center.y + radius * angle.sin(),
+            }
+        })
+        .collect();
+    polygon(coords)
+}
+
+fn length(c: Coord<f64>) -> f64 {
+    (c.x * c.x + c.y * c.y).sqrt()
+}
+
+// Unit vectors along a segment and to its left
+fn direction(l: &Line<f64>) -> (Coord<f64>, Coord<f64>) {
+    let along = l.delta() / length(l.delta());
+    (
+        along,
+        Coord {
+            x: -along.y,
+            y: along.x,
+        },
+    )
+}
+
+fn cap(
+    end: Coord<f64>,
+    along: Coord<f64>,
+    left: Coord<f64>,
+    d: f64,
+    style: &Style,
+) -> Option<MultiPolygon<f64>> {
+    match style.cap {
+        Cap::Round => Some(circle(end, d, style.segments)),
+        Cap::Flat => None,
+        Cap::Square => {
+            let (out, side) = (along * d, left * d);
+            Some(polygon(vec![
+                end - side,
+                end + out - side,
+                end + out + side,
+                end + side,
+                end - side,
+            ]))
+        }
+    }
+}
+
+// Fills in the outside of the corner where one segment turns into the next
+fn join(l1: &Line<f64>, l2: &Line<f64>, d: f64, style: &Style) -> Option<MultiPolygon<f64>> {
+    let ((u1, n1), (u2, n2)) = (direction(l1), direction(l2));
+    let vertex = l2.start;
+    let turn = u1.x * u2.y - u1.y * u2.x;
+    if turn.abs() < 1e-12 {
+        return None;
+    }
+    if style.join == Join::Round {
+        return Some(circle(vertex, d, style.segments));
+    }
+    // The outside of a left turn is on the right
+    let side = if turn > 0.0 { -d } else { d };
+    let (a, b) = (vertex + n1 * side, vertex + n2 * side);
+    let bisector = n1 + n2;
+    let mitre = vertex + bisector * (side / (1.0 + n1.x * n2.x + n1.y * n2.y));
+    if style.join == Join::Mitre && length(mitre - vertex) <= MITRE_LIMIT * d {
+        Some(polygon(vec![vertex, a, mitre, b, vertex]))
+    } else {
+        Some(polygon(vec![vertex, a, b, vertex]))
+    }
+}
+
+// Pieces whose union is the buffer around a line: a rectangle along each
+// segment, plus the joins between them and the caps at either end
+fn line_pieces(
+    coords: &[Coord<f64>],
+    closed: bool,
+    d: f64,
+    style: &Style,
+    out: &mut Vec<MultiPolygon<f64>>,
+) {
+    let mut coords = coords.to_vec();
+    coords.dedup();
+    let segments: Vec<Line<f64>> = coords.windows(2).map(|w| Line::new(w[0], w[1])).collect();
+    let (first, last) = match (segments.first(), segments.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => {
+            out.extend(coords.first().map(|c| circle(*c, d, style.segments)));
+            return;
+        }
+    };
+
+    for l in segments.iter() {
+        let side = direction(l).1 * d;
+        out.push(polygon(vec![
+            l.start - side,
+            l.end - side,
+            l.end + side,
+            l.start + side,
+            l.start - side,
+        ]));
+    }
+    out.extend(
+        segments
+            .windows(2)
+            .filter_map(|pair| join(&pair[0], &pair[1], d, style)),
+    );
+    if closed {
+        out.extend(join(&last, &first, d, style));
+    } else {
+        let (along, left) = direction(&first);
+        out.extend(cap(first.start, -along, -left, d, style));
+        let (along, left) = direction(&last);
+        out.extend(cap(last.end, along, left, d, style));
+    }
+}
+
+fn polygon_buffer(mp: &MultiPolygon<f64>, d: f64, style: &Style) -> MultiPolygon<f64> {
+    if d == 0.0 {
+        return mp.clone();
+    }
+    let mut pieces = Vec::new();
+    for poly in mp.0.iter() {
+        line_pieces(&poly.exterior().0, true, d.abs(), style, &mut pieces);
+        for hole in poly.interiors() {
+            line_pieces(&hole.0, true, d.abs(), style, &mut pieces);
+        }
+    }
+    let rings = overlay::union_all(pieces);
+    if d > 0.0 {
+        overlay::overlay(mp, &rings, Operation::Union)
+    } else {
+        overlay::overlay(mp, &rings, Operation::Difference)
+    }
+}
+
+// Buffers a geometry in planar coordinates. Only polygons can
+// be shrunk, with a negative distance.
+fn planar_buffer(geom: &Geometry<f64>, d: f64, style: &Style) -> MultiPolygon<f64> {
+    let mut pieces = Vec::new();
+    match geom {
+        Geometry::Polygon(_)
+        | Geometry::MultiPolygon(_)
+        | Geometry::Rect(_)
+        | Geometry::Triangle(_) => {
+            return polygon_buffer(&overlay::polygons(geom), d, style);
+        }
+        _ if d <= 0.0 => (),
+        Geometry::Point(p) => pieces.push(circle(p.0, d, style.segments)),
+        Geometry::MultiPoint(mp) => {
+            pieces.extend(mp.0.iter().map(|p| circle(p.0, d, style.segments)))
+        }
+        Geometry::Line(l) => line_pieces(&[l.start, l.end], false, d, style, &mut pieces),
+        Geometry::LineString(ls) => line_pieces(&ls.0, false, d, style, &mut pieces),
+        Geometry::MultiLineString(mls) => {
+            for ls in mls.0.iter() {
+                line_pieces(&ls.0, false, d, style, &mut pieces);
+            }
+        }
+        Geometry::GeometryCollection(gc) => {
+            pieces.extend(gc.0.iter().map(|g| planar_buffer(g, d, style)));
+        }
+    }
+    overlay::union_all(pieces)
+}
+
+fn map_coords(geom: &Geometry<f64>, f: &dyn Fn(Coord<f64>) -> Coord<f64>) -> Geometry<f64> {
+    let line = |ls: &LineString<f64>| LineString(ls.0.iter().map(|c| f(*c)).collect());
+    let poly = |p: &Polygon<f64>| {
+        Polygon::new(line(p.exterior()), p.interiors().iter().map(line).collect())
+    };
+    match geom {
+        Geometry::Point(p) => Geometry::Point(Point(f(p.0))),
+        Geometry::MultiPoint(mp) => {
+            Geometry::MultiPoint(MultiPoint(mp.0.iter().map(|p| Point(f(p.0))).collect()))
+        }
+        Geometry::Line(l) => Geometry::Line(Line::new(f(l.start), f(l.end))),
+        Geometry::LineString(ls) => Geometry::LineString(line(ls)),
+        Geometry::MultiLineString(mls) => {
+            Geometry::MultiLineString(MultiLineString(mls.0.iter().map(line).collect()))
+        }
+        Geometry::Polygon(p) => Geometry::Polygon(poly(p)),
+        Geometry::MultiPolygon(mp) => {
+            Geometry::MultiPolygon(MultiPolygon(mp.0.iter().map(poly).collect()))
+        }
+        Geometry::Rect(r) => Geometry::Polygon(poly(&r.to_polygon())),
+        Geometry::Triangle(t) => Geometry::Polygon(poly(&t.to_polygon())),
+        Geometry::GeometryCollection(gc) => Geometry::GeometryCollection(GeometryCollection(
+            gc.0.iter().map(|g| map_coords(g, f)).collect(),
+        )),
+    }
+}
+
+// Buffers a lon/lat geometry by a distance in meters, working in an azimuthal
+// equidistant projection around the center of its bounding box. Buffers around
+// points are exact geodesic circles, while larger geometries are distorted
+// more the further they reach from their center. Gives None when nothing is
+// left, e.g. after shrinking a polygon by more than its width.
+pub fn buffer(geom: &Geometry<f64>, meters: f64, style: &Style) -> Option<Geometry<f64>> {
+    let projection = Projection::new(bbox::bbox(geom).center());
+    let projected = map_coords(geom, &|c| projection.project(c));
+    let mut buffered = planar_buffer(&projected, meters, style);
+    let polygons = match buffered.0.len() {
+        0 => return None,
+        1 => Geometry::Polygon(buffered.0.remove(0)),
+        _ => Geometry::MultiPolygon(buffered),
+    };
+    // Buffers reaching past the antimeridian are cut in two there
+    Some(antimeridian::split(&map_coords(&polygons, &|c| {
+        projection.unproject(c)
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{buffer, planar_buffer, Cap, Join, Style};
+    use crate::geoq::geodesic;
+    use geo::algorithm::area::Area;
+    use geo_types::{line_string, polygon, Coord, Geometry, Point};
+
+    fn area(geom: &Geometry<f64>, style: &Style, d: f64) -> f64 {
+        planar_buffer(geom, d, style).unsigned_area()
+    }
+
+    #[test]
+    fn test_planar_buffers() {
+        let style = Style::default();
+        let point = Geometry::Point(Point::new(0.0, 0.0));
+        // A 32-gon inscribed in the unit circle
+        assert!(
+            (area(&point, &style, 1.0) - 16.0 * (std::f64::consts::PI / 16.0).sin()).abs() < 1e-9
+        );
+
+        let line = Geometry::LineString(line_string![(x: 0.0, y: 0.0), (x: 4.0, y: 0.0)]);
+        let flat = Style {
+            cap: Cap::Flat,
+            ..style
+        };
+        assert!((area(&line, &flat, 1.0) - 8.0).abs() < 1e-9);
+        let square = Style {
+            cap: Cap::Square,
+            ..style
+        };
+        assert!((area(&line, &square, 1.0) - 12.0).abs() < 1e-9);
+
+        let corner = Geometry::LineString(
+            line_string![(x: 0.0, y: 0.0), (x: 4.0, y: 0.0), (x: 4.0, y: 4.0)],
+        );
+        let mitre = Style {
+            cap: Cap::Flat,
+            join: Join::Mitre,
+            ..style
+        };
+        assert!((area(&corner, &mitre, 1.0) - 16.0).abs() < 1e-9);
+        let bevel = Style {
+            cap: Cap::Flat,
+            join: Join::Bevel,
+            ..style
+        };
+        assert!((area(&corner, &bevel, 1.0) - 15.5).abs() < 1e-9);
+
+        let poly = Geometry::Polygon(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 4.0, y: 0.0),
+            (x: 4.0, y: 4.0),
+            (x: 0.0, y: 4.0),
+            (x: 0.0, y: 0.0),
+        ]);
+        assert!((area(&poly, &mitre, 1.0) - 36.0).abs() < 1e-9);
+        assert!((area(&poly, &mitre, -1.0) - 4.0).abs() < 1e-9);
+        assert_eq!(0.0, area(&poly, &mitre, -2.0));
+        assert_eq!(0.0, area(&line, &style, -1.0));
+    }
+
+    #[test]
+    fn test_geodesic_circle() {
+        let center = Coord { x: -73.9, y: 40.7 };
+        let circle = buffer(&Geometry::Point(Point(center)), 1000.0, &Style::default()).unwrap();
+        match circle {
+            Geometry::Polygon(p) => {
+                assert_eq!(33, p.exterior().0.len());
+                for c in p.exterior().0.iter() {
+                    assert!((geodesic::distance(center, *c) - 1000.0).abs() < 1e-6);
+                }
+            }
+            _ => panic!("Expected a polygon"),
+        }
+    }
+
+    #[test]
+    fn test_buffer_across_antimeridian() {
+        let center = Coord { x: 179.99, y: 10.0 };
+        let circle = buffer(&Geometry::Point(Point(center)), 5000.0, &Style::default()).unwrap();
+        match circle {
+            Geometry::MultiPolygon(mp) => {
+                assert_eq!(2, mp.0.len());
+                let xs: Vec<f64> =
+                    mp.0.iter()
+                        .flat_map(|p| p.exterior().0.iter().map(|c| c.x))
+                        .collect();
+                assert!(xs.iter().all(|x| (-180.0..=180.0).contains(x)));
+                assert!(xs.iter().any(|x| *x < -179.9));
+            }
+            _ => panic!("Expected a multipolygon"),
+        }
+    }
+}
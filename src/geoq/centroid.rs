@@ -1,3 +1,4 @@
+use geo::algorithm::area::Area;
 use geo::algorithm::centroid::Centroid;
 use geo_types::*;
 
@@ -15,3 +16,353 @@ pub fn centroid(geom: &Geometry<f64>) -> Option<Point<f64>> {
         _ => None,
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    // Weighted mean of coordinates on a plane
+    Planar,
+    // Weighted mean of unit vectors on a sphere, projected back to its surface
+    Geodesic,
+}
+
+// Accumulates the center of any number of geometries. As with a single
+// geometry's centroid, only the parts with the highest dimension count:
+// points are left out once there's a line, and lines once there's a polygon.
+// Parts are weighted by their area, length, or count, so polygons with zero
+// area fall back to their rings, and lines with zero length to their points.
+pub struct Center {
+    method: Method,
+    // Weighted sums of positions and total weights, by dimension
+    sums: [[f64; 3]; 3],
+    weights: [f64; 3],
+}
+
+impl Center {
+    pub fn new(method: Method) -> Center {
+        Center {
+            method,
+            sums: [[0.0; 3]; 3],
+            weights: [0.0; 3],
+        }
+    }
+
+    fn push(&mut self, dimension: usize, position: [f64; 3], weight: f64) {
+        for (sum, p) in self.sums[dimension].iter_mut().zip(position.iter()) {
+            *sum += p * weight;
+        }
+        self.weights[dimension] += weight;
+    }
+
+    fn add_points(&mut self, coords: &[Coord<f64>]) {
+        for c in coords {
+            let position = match self.method {
+                Method::Planar => [c.x, c.y, 0.0],
+                Method::Geodesic => to_vector(*c),
+            };
+            self.push(0, position, 1.0);
+        }
+    }
+
+    fn add_line(&mut self, ls: &LineString<f64>) {
+        self.add_points(&ls.0);
+        for l in ls.lines() {
+            match self.method {
+                Method::Planar => {
+                    let length = (l.dx() * l.dx() + l.dy() * l.dy()).sqrt();
+                    let mid = [
+                        (l.start.x + l.end.x) / 2.0,
+                        (l.start.y + l.end.y) / 2.0,
+                        0.0,
+                    ];
+                    self.push(1, mid, length);
+                }
+                Method::Geodesic => {
+                    let (a, b) = (to_vector(l.start), to_vector(l.end));
+                    let angle = norm(cross(a, b)).atan2(dot(a, b));
+                    self.push(1, normalize(add(a, b)), angle);
+                }
+            }
+        }
+    }
+
+    fn add_polygon(&mut self, poly: &Polygon<f64>) {
+        self.add_line(poly.exterior());
+        for ring in poly.interiors() {
+            self.add_line(ring);
+        }
+        match self.method {
+            Method::Planar => {
+                let area = poly.unsigned_area();
+                if let Some(c) = poly.centroid().filter(|_| area > 0.0) {
+                    self.push(2, [c.x(), c.y(), 0.0], area);
+                }
+            }
+            Method::Geodesic => {
+                self.add_spherical_ring(poly.exterior(), 1.0);
+                for ring in poly.interiors() {
+                    self.add_spherical_ring(ring, -1.0);
+                }
+            }
+        }
+    }
+
+    // Splits a ring into a fan of spherical triangles, weighted by their
+    // signed areas so the parts of the fan outside the ring cancel out
+    fn add_spherical_ring(&mut self, ring: &LineString<f64>, sign: f64) {
+        let vectors: Vec<[f64; 3]> = ring.0.iter().map(|c| to_vector(*c)).collect();
+        if vectors.len() < 4 {
+            return;
+        }
+        let origin = vectors[0];
+        let triangles: Vec<([f64; 3], f64)> = vectors[1..]
+            .windows(2)
+            .map(|w| {
+                let (b, c) = (w[0], w[1]);
+                let excess = 2.0
+                    * dot(origin, cross(b, c))
+                        .atan2(1.0 + dot(origin, b) + dot(b, c) + dot(c, origin));
+                (normalize(add(add(origin, b), c)), excess)
+            })
+            .collect();
+        // Holes count against their polygon whichever way their rings wind
+        let total: f64 = triangles.iter().map(|(_, excess)| excess).sum();
+        let orientation = if total < 0.0 { -sign } else { sign };
+        for (center, excess) in triangles {
+            self.push(2, center, excess * orientation);
+        }
+    }
+
+    pub fn add(&mut self, geom: &Geometry<f64>) {
+        match geom {
+            Geometry::Point(p) => self.add_points(&[p.0]),
+            Geometry::MultiPoint(mp) => mp.0.iter().for_each(|p| self.add_points(&[p.0])),
+            Geometry::Line(l) => self.add_line(&LineString(vec![l.start, l.end])),
+            Geometry::LineString(ls) => self.add_line(ls),
+            Geometry::MultiLineString(mls) => mls.0.iter().for_each(|ls| self.add_line(ls)),
+            Geometry::Polygon(poly) => self.add_polygon(poly),
+            Geometry::MultiPolygon(mp) => mp.0.iter().for_each(|poly| self.add_polygon(poly)),
+            Geometry::Rect(r) => self.add_polygon(&r.to_polygon()),
+            Geometry::Triangle(t) => self.add_polygon(&t.to_polygon()),
+            Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| self.add(g)),
+        }
+    }
+
+    pub fn point(&self) -> Option<Point<f64>> {
+        let dimension = (0..3).rev().find(|d| self.weights[*d] > 0.0)?;
+        let sum = self.sums[dimension];
+        match self.method {
+            Method::Planar => {
+                let weight = self.weights[dimension];
+                Some(Point::new(sum[0] / weight, sum[1] / weight))
+            }
+            Method::Geodesic => {
+                // Inputs spread evenly around the globe have no center
+                if norm(sum) < 1e-12 * self.weights[dimension] {
+                    None
+                } else {
+                    Some(Point(from_vector(normalize(sum))))
+                }
+            }
+        }
+    }
+}
+
+pub fn geodesic_centroid(geom: &Geometry<f64>) -> Option<Point<f64>> {
+    let mut center = Center::new(Method::Geodesic);
+    center.add(geom);
+    center.point()
+}
+
+fn to_vector(c: Coord<f64>) -> [f64; 3] {
+    let (lon, lat) = (c.x.to_radians(), c.y.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn from_vector(v: [f64; 3]) -> Coord<f64> {
+    Coord {
+        x: v[1].atan2(v[0]).to_degrees(),
+        y: v[2].atan2((v[0] * v[0] + v[1] * v[1]).sqrt()).to_degrees(),
+    }
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let n = norm(v);
+    if n == 0.0 {
+        v
+    } else {
+        [v[0] / n, v[1] / n, v[2] / n]
+    }
+}
+
+// Midpoint of the widest span of a horizontal line across the middle of the
+// polygon, along with that width. Crossings count each edge's lower end but
+// not its upper one, so a line through a vertex is still crossed evenly.
+fn widest_span(poly: &Polygon<f64>) -> Option<(f64, Point<f64>)> {
+    let (min_y, max_y) = poly
+        .exterior()
+        .0
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), c| {
+            (min.min(c.y), max.max(c.y))
+        });
+    let y = (min_y + max_y) / 2.0;
+
+    let mut crossings: Vec<f64> = std::iter::once(poly.exterior())
+        .chain(poly.interiors().iter())
+        .flat_map(|ring| ring.lines())
+        .filter(|l| (l.start.y > y) != (l.end.y > y))
+        .map(|l| l.start.x + (y - l.start.y) * (l.end.x - l.start.x) / (l.end.y - l.start.y))
+        .collect();
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    crossings
+        .chunks(2)
+        .filter(|span| span.len() == 2)
+        .map(|span| (span[1] - span[0], Point::new((span[0] + span[1]) / 2.0, y)))
+        .fold(
+            None,
+            |widest: Option<(f64, Point<f64>)>, (width, p)| match widest {
+                Some((w, _)) if w >= width => widest,
+                _ => Some((width, p)),
+            },
+        )
+}
+
+fn polygons(geom: &Geometry<f64>, out: &mut Vec<Polygon<f64>>) {
+    match geom {
+        Geometry::Polygon(poly) => out.push(poly.clone()),
+        Geometry::MultiPolygon(mp) => out.extend(mp.0.iter().cloned()),
+        Geometry::Rect(r) => out.push(r.to_polygon()),
+        Geometry::Triangle(t) => out.push(t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| polygons(g, out)),
+        _ => (),
+    }
+}
+
+// A point guaranteed to fall on the geometry, unlike a centroid, which can
+// fall outside of a curved or hollow shape. Polygons give a point inside
+// their widest part; other geometries give their vertex nearest the centroid.
+pub fn point_on_surface(geom: &Geometry<f64>) -> Option<Point<f64>> {
+    let mut polys = vec![];
+    polygons(geom, &mut polys);
+    let widest = polys.iter().filter_map(widest_span).fold(
+        None,
+        |widest: Option<(f64, Point<f64>)>, (width, p)| match widest {
+            Some((w, _)) if w >= width => widest,
+            _ => Some((width, p)),
+        },
+    );
+    if let Some((_, p)) = widest {
+        return Some(p);
+    }
+
+    let mut center = Center::new(Method::Planar);
+    center.add(geom);
+    let c = center.point()?;
+    geo::algorithm::coords_iter::CoordsIter::coords_iter(geom)
+        .map(Point)
+        .min_by(|a, b| {
+            let da = (a.x() - c.x()).hypot(a.y() - c.y());
+            let db = (b.x() - c.x()).hypot(b.y() - c.y());
+            da.partial_cmp(&db).unwrap()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{geodesic_centroid, point_on_surface, Center, Method};
+    use geo::algorithm::contains::Contains;
+    use geo_types::{line_string, point, polygon, Geometry};
+
+    fn c_shape() -> Geometry<f64> {
+        Geometry::Polygon(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 3.0, y: 0.0),
+            (x: 3.0, y: 1.0),
+            (x: 1.0, y: 1.0),
+            (x: 1.0, y: 2.0),
+            (x: 3.0, y: 2.0),
+            (x: 3.0, y: 3.0),
+            (x: 0.0, y: 3.0),
+            (x: 0.0, y: 0.0),
+        ])
+    }
+
+    #[test]
+    fn test_point_on_surface() {
+        let c = c_shape();
+        let p = point_on_surface(&c).unwrap();
+        match &c {
+            Geometry::Polygon(poly) => assert!(poly.contains(&p)),
+            _ => unreachable!(),
+        }
+        // The C's centroid falls in its gap
+        assert_eq!(point!(x: 0.5, y: 1.5), p);
+
+        let line = Geometry::LineString(
+            line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0), (x: 10.0, y: 0.0)],
+        );
+        assert_eq!(Some(point!(x: 1.0, y: 1.0)), point_on_surface(&line));
+    }
+
+    #[test]
+    fn test_geodesic_centroid() {
+        // Points on either side of the antimeridian center on it, not on the prime meridian
+        let points =
+            Geometry::MultiPoint(vec![point!(x: 170.0, y: 0.0), point!(x: -170.0, y: 0.0)].into());
+        let c = geodesic_centroid(&points).unwrap();
+        assert!((c.x().abs() - 180.0).abs() < 1e-9);
+        assert!(c.y().abs() < 1e-9);
+
+        // A small square is centered much as it would be on a plane
+        let square = Geometry::Polygon(
+            polygon![(x: 0.0, y: 0.0), (x: 1.0, y: 0.0), (x: 1.0, y: 1.0), (x: 0.0, y: 1.0), (x: 0.0, y: 0.0)],
+        );
+        let c = geodesic_centroid(&square).unwrap();
+        assert!((c.x() - 0.5).abs() < 1e-3);
+        assert!((c.y() - 0.5).abs() < 1e-3);
+
+        // A hole pulls the center away from it
+        let holed = Geometry::Polygon(polygon![
+            exterior: [(x: 0.0, y: 0.0), (x: 4.0, y: 0.0), (x: 4.0, y: 4.0), (x: 0.0, y: 4.0), (x: 0.0, y: 0.0)],
+            interiors: [[(x: 0.0, y: 0.0), (x: 2.0, y: 0.0), (x: 2.0, y: 4.0), (x: 0.0, y: 4.0), (x: 0.0, y: 0.0)]],
+        ]);
+        let c = geodesic_centroid(&holed).unwrap();
+        assert!((c.x() - 3.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_center_of_many() {
+        let mut center = Center::new(Method::Planar);
+        center.add(&Geometry::Point(point!(x: 100.0, y: 100.0)));
+        center.add(&Geometry::Polygon(polygon![(x: 0.0, y: 0.0), (x: 2.0, y: 0.0), (x: 2.0, y: 2.0), (x: 0.0, y: 2.0), (x: 0.0, y: 0.0)]));
+        center.add(&Geometry::Polygon(polygon![(x: 4.0, y: 0.0), (x: 6.0, y: 0.0), (x: 6.0, y: 2.0), (x: 4.0, y: 2.0), (x: 4.0, y: 0.0)]));
+        assert_eq!(Some(point!(x: 3.0, y: 1.0)), center.point());
+
+        let mut points = Center::new(Method::Planar);
+        points.add(&Geometry::Point(point!(x: 0.0, y: 0.0)));
+        points.add(&Geometry::Point(point!(x: 2.0, y: 4.0)));
+        assert_eq!(Some(point!(x: 1.0, y: 2.0)), points.point());
+        assert_eq!(None, Center::new(Method::Geodesic).point());
+    }
+}
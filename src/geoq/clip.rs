@@ -0,0 +1,240 @@
+use crate::geoq::{
+    bbox,
+    overlay::{self, Operation},
+};
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect,
+};
+
+fn covers(rect: &Rect<f64>, c: Coord<f64>) -> bool {
+    rect.min().x <= c.x && c.x <= rect.max().x && rect.min().y <= c.y && c.y <= rect.max().y
+}
+
+// Liang-Barsky: the part of a segment within the box, if any
+fn clip_segment(rect: &Rect<f64>, l: &Line<f64>) -> Option<Line<f64>> {
+    let d = l.end - l.start;
+    let (mut t0, mut t1) = (0.0, 1.0);
+    let edges = [
+        (-d.x, l.start.x - rect.min().x),
+        (d.x, rect.max().x - l.start.x),
+        (-d.y, l.start.y - rect.min().y),
+        (d.y, rect.max().y - l.start.y),
+    ];
+    for &(p, q) in edges.iter() {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = f64::max(t0, t);
+            } else {
+                t1 = f64::min(t1, t);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    let at = |t: f64| {
+        if t == 0.0 {
+            l.start
+        } else if t == 1.0 {
+            l.end
+        } else {
+            l.start + d * t
+        }
+    };
+    Some(Line::new(at(t0), at(t1)))
+}
+
+fn clip_line_string(rect: &Rect<f64>, ls: &LineString<f64>) -> Vec<LineString<f64>> {
+    let mut clipped: Vec<Vec<Coord<f64>>> = Vec::new();
+    for segment in ls.lines() {
+        let piece = match clip_segment(rect, &segment) {
+            Some(piece) => piece,
+            None => continue,
+        };
+        match clipped.last_mut() {
+            Some(line) if line[line.len() - 1] == piece.start && piece.start == segment.start => {
+                line.push(piece.end)
+            }
+            _ => clipped.push(vec![piece.start, piece.end]),
+        }
+    }
+    clipped
+        .into_iter()
+        .filter(|line| line.iter().any(|c| *c != line[0]))
+        .map(LineString)
+        .collect()
+}
+
+// Polygons are intersected with the box as a polygon, so concave polygons
+// which leave and re-enter it are cut into separate parts
+fn clip_polygons(rect: &Rect<f64>, polys: Vec<Polygon<f64>>) -> Option<Geometry<f64>> {
+    let box_poly = MultiPolygon(vec![rect.to_polygon()]);
+    let mut clipped = overlay::overlay(&MultiPolygon(polys), &box_poly, Operation::Intersection);
+    match clipped.0.len() {
+        0 => None,
+        1 => Some(Geometry::Polygon(clipped.0.remove(0))),
+        _ => Some(Geometry::MultiPolygon(clipped)),
+    }
+}
+
+fn multi<T, F>(parts: Vec<T>, wrap: F) -> Option<Geometry<f64>>
+where
+    F: Fn(Vec<T>) -> Geometry<f64>,
+{
+    if parts.is_empty() {
+        None
+    } else {
+        Some(wrap(parts))
+    }
+}
+
+// Clips a geometry to a bounding box, giving None if nothing falls within it.
+// Single geometries which are cut into several pieces become Multi geometries.
+pub fn clip(geom: &Geometry<f64>, rect: &Rect<f64>) -> Option<Geometry<f64>> {
+    let bounds = bbox::bbox(geom);
+    if !bbox::intersects(rect, &bounds) {
+        return None;
+    }
+    if bbox::contains(rect, &bounds) {
+        return Some(geom.clone());
+    }
+
+    match geom {
+        Geometry::Point(p) if covers(rect, p.0) => Some(Geometry::Point(*p)),
+        Geometry::Point(_) => None,
+        Geometry::MultiPoint(mp) => {
+            let points: Vec<Point<f64>> =
+                mp.0.iter().filter(|p| covers(rect, p.0)).cloned().collect();
+            multi(points, |points| Geometry::MultiPoint(MultiPoint(points)))
+        }
+        Geometry::Line(l) => clip_segment(rect, l)
+            .filter(|l| l.start != l.end)
+            .map(Geometry::Line),
+        Geometry::LineString(ls) => {
+            let mut lines = clip_line_string(rect, ls);
+            match lines.len() {
+                0 => None,
+                1 => Some(Geometry::LineString(lines.remove(0))),
+                _ => Some(Geometry::MultiLineString(MultiLineString(lines))),
+            }
+        }
+        Geometry::MultiLineString(mls) => {
+            let lines: Vec<LineString<f64>> = mls
+                .0
+                .iter()
+                .flat_map(|ls| clip_line_string(rect, ls))
+                .collect();
+            multi(lines, |lines| {
+                Geometry::MultiLineString(MultiLineString(lines))
+            })
+        }
+        Geometry::Polygon(poly) => clip_polygons(rect, vec![poly.clone()]),
+        Geometry::MultiPolygon(mp) => clip_polygons(rect, mp.0.clone()),
+        Geometry::Rect(r) => clip_polygons(rect, vec![r.to_polygon()]),
+        Geometry::Triangle(t) => clip_polygons(rect, vec![t.to_polygon()]),
+        Geometry::GeometryCollection(gc) => {
+            let geoms: Vec<Geometry<f64>> = gc.0.iter().filter_map(|g| clip(g, rect)).collect();
+            multi(geoms, |geoms| {
+                Geometry::GeometryCollection(GeometryCollection(geoms))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clip;
+    use crate::geoq::validate;
+    use geo::algorithm::area::Area;
+    use geo_types::{line_string, point, polygon, Coord, Geometry, MultiLineString, Rect};
+
+    fn unit_box() -> Rect<f64> {
+        Rect::new(Coord { x: 0.0, y: 0.0 }, Coord { x: 1.0, y: 1.0 })
+    }
+
+    #[test]
+    fn test_clipping_points_and_lines() {
+        let inside = Geometry::Point(point!(x: 0.5, y: 0.5));
+        assert_eq!(Some(inside.clone()), clip(&inside, &unit_box()));
+        assert_eq!(
+            None,
+            clip(&Geometry::Point(point!(x: 2.0, y: 0.5)), &unit_box())
+        );
+
+        let line = Geometry::LineString(
+            line_string![(x: -1.0, y: 0.5), (x: 0.5, y: 0.5), (x: 0.5, y: 2.0)],
+        );
+        assert_eq!(
+            Some(Geometry::LineString(
+                line_string![(x: 0.0, y: 0.5), (x: 0.5, y: 0.5), (x: 0.5, y: 1.0)]
+            )),
+            clip(&line, &unit_box())
+        );
+
+        let zigzag = Geometry::LineString(
+            line_string![(x: 0.25, y: 0.5), (x: 0.25, y: 2.0), (x: 0.75, y: 2.0), (x: 0.75, y: 0.5)],
+        );
+        assert_eq!(
+            Some(Geometry::MultiLineString(MultiLineString(vec![
+                line_string![(x: 0.25, y: 0.5), (x: 0.25, y: 1.0)],
+                line_string![(x: 0.75, y: 1.0), (x: 0.75, y: 0.5)],
+            ]))),
+            clip(&zigzag, &unit_box())
+        );
+    }
+
+    #[test]
+    fn test_clipping_polygons() {
+        let poly = Geometry::Polygon(polygon![
+            exterior: [(x: -1.0, y: -1.0), (x: 0.5, y: -1.0), (x: 0.5, y: 2.0), (x: -1.0, y: 2.0), (x: -1.0, y: -1.0)],
+            interiors: [[(x: -0.5, y: -0.5), (x: 0.25, y: -0.5), (x: 0.25, y: 0.25), (x: -0.5, y: 0.25), (x: -0.5, y: -0.5)]],
+        ]);
+        assert_eq!(
+            Some(Geometry::Polygon(polygon![
+                (x: 0.5, y: 0.0),
+                (x: 0.5, y: 1.0),
+                (x: 0.0, y: 1.0),
+                (x: 0.0, y: 0.25),
+                (x: 0.25, y: 0.25),
+                (x: 0.25, y: 0.0),
+                (x: 0.5, y: 0.0),
+            ])),
+            clip(&poly, &unit_box())
+        );
+
+        let outside =
+            Geometry::Polygon(polygon![(x: 2.0, y: 0.0), (x: 3.0, y: 0.0), (x: 3.0, y: 1.0)]);
+        assert_eq!(None, clip(&outside, &unit_box()));
+    }
+
+    #[test]
+    fn test_clipping_concave_polygons() {
+        // A U shape whose arms reach up out of the box and back in
+        let u = Geometry::Polygon(polygon![
+            (x: 0.2, y: 0.5),
+            (x: 0.2, y: 2.0),
+            (x: 0.4, y: 2.0),
+            (x: 0.4, y: 1.5),
+            (x: 0.6, y: 1.5),
+            (x: 0.6, y: 2.0),
+            (x: 0.8, y: 2.0),
+            (x: 0.8, y: 0.5),
+            (x: 0.2, y: 0.5),
+        ]);
+        let u_top = Rect::new(Coord { x: 0.0, y: 1.75 }, Coord { x: 1.0, y: 3.0 });
+        match clip(&u, &u_top) {
+            Some(Geometry::MultiPolygon(mp)) => {
+                assert_eq!(2, mp.0.len());
+                assert!((mp.unsigned_area() - 0.1).abs() < 1e-9);
+                assert!(validate::issues(&Geometry::MultiPolygon(mp)).is_empty());
+            }
+            other => panic!("Expected a multipolygon, got {:?}", other),
+        }
+    }
+}
@@ -6,6 +6,10 @@ pub fn run(matches: &ArgMatches) -> Result<(), Error> {
     let embed = matches.is_present("embed");
     let all = matches.is_present("all");
 
+    if matches.is_present("antimeridian") {
+        return antimeridian(embed, all);
+    }
+
     if all {
         let mut bbox: Option<Rect<f64>> = None;
 
@@ -47,3 +51,44 @@ pub fn run(matches: &ArgMatches) -> Result<(), Error> {
         })
     }
 }
+
+fn antimeridian_output(bbox: &[f64; 4]) -> String {
+    let geom = bbox::antimeridian_bbox_geometry(bbox);
+    let gj = geojson::Geometry::new(geojson::Value::from(&geom));
+    serde_json::to_string(&gj).unwrap()
+}
+
+fn antimeridian(embed: bool, all: bool) -> Result<(), Error> {
+    if all {
+        let mut bbox: Option<[f64; 4]> = None;
+
+        reader::for_entity(|e| {
+            let curr = bbox::antimeridian_bbox(&e.geom());
+            bbox = match bbox {
+                Some(prev) => Some(bbox::merge_antimeridian(&prev, &curr)),
+                None => Some(curr),
+            };
+            Ok(())
+        })?;
+
+        match bbox {
+            None => Err(Error::NoInputGiven),
+            Some(bbox) => {
+                println!("{}", antimeridian_output(&bbox));
+                Ok(())
+            }
+        }
+    } else {
+        par::for_stdin_entity(move |e| {
+            let bbox = bbox::antimeridian_bbox(&e.geom());
+
+            if embed {
+                let mut feat = e.geojson_feature();
+                feat.bbox = Some(bbox.to_vec());
+                Ok(vec![serde_json::to_string(&feat).unwrap()])
+            } else {
+                Ok(vec![antimeridian_output(&bbox)])
+            }
+        })
+    }
+}
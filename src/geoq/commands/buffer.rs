@@ -0,0 +1,58 @@
+use crate::geoq::{
+    buffer::{self, Style},
+    error::Error,
+    geodesic::Unit,
+    par,
+};
+use clap::ArgMatches;
+
+fn read_distance(matches: &ArgMatches) -> Result<f64, Error> {
+    let arg = matches.value_of("distance").unwrap();
+    let distance: f64 = arg.parse().map_err(|_| {
+        Error::InvalidNumberFormat(format!(
+            "Invalid distance: {} - must be a number, e.g. 100 or -2.5.",
+            arg
+        ))
+    })?;
+    match matches.value_of("units") {
+        Some(units) => Ok(distance / units.parse::<Unit>()?.length(1.0)?),
+        None => Ok(distance),
+    }
+}
+
+fn read_style(matches: &ArgMatches) -> Result<Style, Error> {
+    let mut style = Style::default();
+    if let Some(arg) = matches.value_of("segments") {
+        style.segments = match arg.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                return Err(Error::InvalidNumberFormat(format!(
+                    "Invalid segments: {} - must be a positive integer, e.g. 8.",
+                    arg
+                )))
+            }
+        };
+    }
+    if let Some(cap) = matches.value_of("cap") {
+        style.cap = cap.parse()?;
+    }
+    if let Some(join) = matches.value_of("join") {
+        style.join = join.parse()?;
+    }
+    Ok(style)
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let meters = read_distance(matches)?;
+    let style = read_style(matches)?;
+
+    par::for_stdin_entity(move |e| {
+        let buffered = match buffer::buffer(&e.geom(), meters, &style) {
+            Some(geom) => geom,
+            None => return Ok(vec![]),
+        };
+        let gj_geom = geojson::Geometry::new(geojson::Value::from(&buffered));
+        let feature = e.with_geometry(gj_geom);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
@@ -1,4 +1,11 @@
-use crate::geoq::{self, error::Error, reader};
+use crate::geoq::{
+    self,
+    centroid::{Center, Method},
+    entity::Entity,
+    error::Error,
+    reader,
+};
+use clap::ArgMatches;
 use geo_types::{Geometry, Point};
 use geojson;
 use serde_json;
@@ -9,11 +16,74 @@ fn gj_point(point: Point<f64>) -> String {
     serde_json::to_string(&gj).unwrap()
 }
 
-pub fn run() -> Result<(), Error> {
+fn gj_feature(point: Point<f64>, e: Option<&Entity>) -> String {
+    let geom = Geometry::Point(point);
+    let gj_geom = geojson::Geometry::new(geojson::Value::from(&geom));
+    let feature = match e {
+        Some(e) => e.with_geometry(gj_geom),
+        None => geojson::Feature {
+            bbox: None,
+            geometry: Some(gj_geom),
+            id: None,
+            properties: Some(serde_json::Map::new()),
+            foreign_members: None,
+        },
+    };
+    serde_json::to_string(&feature).unwrap()
+}
+
+fn center(g: &Geometry<f64>, method: Method) -> Option<Point<f64>> {
+    let mut center = Center::new(method);
+    center.add(g);
+    center.point()
+}
+
+fn point(g: &Geometry<f64>, matches: &ArgMatches) -> Option<Point<f64>> {
+    if matches.is_present("point-on-surface") {
+        geoq::centroid::point_on_surface(g)
+    } else if matches.is_present("geodesic") {
+        center(g, Method::Geodesic)
+    } else {
+        geoq::centroid::centroid(g).or_else(|| center(g, Method::Planar))
+    }
+}
+
+fn all(matches: &ArgMatches) -> Result<(), Error> {
+    let method = if matches.is_present("geodesic") {
+        Method::Geodesic
+    } else {
+        Method::Planar
+    };
+    let mut center = Center::new(method);
+    let mut count = 0;
+    reader::for_entity(|e| {
+        center.add(&e.geom());
+        count += 1;
+        Ok(())
+    })?;
+
+    if count == 0 {
+        return Err(Error::NoInputGiven);
+    }
+    match center.point() {
+        Some(point) if matches.is_present("geojson") => println!("{}", gj_feature(point, None)),
+        Some(point) => println!("{}", gj_point(point)),
+        None => eprintln!("Could not calculate centroid for inputs"),
+    }
+    Ok(())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    if matches.is_present("all") {
+        return all(matches);
+    }
+
+    let geojson = matches.is_present("geojson");
     reader::for_entity(|e| {
         let raw = e.raw();
         let g = e.geom();
-        match geoq::centroid::centroid(&g) {
+        match point(&g, matches) {
+            Some(point) if geojson => println!("{}", gj_feature(point, Some(&e))),
             Some(point) => println!("{}", gj_point(point)),
             None => eprintln!("Could not calculate centroid for geom: {}", raw),
         }
@@ -0,0 +1,16 @@
+use crate::geoq::{bbox, clip, error::Error, par};
+use clap::ArgMatches;
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let rect = bbox::parse(matches.value_of("bbox").unwrap())?;
+
+    par::for_stdin_entity(move |e| {
+        let clipped = match clip::clip(&e.geom(), &rect) {
+            Some(geom) => geom,
+            None => return Ok(vec![]),
+        };
+        let gj_geom = geojson::Geometry::new(geojson::Value::from(&clipped));
+        let feature = e.with_geometry(gj_geom);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
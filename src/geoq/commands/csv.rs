@@ -0,0 +1,346 @@
+use crate::geoq::{
+    commands::json::find_geometry,
+    csv::{self, CsvRecords},
+    entity::Entity,
+    error::Error,
+    par,
+    reader::Reader,
+    rejects,
+};
+use clap::ArgMatches;
+use geo_types::{Geometry, Point};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::io;
+use std::str::FromStr;
+
+enum GeometryColumns {
+    // Check each row for the same patterns as json munge
+    Detect,
+    LatLon(usize, usize),
+    Wkt(usize),
+}
+
+struct Layout {
+    header: Vec<String>,
+    delimiter: char,
+    geometry: GeometryColumns,
+}
+
+fn column_index(header: &[String], name: &str) -> Result<usize, Error> {
+    header.iter().position(|h| h == name).ok_or_else(|| {
+        Error::InvalidInput(format!(
+            "No column named {} in CSV header: {}",
+            name,
+            header.join(",")
+        ))
+    })
+}
+
+fn parse_coord(value: &str, column: &str) -> Result<f64, Error> {
+    value.trim().parse::<f64>().map_err(|_| {
+        Error::InvalidNumberFormat(format!("Expected number in column {}: {}", column, value))
+    })
+}
+
+fn parse_wkt(value: &str) -> Result<Geometry<f64>, Error> {
+    let wkt = wkt::Wkt::from_str(value).map_err(|e| Error::InvalidWkt(e.to_string()))?;
+    wkt.try_into()
+        .map_err(|_| Error::InvalidWkt(format!("Unsupported WKT geometry: {}", value)))
+}
+
+fn row_geometry(layout: &Layout, values: &[String]) -> Result<(Geometry<f64>, Vec<usize>), Error> {
+    match layout.geometry {
+        GeometryColumns::LatLon(lat_i, lon_i) => {
+            let lat = parse_coord(&values[lat_i], &layout.header[lat_i])?;
+            let lon = parse_coord(&values[lon_i], &layout.header[lon_i])?;
+            Ok((Geometry::Point(Point::new(lon, lat)), vec![lat_i, lon_i]))
+        }
+        GeometryColumns::Wkt(i) => Ok((parse_wkt(&values[i])?, vec![i])),
+        GeometryColumns::Detect => {
+            // Header names are matched case-insensitively
+            let row: Map<String, Value> = layout
+                .header
+                .iter()
+                .zip(values)
+                .map(|(h, v)| (h.trim().to_lowercase(), csv::typed_value(v)))
+                .collect();
+            match find_geometry(&row) {
+                Some((geom, keys)) => {
+                    let cols = (0..layout.header.len())
+                        .filter(|i| keys.contains(&layout.header[*i].trim().to_lowercase().as_str()))
+                        .collect();
+                    Ok((geom, cols))
+                }
+                None => Err(Error::InvalidInput(
+                    "Couldn't find a geometry in CSV row. Use --lat-col and --lon-col or --wkt-col to choose geometry columns.".to_string(),
+                )),
+            }
+        }
+    }
+}
+
+fn feature(layout: &Layout, record: &str) -> Result<String, Error> {
+    let values = csv::fields(record, layout.delimiter)?;
+    if values.len() != layout.header.len() {
+        return Err(Error::InvalidInput(format!(
+            "Expected {} CSV columns but found {}",
+            layout.header.len(),
+            values.len()
+        )));
+    }
+
+    let (geom, geom_cols) = row_geometry(layout, &values)?;
+    let properties: Map<String, Value> = layout
+        .header
+        .iter()
+        .zip(values.iter())
+        .enumerate()
+        .filter(|(i, _)| !geom_cols.contains(i))
+        .map(|(_, (h, v))| (h.clone(), csv::typed_value(v)))
+        .collect();
+
+    let gj_geom = geojson::Geometry::new(geojson::Value::from(&geom));
+    let geojson = json!({
+        "type": "Feature",
+        "properties": Value::Object(properties),
+        "geometry": gj_geom
+    });
+    Ok(serde_json::to_string(&geojson)?)
+}
+
+fn read(matches: &ArgMatches) -> Result<(), Error> {
+    let stdin = io::stdin();
+    let mut stdin_reader = stdin.lock();
+    let mut records = CsvRecords::new(&mut stdin_reader);
+    let given_delimiter = match matches.value_of("delimiter") {
+        Some(d) => Some(csv::parse_delimiter(d)?),
+        None => None,
+    };
+    if let Some(d) = given_delimiter {
+        records.set_delimiter(d);
+    }
+
+    let header_row = match records.next() {
+        Some((_, row)) => row.trim_start_matches('\u{feff}').to_string(),
+        None => return Err(Error::NoInputGiven),
+    };
+    let delimiter = given_delimiter.unwrap_or_else(|| csv::detect_delimiter(&header_row));
+    records.set_delimiter(delimiter);
+    let header = csv::fields(&header_row, delimiter)?;
+
+    let geometry = match (
+        matches.value_of("lat-col"),
+        matches.value_of("lon-col"),
+        matches.value_of("wkt-col"),
+    ) {
+        (Some(lat), Some(lon), None) => {
+            GeometryColumns::LatLon(column_index(&header, lat)?, column_index(&header, lon)?)
+        }
+        (None, None, Some(wkt)) => GeometryColumns::Wkt(column_index(&header, wkt)?),
+        _ => GeometryColumns::Detect,
+    };
+
+    let layout = Layout {
+        header,
+        delimiter,
+        geometry,
+    };
+    par::for_record_par(records, move |record| match feature(&layout, &record) {
+        Ok(f) => Ok(vec![f]),
+        Err(e) => Err((e, record)),
+    })
+}
+
+#[derive(Debug, PartialEq)]
+enum GeometryOutput {
+    Wkt,
+    LatLon,
+}
+
+impl GeometryOutput {
+    fn columns(&self) -> Vec<String> {
+        match self {
+            GeometryOutput::Wkt => vec!["wkt".to_string()],
+            GeometryOutput::LatLon => vec!["lat".to_string(), "lon".to_string()],
+        }
+    }
+}
+
+// Union of property keys, in the order they're first seen
+fn property_columns<'a, I: Iterator<Item = &'a Entity>>(entities: I) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut columns = Vec::new();
+    for e in entities {
+        for key in e.geojson_properties().keys() {
+            if seen.insert(key.clone()) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    columns
+}
+
+fn row(e: &Entity, geometry: &GeometryOutput, columns: &[String]) -> Result<Vec<String>, Error> {
+    let mut fields = match geometry {
+        GeometryOutput::Wkt => vec![e.wkt_string()],
+        GeometryOutput::LatLon => match e.geom() {
+            Geometry::Point(p) => vec![p.y().to_string(), p.x().to_string()],
+            _ => return Err(Error::PointRequired),
+        },
+    };
+    let props = e.geojson_properties();
+    fields.extend(
+        columns
+            .iter()
+            .map(|c| props.get(c).map(csv::field_text).unwrap_or_default()),
+    );
+    Ok(fields)
+}
+
+fn write(matches: &ArgMatches) -> Result<(), Error> {
+    let delimiter = match matches.value_of("delimiter") {
+        Some(d) => csv::parse_delimiter(d)?,
+        None => ',',
+    };
+    let geometry = match matches.value_of("geometry") {
+        Some("latlon") => GeometryOutput::LatLon,
+        _ => GeometryOutput::Wkt,
+    };
+    let sample_size = match matches.value_of("sample") {
+        Some(n) => n.parse::<usize>().map_err(|_| {
+            Error::InvalidNumberFormat(format!("Expected positive integer for --sample: {}", n))
+        })?,
+        None => 1000,
+    };
+
+    let stdin = io::stdin();
+    let mut stdin_reader = stdin.lock();
+    let mut reader = Reader::new(&mut stdin_reader);
+
+    // Without a column list, buffer a sample of entities to find their properties
+    let mut sample: Vec<(usize, Entity)> = Vec::new();
+    let columns: Vec<String> = match matches.value_of("columns") {
+        Some(cols) => cols.split(',').map(|c| c.to_string()).collect(),
+        None => {
+            while sample.len() < sample_size {
+                match reader.next() {
+                    Some(e) => sample.push((reader.line(), e?)),
+                    None => break,
+                }
+            }
+            property_columns(sample.iter().map(|(_, e)| e))
+        }
+    };
+
+    let mut header = geometry.columns();
+    header.extend(columns.iter().cloned());
+    println!("{}", csv::record(&header, delimiter));
+
+    let write_row = |line: usize, e: Entity| -> Result<(), Error> {
+        match row(&e, &geometry, &columns) {
+            Ok(fields) => println!("{}", csv::record(&fields, delimiter)),
+            Err(err) => rejects::reject(line, &e.raw(), err)?,
+        }
+        Ok(())
+    };
+    for (line, e) in sample {
+        write_row(line, e)?;
+    }
+    while let Some(e) = reader.next() {
+        write_row(reader.line(), e?)?;
+    }
+    Ok(())
+}
+
+pub fn run(m: &ArgMatches) -> Result<(), Error> {
+    match m.subcommand() {
+        ("read", Some(m)) => read(m),
+        ("write", Some(m)) => write(m),
+        _ => Err(Error::UnknownCommand),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{feature, property_columns, row, GeometryColumns, GeometryOutput, Layout};
+    use crate::geoq::{entity, input};
+
+    fn layout(header: &[&str], geometry: GeometryColumns) -> Layout {
+        Layout {
+            header: header.iter().map(|h| h.to_string()).collect(),
+            delimiter: ',',
+            geometry,
+        }
+    }
+
+    #[test]
+    fn detects_lat_lon_columns() {
+        let l = layout(
+            &["Name", "Latitude", "LNG", "count"],
+            GeometryColumns::Detect,
+        );
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[34.0,12.0],"type":"Point"},"properties":{"Name":"pizza","count":3},"type":"Feature"}"#,
+            feature(&l, "pizza,12,34,3").unwrap()
+        );
+    }
+
+    #[test]
+    fn detects_wkt_column() {
+        let l = layout(&["id", "wkt"], GeometryColumns::Detect);
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[[1.0,2.0],[3.0,4.0]],"type":"LineString"},"properties":{"id":"007"},"type":"Feature"}"#,
+            feature(&l, "007,\"LINESTRING (1 2, 3 4)\"").unwrap()
+        );
+    }
+
+    #[test]
+    fn uses_given_columns() {
+        let l = layout(&["y", "x", "lat"], GeometryColumns::LatLon(0, 1));
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[34.0,12.0],"type":"Point"},"properties":{"lat":"n/a"},"type":"Feature"}"#,
+            feature(&l, "12,34,n/a").unwrap()
+        );
+        let l = layout(&["shape", "id"], GeometryColumns::Wkt(0));
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[1.0,2.0],"type":"Point"},"properties":{"id":1},"type":"Feature"}"#,
+            feature(&l, "POINT (1 2),1").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let l = layout(&["lat", "lon"], GeometryColumns::Detect);
+        assert!(feature(&l, "12,34,56").is_err());
+        assert!(feature(&l, "12,pizza").is_err());
+        let l = layout(&["lat", "lon"], GeometryColumns::LatLon(0, 1));
+        assert!(feature(&l, "12,pizza").is_err());
+    }
+
+    #[test]
+    fn writes_rows() {
+        let entities: Vec<_> = vec![
+            r#"{"type":"Feature","properties":{"b":1,"a":"x,y"},"geometry":{"type":"Point","coordinates":[34,12]}}"#,
+            r#"{"type":"Feature","properties":{"c":null},"geometry":{"type":"Point","coordinates":[35,13]}}"#,
+            "9q5",
+        ]
+        .into_iter()
+        .flat_map(|l| entity::from_input(input::read_line(l.to_string()).unwrap()).unwrap())
+        .collect();
+
+        let columns = property_columns(entities.iter());
+        assert_eq!(vec!["a", "b", "c"], columns);
+
+        assert_eq!(
+            vec!["12", "34", "x,y", "1", ""],
+            row(&entities[0], &GeometryOutput::LatLon, &columns).unwrap()
+        );
+        assert_eq!(
+            vec!["POINT(35 13)", "", "", ""],
+            row(&entities[1], &GeometryOutput::Wkt, &columns).unwrap()
+        );
+        assert!(row(&entities[2], &GeometryOutput::LatLon, &columns).is_err());
+    }
+}
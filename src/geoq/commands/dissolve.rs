@@ -0,0 +1,90 @@
+use crate::geoq::{error::Error, overlay, reader};
+use clap::ArgMatches;
+use geo_types::{Geometry, MultiPolygon};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// Union pending pieces in batches, so a large group never holds every input at once
+const BATCH_SIZE: usize = 64;
+
+struct Group {
+    value: Value,
+    merged: MultiPolygon<f64>,
+    pending: Vec<MultiPolygon<f64>>,
+}
+
+impl Group {
+    fn add(&mut self, polygons: MultiPolygon<f64>) {
+        self.pending.push(polygons);
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let mut pieces = std::mem::take(&mut self.pending);
+        pieces.push(std::mem::replace(&mut self.merged, MultiPolygon(vec![])));
+        self.merged = overlay::union_all(pieces);
+    }
+}
+
+fn output(mut group: Group, key: Option<&str>) {
+    group.flush();
+    if group.merged.0.is_empty() {
+        return;
+    }
+    let geom = match group.merged.0.len() {
+        1 => Geometry::Polygon(group.merged.0.remove(0)),
+        _ => Geometry::MultiPolygon(group.merged),
+    };
+    let mut properties = Map::new();
+    if let Some(key) = key {
+        properties.insert(key.to_string(), group.value);
+    }
+    let feature = geojson::Feature {
+        bbox: None,
+        geometry: Some(geojson::Geometry::new(geojson::Value::from(&geom))),
+        id: None,
+        properties: Some(properties),
+        foreign_members: None,
+    };
+    println!("{}", serde_json::to_string(&feature).unwrap());
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let key = matches.value_of("group-by");
+
+    // Groups are output in the order their first input was seen
+    let mut groups: Vec<Group> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    reader::for_entity(|e| {
+        let polygons = overlay::polygons(&e.geom());
+        if polygons.0.is_empty() {
+            return Ok(());
+        }
+        let value = match key {
+            Some(key) => e.geojson_properties().remove(key).unwrap_or(Value::Null),
+            None => Value::Null,
+        };
+        let id = serde_json::to_string(&value).unwrap();
+        let position = *positions.entry(id).or_insert_with(|| {
+            groups.push(Group {
+                value,
+                merged: MultiPolygon(vec![]),
+                pending: vec![],
+            });
+            groups.len() - 1
+        });
+        groups[position].add(polygons);
+        Ok(())
+    })?;
+
+    if groups.is_empty() {
+        return Err(Error::NoInputGiven);
+    }
+    for group in groups {
+        output(group, key);
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::geoq::{error::Error, fgb, reader::Reader};
+use crate::geoq::{bbox, error::Error, fgb, reader::Reader};
 use clap::ArgMatches;
 use flatgeobuf::FgbReader;
 use geozero::GeozeroDatasource;
@@ -44,28 +44,19 @@ use geozero::ToJson;
 fn read(path: &str, bbox: Option<&str>) -> Result<(), Error> {
     let mut file = BufReader::new(File::open(path)?);
     let fgb = FgbReader::open(&mut file)?;
+    let has_z = fgb.header().has_z();
 
-    let mut fgb = if let Some(bbox) = bbox {
-        let parts: Vec<f64> = bbox
-            .split(",")
-            .map(|num| {
-                num.parse::<f64>()
-                    .expect("Invalid bbox format -- should be 4 comma-sepparated numbers")
-            })
-            .collect();
-        if parts.len() != 4 {
-            let e = Error::InvalidInput(format!("Invalid bounding box format: {}. Should be 4 comma-separated numbers: minX,minY,maxX,maxY.", bbox));
-            return Err(e);
-        }
-
-        let (min_x, min_y, max_x, max_y) = (parts[0], parts[1], parts[2], parts[3]);
-        fgb.select_bbox(min_x, min_y, max_x, max_y)?
+    let mut fgb = if let Some(b) = bbox {
+        let rect = bbox::parse(b)?;
+        fgb.select_bbox(rect.min().x, rect.min().y, rect.max().x, rect.max().y)?
     } else {
         fgb.select_all()?
     };
 
     let mut json_data: Vec<u8> = Vec::new();
     let mut json = GeoJsonWriter::new(&mut json_data);
+    // The writer only gives XY unless asked for more
+    json.dims.z = has_z;
     fgb.process(&mut json)?;
     println!("{}", std::str::from_utf8(&json_data)?);
     Ok(())
@@ -1,25 +1,47 @@
-use crate::geoq::{self, error::Error, par, reader::Reader};
+use crate::geoq::{
+    antimeridian, bbox,
+    distance::Options,
+    entity::Entity,
+    error::Error,
+    input::InputFormat,
+    par,
+    query::{Predicate, QuerySet},
+    reader::Reader,
+};
 use clap::ArgMatches;
-use geo_types::{Geometry, Polygon};
+use geo_types::Geometry;
 use std::{fs::File, io::BufReader};
 
-fn read_query_geoms(matches: &ArgMatches) -> Result<Vec<Geometry<f64>>, Error> {
-    let f = matches.value_of("query-file");
-    let q = matches.value_of("query");
-    match (f, q) {
+// Query features given either as a --query-file or as a positional argument
+pub fn read_query_entities(matches: &ArgMatches) -> Result<Vec<Entity>, Error> {
+    read_queries(matches.value_of("query-file"), matches.value_of("query"))
+}
+
+// Value of an option which can be given either before or after a subcommand, e.g.
+// geoq join --mode count contains
+// OR
+// geoq join contains --mode count
+pub fn global_value_of<'a>(
+    parent: &'a ArgMatches,
+    sub: &'a ArgMatches,
+    name: &str,
+) -> Option<&'a str> {
+    sub.value_of(name).or_else(|| parent.value_of(name))
+}
+
+pub fn read_queries(file: Option<&str>, query: Option<&str>) -> Result<Vec<Entity>, Error> {
+    match (file, query) {
         (Some(path), None) => {
             let f = File::open(path)?;
             let mut f = BufReader::new(f);
-            let reader = Reader::new(&mut f);
-            let entities = reader.into_iter().collect::<Result<Vec<_>, _>>()?;
-            Ok(entities.into_iter().map(|e| e.geom()).collect())
+            let reader = Reader::with_format(&mut f, InputFormat::Auto);
+            reader.into_iter().collect::<Result<Vec<_>, _>>()
         }
         (None, Some(q)) => {
             let q_buff = q.as_bytes();
             let mut f = BufReader::new(q_buff);
-            let reader = Reader::new(&mut f);
-            let entities = reader.into_iter().collect::<Result<Vec<_>, _>>()?;
-            Ok(entities.into_iter().map(|e| e.geom()).collect())
+            let reader = Reader::with_format(&mut f, InputFormat::Auto);
+            reader.into_iter().collect::<Result<Vec<_>, _>>()
         }
         _ => {
             eprintln!("Must provide Query Features as either --file or positional argument.");
@@ -28,15 +50,29 @@ fn read_query_geoms(matches: &ArgMatches) -> Result<Vec<Geometry<f64>>, Error> {
     }
 }
 
-fn intersects(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
-    use geo::algorithm::intersects::Intersects;
-    let query_geoms = read_query_geoms(matches)?;
+pub fn read_query_geoms(matches: &ArgMatches) -> Result<Vec<Geometry<f64>>, Error> {
+    let entities = read_query_entities(matches)?;
+    Ok(entities
+        .into_iter()
+        .map(|e| antimeridian::split(&e.geom()))
+        .collect())
+}
+
+// Distance options and radius for dwithin, which can be given to 'filter' or 'join'
+pub fn read_dwithin(matches: &ArgMatches) -> Result<Predicate, Error> {
+    let rad_arg = matches.value_of("radius").unwrap();
+    let radius: f64 = rad_arg
+        .parse()
+        .map_err(|_| Error::InvalidNumberFormat(format!("Invalid Radius: {}", rad_arg)))?;
+    let options = Options::parse(matches.value_of("method"), matches.value_of("units"))?;
+    Ok(Predicate::DWithin(radius, options))
+}
+
+fn filter(queries: QuerySet<()>, predicate: Predicate, negate: bool) -> Result<(), Error> {
     par::for_stdin_entity(move |entity| {
         let output = entity.raw();
-        let geom = entity.geom();
-        let is_match: bool = query_geoms
-            .iter()
-            .any(|ref query_geom| query_geom.intersects(&geom));
+        let geom = antimeridian::split(&entity.geom());
+        let is_match = queries.any(&geom, &predicate);
         if is_match ^ negate {
             Ok(vec![output])
         } else {
@@ -45,68 +81,44 @@ fn intersects(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
     })
 }
 
-fn contains(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
-    let query_geoms = read_query_geoms(matches)?;
-    let query_polygons: Vec<Polygon<f64>> = query_geoms
-        .into_iter()
-        .flat_map(|geom| match geom {
-            Geometry::Polygon(poly) => vec![poly],
-            Geometry::MultiPolygon(mp) => mp.0,
-            _ => vec![],
-        })
-        .collect();
-
-    if query_polygons.is_empty() {
-        Err(Error::PolygonRequired)
-    } else {
-        par::for_stdin_entity(move |entity| {
-            let output = entity.raw();
-            let geom = entity.geom();
-            let is_match = query_polygons
-                .iter()
-                .any(|ref query_poly| geoq::contains::contains(query_poly, &geom));
-            if is_match ^ negate {
-                Ok(vec![output])
-            } else {
-                Ok(vec![])
-            }
-        })
-    }
+fn query_set(geoms: Vec<Geometry<f64>>) -> QuerySet<()> {
+    QuerySet::new(geoms.into_iter().map(|g| (g, ())).collect())
 }
 
-fn dwithin(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
+fn predicate_filter(matches: &ArgMatches, predicate: Predicate, negate: bool) -> Result<(), Error> {
     let query_geoms = read_query_geoms(matches)?;
-    let rad_arg = matches.value_of("radius").unwrap();
-    let radius: f64 = rad_arg
-        .parse()
-        .map_err(|_| Error::InvalidNumberFormat(format!("Invalid Radius: {}", rad_arg)))?;
-
     if query_geoms.is_empty() {
         Err(Error::NoInputGiven)
     } else {
-        par::for_stdin_entity(move |entity| {
-            let output = entity.raw();
-            let geom = entity.geom();
-            let point = match geom {
-                Geometry::Point(p) => Ok(p),
-                _ => Err(Error::PointRequired),
-            }?;
-            let is_match = query_geoms.iter().any(|ref query_geom| {
-                let dist = geoq::distance::distance(&point, query_geom);
-                match dist {
-                    Some(d) => d < radius,
-                    None => false,
-                }
-            });
-            if is_match ^ negate {
-                Ok(vec![output])
-            } else {
-                Ok(vec![])
-            }
-        })
+        filter(query_set(query_geoms), predicate, negate)
     }
 }
 
+// Checks only bounding boxes, so there's no need for an index or
+// any exact geometry comparisons
+fn bbox_filter(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
+    let rect = bbox::parse(matches.value_of("bbox").unwrap())?;
+    let strict = matches.is_present("strict");
+    par::for_stdin_entity(move |entity| {
+        let geom_bbox = bbox::bbox(&entity.geom());
+        let is_match = if strict {
+            bbox::contains(&rect, &geom_bbox)
+        } else {
+            bbox::intersects(&rect, &geom_bbox)
+        };
+        if is_match ^ negate {
+            Ok(vec![entity.raw()])
+        } else {
+            Ok(vec![])
+        }
+    })
+}
+
+fn relate(matches: &ArgMatches, negate: bool) -> Result<(), Error> {
+    let pattern = matches.value_of("pattern").unwrap().parse()?;
+    predicate_filter(matches, Predicate::Relate(pattern), negate)
+}
+
 pub fn run(matches: &ArgMatches) -> Result<(), Error> {
     // allow --negate to be passed either before or after the subcommand
     // geoq filter --negate intersects
@@ -119,9 +131,17 @@ pub fn run(matches: &ArgMatches) -> Result<(), Error> {
         .is_some();
 
     match matches.subcommand() {
-        ("intersects", Some(m)) => intersects(m, negate),
-        ("contains", Some(m)) => contains(m, negate),
-        ("dwithin", Some(m)) => dwithin(m, negate),
+        ("intersects", Some(m)) => predicate_filter(m, Predicate::Intersects, negate),
+        ("contains", Some(m)) => predicate_filter(m, Predicate::Contains, negate),
+        ("within", Some(m)) => predicate_filter(m, Predicate::Within, negate),
+        ("touches", Some(m)) => predicate_filter(m, Predicate::Touches, negate),
+        ("crosses", Some(m)) => predicate_filter(m, Predicate::Crosses, negate),
+        ("overlaps", Some(m)) => predicate_filter(m, Predicate::Overlaps, negate),
+        ("disjoint", Some(m)) => predicate_filter(m, Predicate::Disjoint, negate),
+        ("equals", Some(m)) => predicate_filter(m, Predicate::Equals, negate),
+        ("relate", Some(m)) => relate(m, negate),
+        ("bbox", Some(m)) => bbox_filter(m, negate),
+        ("dwithin", Some(m)) => predicate_filter(m, read_dwithin(m)?, negate),
         _ => Err(Error::UnknownCommand),
     }
 }
@@ -38,11 +38,11 @@ fn covering(matches: &ArgMatches) -> Result<(), Error> {
     par::for_stdin_entity(move |e| {
         if include_original {
             let mut results = vec![e.raw()];
-            let g = e.geom();
+            let g = geoq::antimeridian::split(&e.geom());
             results.extend(geoq::geohash::covering(&g, level));
             Ok(results)
         } else {
-            let g = e.geom();
+            let g = geoq::antimeridian::split(&e.geom());
             Ok(geoq::geohash::covering(&g, level))
         }
     })
@@ -83,7 +83,7 @@ fn encode_long() -> Result<(), Error> {
                     )))
                 }
             },
-            _ => return Err(Error::IOError),
+            Err(e) => return Err(Error::IOError(e)),
         }
     }
     Ok(())
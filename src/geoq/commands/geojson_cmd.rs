@@ -1,23 +1,34 @@
-use crate::geoq::{error::Error, par, reader::Reader};
+use crate::geoq::{
+    error::Error,
+    orient::{self, Winding},
+    par,
+    reader::Reader,
+};
 use clap::ArgMatches;
 use geojson::GeoJson;
 use std::io;
 
-fn geom() -> Result<(), Error> {
-    par::for_stdin_entity(|e| {
-        let gj_geom = e.geojson_geometry();
+fn geom(winding: Option<Winding>) -> Result<(), Error> {
+    par::for_stdin_entity(move |e| {
+        let mut gj_geom = e.geojson_geometry();
+        if let Some(w) = winding {
+            orient::orient(&mut gj_geom.value, w);
+        }
         Ok(vec![serde_json::to_string(&gj_geom).unwrap()])
     })
 }
 
-fn feature() -> Result<(), Error> {
-    par::for_stdin_entity(|e| {
-        let f = e.geojson_feature();
+fn feature(winding: Option<Winding>) -> Result<(), Error> {
+    par::for_stdin_entity(move |e| {
+        let mut f = e.geojson_feature();
+        if let Some(w) = winding {
+            orient::orient_feature(&mut f, w);
+        }
         Ok(vec![serde_json::to_string(&f).unwrap()])
     })
 }
 
-fn feature_collection() -> Result<(), Error> {
+fn feature_collection(winding: Option<Winding>) -> Result<(), Error> {
     let mut features: Vec<geojson::Feature> = Vec::new();
 
     let stdin = io::stdin();
@@ -26,7 +37,13 @@ fn feature_collection() -> Result<(), Error> {
     for e_res in reader {
         match e_res {
             Err(e) => return Err(e),
-            Ok(e) => features.push(e.geojson_feature()),
+            Ok(e) => {
+                let mut f = e.geojson_feature();
+                if let Some(w) = winding {
+                    orient::orient_feature(&mut f, w);
+                }
+                features.push(f)
+            }
         }
     }
 
@@ -39,11 +56,15 @@ fn feature_collection() -> Result<(), Error> {
     Ok(())
 }
 
+fn winding(m: &ArgMatches) -> Result<Option<Winding>, Error> {
+    m.value_of("winding").map(|w| w.parse()).transpose()
+}
+
 pub fn run(gj: &ArgMatches) -> Result<(), Error> {
     match gj.subcommand() {
-        ("geom", Some(_)) => geom(),
-        ("f", Some(_)) => feature(),
-        ("fc", Some(_)) => feature_collection(),
+        ("geom", Some(m)) => geom(winding(m)?),
+        ("f", Some(m)) => feature(winding(m)?),
+        ("fc", Some(m)) => feature_collection(winding(m)?),
         _ => Err(Error::UnknownCommand),
     }
 }
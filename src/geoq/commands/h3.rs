@@ -131,7 +131,7 @@ fn children(matches: &ArgMatches) -> Result<(), Error> {
     };
 
     par::for_stdin_entity(move |e| match e {
-        Entity::H3(cell) => cell_children(cell, resolution),
+        Entity::H3(cell) => cell_children(*cell, resolution),
         _ => Err(Error::InvalidInput(format!(
             "Input for 'geoq h3 children' should be a hexadecimal h3 cell. Got: {}",
             e
@@ -224,7 +224,7 @@ fn to_str() -> Result<(), Error> {
                     e => return e.map(|_| ()),
                 }
             }
-            _ => return Err(Error::IOError),
+            Err(e) => return Err(Error::IOError(e)),
         }
     }
     Ok(())
@@ -263,7 +263,7 @@ fn grid_disk(matches: &ArgMatches) -> Result<(), Error> {
     let radius = radius_opt.unwrap_or(1);
 
     par::for_stdin_entity(move |e| match e {
-        Entity::H3(cell) => cell_disk(cell, radius),
+        Entity::H3(cell) => cell_disk(*cell, radius),
         other => Err(Error::InvalidInput(format!(
             "geoq h3 grid-disk requires H3 cell strings as inputs -- got {}",
             other
@@ -513,7 +513,9 @@ fn covering(matches: &ArgMatches) -> Result<(), Error> {
         } else {
             vec![]
         };
-        let cells = match e.geom() {
+        // Polygons crossing the antimeridian would otherwise be
+        // covered the long way around the globe
+        let cells = match geoq::antimeridian::split(&e.geom()) {
             geo_types::Geometry::Point(g) => cell_at_res(g, res).map(|c| vec![c]),
             geo_types::Geometry::MultiPoint(g) => {
                 g.0.iter().map(|p| cell_at_res(*p, res)).collect()
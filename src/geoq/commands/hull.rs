@@ -0,0 +1,85 @@
+use crate::geoq::{
+    error::Error,
+    hull::{self, Hull},
+    par, reader,
+};
+use clap::ArgMatches;
+use geo_types::Geometry;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+fn read_concavity(matches: &ArgMatches) -> Result<Option<f64>, Error> {
+    match matches.value_of("concave") {
+        None => Ok(None),
+        Some(arg) => match arg.parse::<f64>() {
+            Ok(ratio) if ratio > 0.0 => Ok(Some(ratio)),
+            _ => Err(Error::InvalidNumberFormat(format!(
+                "Invalid concave ratio: {} - must be a positive number, e.g. 2.0.",
+                arg
+            ))),
+        },
+    }
+}
+
+fn feature_output(geom: &Geometry<f64>, properties: Map<String, Value>) -> String {
+    let feature = geojson::Feature {
+        bbox: None,
+        geometry: Some(geojson::Geometry::new(geojson::Value::from(geom))),
+        id: None,
+        properties: Some(properties),
+        foreign_members: None,
+    };
+    serde_json::to_string(&feature).unwrap()
+}
+
+// One hull for every input, or one per value of the group-by property
+fn aggregate(concavity: Option<f64>, key: Option<&str>) -> Result<(), Error> {
+    // Groups are output in the order their first input was seen
+    let mut groups: Vec<(Value, Hull)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    reader::for_entity(|e| {
+        let value = match key {
+            Some(key) => e.geojson_properties().remove(key).unwrap_or(Value::Null),
+            None => Value::Null,
+        };
+        let id = serde_json::to_string(&value).unwrap();
+        let position = *positions.entry(id).or_insert_with(|| {
+            groups.push((value, Hull::new(concavity)));
+            groups.len() - 1
+        });
+        groups[position].1.add(&e.geom());
+        Ok(())
+    })?;
+
+    if groups.is_empty() {
+        return Err(Error::NoInputGiven);
+    }
+    for (value, hull) in groups {
+        if let Some(geom) = hull.finish() {
+            let mut properties = Map::new();
+            if let Some(key) = key {
+                properties.insert(key.to_string(), value);
+            }
+            println!("{}", feature_output(&geom, properties));
+        }
+    }
+    Ok(())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let concavity = read_concavity(matches)?;
+
+    if matches.is_present("all") || matches.is_present("group-by") {
+        aggregate(concavity, matches.value_of("group-by"))
+    } else {
+        par::for_stdin_entity(move |e| match hull::hull(&e.geom(), concavity) {
+            Some(geom) => {
+                let gj_geom = geojson::Geometry::new(geojson::Value::from(&geom));
+                let feature = e.with_geometry(gj_geom);
+                Ok(vec![serde_json::to_string(&feature).unwrap()])
+            }
+            None => Ok(vec![]),
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use crate::geoq::{
+    antimeridian,
+    commands::filter,
+    error::Error,
+    par,
+    query::{Predicate, QuerySet},
+};
+use clap::ArgMatches;
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+
+// How to combine the properties of query features matching an input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    // Properties of the first matching query feature
+    First,
+    // Arrays of property values from every matching query feature
+    All,
+    // Number of matching query features
+    Count,
+}
+
+pub const MODES: [&str; 3] = ["first", "all", "count"];
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" => Ok(Mode::First),
+            "all" => Ok(Mode::All),
+            "count" => Ok(Mode::Count),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --mode value: {}. Expected one of: {}.",
+                s,
+                MODES.join(", ")
+            ))),
+        }
+    }
+}
+
+fn merge(props: &mut Map<String, Value>, found: &[&Map<String, Value>], mode: Mode, prefix: &str) {
+    match mode {
+        Mode::First => {
+            if let Some(first) = found.first() {
+                for (k, v) in first.iter() {
+                    props.insert(format!("{}{}", prefix, k), v.clone());
+                }
+            }
+        }
+        Mode::All => {
+            let mut keys: Vec<&String> = Vec::new();
+            for query_props in found {
+                for k in query_props.keys() {
+                    if !keys.contains(&k) {
+                        keys.push(k);
+                    }
+                }
+            }
+            for k in keys {
+                let values = found
+                    .iter()
+                    .map(|query_props| query_props.get(k).cloned().unwrap_or(Value::Null))
+                    .collect();
+                props.insert(format!("{}{}", prefix, k), Value::Array(values));
+            }
+        }
+        Mode::Count => {
+            props.insert(format!("{}count", prefix), json!(found.len()));
+        }
+    }
+}
+
+fn join(parent: &ArgMatches, matches: &ArgMatches, predicate: Predicate) -> Result<(), Error> {
+    // Options can be given either before or after the predicate subcommand
+    let mode: Mode = match filter::global_value_of(parent, matches, "mode") {
+        Some(m) => m.parse()?,
+        None => Mode::First,
+    };
+    let prefix = filter::global_value_of(parent, matches, "prefix")
+        .unwrap_or("")
+        .to_string();
+    let left = matches.is_present("left") || parent.is_present("left");
+    let file = filter::global_value_of(parent, matches, "query-file");
+
+    let queries = QuerySet::new(
+        filter::read_queries(file, matches.value_of("query"))?
+            .into_iter()
+            .map(|e| (antimeridian::split(&e.geom()), e.geojson_properties()))
+            .collect(),
+    );
+    if queries.is_empty() {
+        return Err(Error::NoInputGiven);
+    }
+
+    par::for_stdin_entity(move |e| {
+        let geom = antimeridian::split(&e.geom());
+        let found = queries.matches(&geom, &predicate);
+        if found.is_empty() && !left {
+            return Ok(vec![]);
+        }
+        let mut feature = e.geojson_feature();
+        let props = feature.properties.get_or_insert_with(Map::new);
+        merge(props, &found, mode, &prefix);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("intersects", Some(m)) => join(matches, m, Predicate::Intersects),
+        ("contains", Some(m)) => join(matches, m, Predicate::Contains),
+        ("within", Some(m)) => join(matches, m, Predicate::Within),
+        ("dwithin", Some(m)) => join(matches, m, filter::read_dwithin(m)?),
+        _ => Err(Error::UnknownCommand),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, Mode};
+    use serde_json::{json, Map, Value};
+
+    fn props(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("Expected an object"),
+        }
+    }
+
+    #[test]
+    fn test_merging_properties() {
+        let a = props(json!({"name": "a", "pop": 1}));
+        let b = props(json!({"name": "b", "zone": "x"}));
+        let found = vec![&a, &b];
+
+        let mut first = props(json!({"id": 7}));
+        merge(&mut first, &found, Mode::First, "q_");
+        assert_eq!(
+            json!({"id": 7, "q_name": "a", "q_pop": 1}),
+            Value::Object(first)
+        );
+
+        let mut all = Map::new();
+        merge(&mut all, &found, Mode::All, "");
+        assert_eq!(
+            json!({"name": ["a", "b"], "pop": [1, null], "zone": [null, "x"]}),
+            Value::Object(all)
+        );
+
+        let mut count = Map::new();
+        merge(&mut count, &found, Mode::Count, "");
+        assert_eq!(json!({"count": 2}), Value::Object(count));
+    }
+}
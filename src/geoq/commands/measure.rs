@@ -1,9 +1,17 @@
-use crate::geoq::{coord_count, distance, entity, error::Error, input, par};
+use crate::geoq::{
+    coord_count, distance, entity,
+    error::Error,
+    geodesic::{self, Unit},
+    input, par, zm,
+};
 use clap::ArgMatches;
-use geo_types::Geometry;
+use geo_types::{Geometry, LineString};
 use serde_json::json;
+use wkt::ToWkt;
 
 fn distance(matches: &ArgMatches) -> Result<(), Error> {
+    let options = distance::Options::parse(matches.value_of("method"), matches.value_of("units"))?;
+    let segment = matches.is_present("segment");
     match matches.value_of("query") {
         Some(q) => {
             let query_input = input::read_line(q.to_string())?;
@@ -13,29 +21,27 @@ fn distance(matches: &ArgMatches) -> Result<(), Error> {
             } else if query_entities.len() > 1 {
                 Err(Error::TooManyFeatures)
             } else {
-                match query_entities.remove(0).geom() {
-                    Geometry::Point(query_point) => par::for_stdin_entity(move |entity| {
-                        let output = entity.raw();
-                        let geom = entity.geom();
+                let query_geom = query_entities.remove(0).geom();
+                let query = q.to_string();
+                par::for_stdin_entity(move |entity| {
+                    let output = entity.raw();
+                    let geom = entity.geom();
 
-                        let dist = distance::distance(&query_point, &geom);
-
-                        match dist {
-                            Some(d) => Ok(vec![format!("{}\t{}", d, output)]),
-                            None => {
-                                eprintln!("Couldn't calculate distance between <query>: {:?} and <input>: {}", query_point, output);
-                                Err(Error::DistanceFailed)
-                            }
+                    match options.closest(&query_geom, &geom) {
+                        Some((d, line)) if segment => {
+                            let wkt = Geometry::LineString(LineString::from(line)).to_wkt();
+                            Ok(vec![format!("{}\t{}\t{}", d, wkt.items[0], output)])
+                        }
+                        Some((d, _)) => Ok(vec![format!("{}\t{}", d, output)]),
+                        None => {
+                            eprintln!(
+                                "Couldn't calculate distance between <query>: {} and <input>: {}",
+                                query, output
+                            );
+                            Err(Error::DistanceFailed)
                         }
-                    }),
-                    _ => {
-                        eprintln!(
-                            "<query> argument for measuing distance must be a Point. Got: {}",
-                            q
-                        );
-                        Err(Error::PointRequired)
                     }
-                }
+                })
             }
         }
         _ => Err(Error::MissingArgument),
@@ -63,10 +69,81 @@ fn coords(matches: &ArgMatches) -> Result<(), Error> {
     })
 }
 
+fn elevation(matches: &ArgMatches) -> Result<(), Error> {
+    let gj = matches.is_present("geojson");
+    par::for_stdin_entity(move |e| {
+        let geom = e.geom();
+        let profile = e.ordinates().and_then(|ords| zm::profile(&geom, &ords));
+        match profile {
+            None => Err(Error::InvalidInput(
+                "Elevation requires a LineString or MultiLineString with Z values".to_string(),
+            )),
+            Some(p) => {
+                if gj {
+                    let mut feature = e.geojson_feature();
+                    if let Some(props) = feature.properties.as_mut() {
+                        props.insert("elevation_gain".to_string(), json!(p.gain));
+                        props.insert("elevation_loss".to_string(), json!(p.loss));
+                        props.insert("length_3d".to_string(), json!(p.length_3d));
+                    }
+                    Ok(vec![serde_json::to_string(&feature).unwrap()])
+                } else {
+                    Ok(vec![format!("{}\t{}\t{}", p.gain, p.loss, p.length_3d)])
+                }
+            }
+        }
+    })
+}
+
+fn read_units(matches: &ArgMatches) -> Result<Unit, Error> {
+    match matches.value_of("units") {
+        Some(u) => u.parse(),
+        None => Ok(Unit::Meters),
+    }
+}
+
+fn geodesic_measure(
+    matches: &ArgMatches,
+    property: &'static str,
+    measure: fn(&Geometry<f64>, Unit) -> Result<f64, Error>,
+) -> Result<(), Error> {
+    let gj = matches.is_present("geojson");
+    let units = read_units(matches)?;
+    par::for_stdin_entity(move |e| {
+        let value = measure(&e.geom(), units)?;
+        if gj {
+            let mut feature = e.geojson_feature();
+            if let Some(props) = feature.properties.as_mut() {
+                props.insert(property.to_string(), json!(value));
+            }
+            Ok(vec![serde_json::to_string(&feature).unwrap()])
+        } else {
+            Ok(vec![format!("{}", value)])
+        }
+    })
+}
+
+fn area(matches: &ArgMatches) -> Result<(), Error> {
+    geodesic_measure(matches, "area", |geom, units| {
+        Ok(units.area(geodesic::area(geom)))
+    })
+}
+
+fn length(matches: &ArgMatches) -> Result<(), Error> {
+    // Check units up front so e.g. '--units ha' fails once, rather than for every input
+    read_units(matches)?.length(0.0)?;
+    geodesic_measure(matches, "length", |geom, units| {
+        units.length(geodesic::length(geom))
+    })
+}
+
 pub fn run(matches: &ArgMatches) -> Result<(), Error> {
     match matches.subcommand() {
         ("distance", Some(m)) => distance(m),
         ("coord-count", Some(m)) => coords(m),
+        ("elevation", Some(m)) => elevation(m),
+        ("area", Some(m)) => area(m),
+        ("length", Some(m)) => length(m),
         _ => Err(Error::UnknownCommand),
     }
 }
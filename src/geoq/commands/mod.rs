@@ -1,16 +1,27 @@
 pub mod bbox;
+pub mod buffer;
 pub mod centroid;
+pub mod clip;
+pub mod csv;
+pub mod dissolve;
 pub mod fgb;
 pub mod filter;
 pub mod geohash;
 pub mod geojson_cmd;
 pub mod h3;
+pub mod hull;
+pub mod join;
 pub mod json;
 pub mod map;
 pub mod measure;
+pub mod nearest;
+pub mod orient;
+pub mod overlay;
 pub mod read;
 pub mod shp;
 pub mod simplify;
 pub mod snip;
+pub mod split_antimeridian;
+pub mod validate;
 pub mod whereami;
 pub mod wkt;
@@ -0,0 +1,93 @@
+use crate::geoq::{
+    bbox, commands::filter, distance::Options, entity::Entity, error::Error, index::SpatialIndex,
+    par,
+};
+use clap::ArgMatches;
+use geo_types::Geometry;
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering::Equal;
+
+fn read_count(matches: &ArgMatches) -> Result<usize, Error> {
+    match matches.value_of("count") {
+        None => Ok(1),
+        Some(arg) => match arg.parse::<usize>() {
+            Ok(k) if k > 0 => Ok(k),
+            _ => Err(Error::InvalidNumberFormat(format!(
+                "Invalid count: {} - must be a positive integer, e.g. 3.",
+                arg
+            ))),
+        },
+    }
+}
+
+// The k query features closest to a geometry, nearest first, with their distances
+fn k_nearest<'a>(
+    index: &'a SpatialIndex<(Geometry<f64>, Entity)>,
+    geom: &Geometry<f64>,
+    k: usize,
+    options: &Options,
+) -> Vec<(f64, &'a Entity)> {
+    // The k queries nearest on a plane give an upper bound on how far away
+    // the k truly nearest can be, then every query within that reach is checked
+    let reach: Vec<f64> = index
+        .nearest_to(geom)
+        .filter_map(|((query_geom, _), _)| options.distance(geom, query_geom))
+        .take(k)
+        .collect();
+    let candidates = if reach.len() < k {
+        index.items().collect()
+    } else {
+        let radius = reach.into_iter().fold(0.0, f64::max);
+        index.candidates_in(&options.search_boxes(&bbox::bbox(geom), radius))
+    };
+    let mut nearest: Vec<(f64, &Entity)> = candidates
+        .into_iter()
+        .filter_map(|(query_geom, query)| options.distance(geom, query_geom).map(|d| (d, query)))
+        .collect();
+    nearest.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Equal));
+    nearest.truncate(k);
+    nearest
+}
+
+fn nearest_properties(nearest: &[(f64, &Entity)]) -> Value {
+    let neighbors = nearest
+        .iter()
+        .map(|(d, query)| {
+            let mut props: Map<String, Value> = query.geojson_properties();
+            props.insert("distance".to_string(), json!(d));
+            Value::Object(props)
+        })
+        .collect();
+    Value::Array(neighbors)
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let k = read_count(matches)?;
+    let options = Options::parse(matches.value_of("method"), matches.value_of("units"))?;
+    let gj = matches.is_present("geojson");
+    let queries: Vec<(Geometry<f64>, Entity)> = filter::read_query_entities(matches)?
+        .into_iter()
+        .map(|e| (e.geom(), e))
+        .collect();
+    if queries.is_empty() {
+        return Err(Error::NoInputGiven);
+    }
+    let index = SpatialIndex::new(queries, |q| &q.0);
+
+    par::for_stdin_entity(move |e| {
+        let nearest = k_nearest(&index, &e.geom(), k, &options);
+        if gj {
+            let mut feature = e.geojson_feature();
+            if let Some(props) = feature.properties.as_mut() {
+                props.insert("nearest".to_string(), nearest_properties(&nearest));
+            }
+            Ok(vec![serde_json::to_string(&feature).unwrap()])
+        } else {
+            let output = e.raw();
+            Ok(nearest
+                .iter()
+                .map(|(d, query)| format!("{}\t{}\t{}", d, query.raw(), output))
+                .collect())
+        }
+    })
+}
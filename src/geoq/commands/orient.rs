@@ -0,0 +1,18 @@
+use crate::geoq::{
+    error::Error,
+    orient::{self, Winding},
+    par,
+};
+use clap::ArgMatches;
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let winding: Winding = match matches.value_of("winding") {
+        Some(w) => w.parse()?,
+        None => Winding::CounterClockwise,
+    };
+    par::for_stdin_entity(move |e| {
+        let mut feature = e.geojson_feature();
+        orient::orient_feature(&mut feature, winding);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
@@ -0,0 +1,69 @@
+use crate::geoq::{
+    antimeridian,
+    commands::filter,
+    error::Error,
+    overlay::{self, Operation},
+    par,
+    query::{Predicate, QuerySet},
+};
+use clap::ArgMatches;
+use geo_types::{Geometry, MultiPolygon};
+
+// Intersections and differences only depend on the queries an input touches,
+// but unions and xors take in the whole query set, so merge it up front.
+fn clip(
+    queries: QuerySet<MultiPolygon<f64>>,
+    merged: Option<MultiPolygon<f64>>,
+    op: Operation,
+) -> Result<(), Error> {
+    par::for_stdin_entity(move |e| {
+        let geom = antimeridian::split(&e.geom());
+        let other = match &merged {
+            Some(merged) => merged.clone(),
+            None => overlay::union_all(
+                queries
+                    .matches(&geom, &Predicate::Intersects)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            ),
+        };
+        let clipped = match overlay::overlay_geometry(&geom, &other, op) {
+            Some(clipped) => clipped,
+            None => return Ok(vec![]),
+        };
+        let gj_geom = geojson::Geometry::new(geojson::Value::from(&clipped));
+        let feature = e.with_geometry(gj_geom);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
+
+fn operation(parent: &ArgMatches, matches: &ArgMatches, op: Operation) -> Result<(), Error> {
+    // --query-file can be given either before or after the operation subcommand
+    let file = filter::global_value_of(parent, matches, "query-file");
+    let query_geoms: Vec<Geometry<f64>> = filter::read_queries(file, matches.value_of("query"))?
+        .into_iter()
+        .map(|e| antimeridian::split(&e.geom()))
+        .collect();
+    if query_geoms.is_empty() {
+        return Err(Error::NoInputGiven);
+    }
+
+    let polygons: Vec<MultiPolygon<f64>> = query_geoms.iter().map(overlay::polygons).collect();
+    let merged = match op {
+        Operation::Union | Operation::Xor => Some(overlay::union_all(polygons.clone())),
+        _ => None,
+    };
+    let queries = QuerySet::new(query_geoms.into_iter().zip(polygons).collect());
+    clip(queries, merged, op)
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("intersection", Some(m)) => operation(matches, m, Operation::Intersection),
+        ("difference", Some(m)) => operation(matches, m, Operation::Difference),
+        ("union", Some(m)) => operation(matches, m, Operation::Union),
+        ("xor", Some(m)) => operation(matches, m, Operation::Xor),
+        _ => Err(Error::UnknownCommand),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::geoq::error::Error;
+use crate::geoq::{
+    error::Error,
+    orient::{self, Winding},
+};
 use clap::ArgMatches;
 use dbase::{FieldValue, Record};
 use geojson;
@@ -234,11 +237,15 @@ fn shp_to_geojson(geom: shapefile::Shape, record: Record) -> Result<geojson::Fea
 
 pub fn run(m: &ArgMatches) -> Result<(), Error> {
     let path = m.value_of("path").unwrap();
+    let winding: Option<Winding> = m.value_of("winding").map(|w| w.parse()).transpose()?;
 
     let mut reader = shapefile::Reader::from_path(path)?;
     for shape_record in reader.iter_shapes_and_records() {
         let (shape, record) = shape_record?;
-        let gj = shp_to_geojson(shape, record)?;
+        let mut gj = shp_to_geojson(shape, record)?;
+        if let Some(w) = winding {
+            orient::orient_feature(&mut gj, w);
+        }
         let str = serde_json::to_string(&gj).unwrap();
         println!("{}", str);
     }
@@ -27,7 +27,12 @@ fn simplify(epsilon: f64, coords_target: Option<usize>) -> Result<(), Error> {
             }
         };
 
-        let gj_geom = geojson::Geometry::new(geojson::Value::from(&simplified));
+        // Simplifying keeps a subset of the original vertices,
+        // so their Z and M values can be carried over
+        let gj_geom = match e.ordinates() {
+            Some(ords) => geojson::Geometry::new(ords.geojson_value(&simplified)),
+            None => geojson::Geometry::new(geojson::Value::from(&simplified)),
+        };
         let feature = geojson::Feature {
             bbox: None,
             geometry: Some(gj_geom),
@@ -0,0 +1,19 @@
+use crate::geoq::{antimeridian, error::Error, par};
+
+pub fn run() -> Result<(), Error> {
+    par::for_stdin_entity(move |e| {
+        let geom = e.geom();
+        let gj_geom = if antimeridian::crosses(&geom) {
+            // Cut points are new vertices, so only unsplit geometries
+            // keep their Z and M values
+            geojson::Geometry::new(geojson::Value::from(&antimeridian::split(&geom)))
+        } else {
+            match e.ordinates() {
+                Some(ords) => geojson::Geometry::new(ords.geojson_value(&geom)),
+                None => geojson::Geometry::new(geojson::Value::from(&geom)),
+            }
+        };
+        let feature = e.with_geometry(gj_geom);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
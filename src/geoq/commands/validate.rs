@@ -0,0 +1,52 @@
+use crate::geoq::{entity::Entity, error::Error, par, validate};
+use clap::ArgMatches;
+
+fn issues(e: &Entity) -> Vec<validate::Issue> {
+    let mut issues = match e {
+        Entity::Wkt(raw, _, _) => validate::wkt_unclosed_rings(raw),
+        Entity::GeoJsonGeometry(_, g) => validate::geojson_unclosed_rings(&g.value),
+        Entity::GeoJsonFeature(_, f) => match &f.geometry {
+            Some(g) => validate::geojson_unclosed_rings(&g.value),
+            None => vec![],
+        },
+        _ => vec![],
+    };
+    issues.extend(validate::issues(&e.geom()));
+    issues
+}
+
+fn report() -> Result<(), Error> {
+    par::for_stdin_entity(|e| {
+        let issues = issues(&e);
+        if issues.is_empty() {
+            Ok(vec![format!("valid\t\t{}", e.raw())])
+        } else {
+            let descriptions: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            Ok(vec![format!(
+                "invalid\t{}\t{}",
+                descriptions.join("; "),
+                e.raw()
+            )])
+        }
+    })
+}
+
+fn fix() -> Result<(), Error> {
+    par::for_stdin_entity(|e| {
+        let fixed = validate::fix(&e.geom());
+        let gj_geom = match e.ordinates() {
+            Some(ords) => geojson::Geometry::new(ords.geojson_value(&fixed)),
+            None => geojson::Geometry::new(geojson::Value::from(&fixed)),
+        };
+        let feature = e.with_geometry(gj_geom);
+        Ok(vec![serde_json::to_string(&feature).unwrap()])
+    })
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Error> {
+    if matches.is_present("fix") {
+        fix()
+    } else {
+        report()
+    }
+}
@@ -1,5 +1,5 @@
 use crate::geoq::{error::Error, par};
 
 pub fn run() -> Result<(), Error> {
-    par::for_stdin_entity(|e| Ok(vec![format!("{}", e.wkt())]))
+    par::for_stdin_entity(|e| Ok(vec![e.wkt_string()]))
 }
@@ -0,0 +1,275 @@
+use crate::geoq::error::Error;
+use serde_json::{Number, Value};
+use std::io::BufRead;
+
+// Splits CSV input into records, which usually are single lines but may
+// span several when a quoted field contains line breaks.
+//
+// Each record is given along with the (1-based) line number where it starts.
+pub struct CsvRecords<'a> {
+    reader: &'a mut dyn BufRead,
+    line: usize,
+    // Until the delimiter is known, both commas and tabs separate fields
+    delimiter: Option<char>,
+}
+
+impl<'a> CsvRecords<'a> {
+    pub fn new(reader: &'a mut dyn BufRead) -> CsvRecords<'a> {
+        CsvRecords {
+            reader,
+            line: 0,
+            delimiter: None,
+        }
+    }
+
+    pub fn set_delimiter(&mut self, delimiter: char) {
+        self.delimiter = Some(delimiter);
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        let mut buf = String::new();
+        match self.reader.read_line(&mut buf) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                self.line += 1;
+                Some(buf)
+            }
+        }
+    }
+}
+
+// Whether the text ends inside a quoted field. Quotes only start a
+// quoted field at the beginning of a line or after a delimiter.
+fn open_quote(text: &str, delimiter: Option<char>) -> bool {
+    let is_delimiter = |c: char| match delimiter {
+        Some(d) => c == d,
+        None => c == ',' || c == '\t',
+    };
+    let mut quoted = false;
+    let mut field_start = true;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+        } else if c == '"' && field_start {
+            quoted = true;
+        }
+        field_start = !quoted && (is_delimiter(c) || c == '\n');
+    }
+    quoted
+}
+
+impl<'a> Iterator for CsvRecords<'a> {
+    type Item = (usize, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.line + 1;
+            let mut record = self.read_line()?;
+            while open_quote(&record, self.delimiter) {
+                match self.read_line() {
+                    Some(next) => record.push_str(&next),
+                    None => break,
+                }
+            }
+            let record = record.trim_end_matches(|c| c == '\n' || c == '\r');
+            if !record.trim().is_empty() {
+                return Some((start, record.to_string()));
+            }
+        }
+    }
+}
+
+// Guess the delimiter from the header row: tab if it
+// has tabs but no commas, otherwise comma
+pub fn detect_delimiter(header: &str) -> char {
+    if header.contains('\t') && !header.contains(',') {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+pub fn parse_delimiter(arg: &str) -> Result<char, Error> {
+    match arg {
+        "tab" | "\\t" | "\t" => Ok('\t'),
+        _ if arg.chars().count() == 1 && arg != "\"" => Ok(arg.chars().next().unwrap()),
+        _ => Err(Error::InvalidInput(format!(
+            "Invalid delimiter: {}. Expected a single character or 'tab'.",
+            arg
+        ))),
+    }
+}
+
+// Split a record into its fields. Fields may be quoted with '"', in which
+// case they can contain delimiters, line breaks, and quotes escaped as '""'.
+pub fn fields(record: &str, delimiter: char) -> Result<Vec<String>, Error> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars().peekable();
+    let mut quoted = false;
+    let mut at_field_start = true;
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == delimiter {
+            fields.push(field);
+            field = String::new();
+            at_field_start = true;
+            continue;
+        } else if c == '"' && at_field_start {
+            quoted = true;
+        } else {
+            field.push(c);
+        }
+        at_field_start = false;
+    }
+
+    if quoted {
+        return Err(Error::InvalidInput(format!(
+            "Unterminated quoted field in CSV record: {}",
+            record
+        )));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+// JSON value for a field: numbers and booleans get their own types,
+// empty fields are null, and anything else is kept as a string.
+// Numbers are only converted when their text is already valid JSON,
+// so values like IDs with leading zeros stay as strings.
+pub fn typed_value(field: &str) -> Value {
+    match field {
+        "" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ if field.trim() == field => match serde_json::from_str::<Number>(field) {
+            Ok(n) => Value::Number(n),
+            Err(_) => Value::String(field.to_string()),
+        },
+        _ => Value::String(field.to_string()),
+    }
+}
+
+// Text for a JSON value in a CSV field. Null is written as an empty
+// field, and nested objects or arrays are written as JSON.
+pub fn field_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+// Join fields into a record, quoting any which contain
+// the delimiter, quotes, or line breaks
+pub fn record(fields: &[String], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            if f.contains(|c| c == delimiter || c == '"' || c == '\n' || c == '\r') {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(&delimiter.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{field_text, fields, record, typed_value, CsvRecords};
+    use serde_json::json;
+
+    fn records(input: &str) -> Vec<(usize, String)> {
+        let mut pointer = input.as_bytes();
+        CsvRecords::new(&mut pointer).collect()
+    }
+
+    #[test]
+    fn test_records() {
+        assert_eq!(
+            vec![
+                (1, "a,b".to_string()),
+                (2, "1,\"two\nlines\"".to_string()),
+                (5, "3,4".to_string())
+            ],
+            records("a,b\n1,\"two\nlines\"\n\n3,4\r\n")
+        );
+        assert_eq!(
+            vec![(1, "5\" disk,a".to_string()), (2, "b,c".to_string())],
+            records("5\" disk,a\nb,c\n")
+        );
+
+        let mut pointer = "a;b\n1;\"two\nlines\"\n".as_bytes();
+        let mut semicolons = CsvRecords::new(&mut pointer);
+        semicolons.set_delimiter(';');
+        assert_eq!(
+            vec![(1, "a;b".to_string()), (2, "1;\"two\nlines\"".to_string())],
+            semicolons.collect::<Vec<(usize, String)>>()
+        );
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(vec!["a", "b", ""], fields("a,b,", ',').unwrap());
+        assert_eq!(
+            vec!["a,b", "say \"hi\"", "c"],
+            fields("\"a,b\",\"say \"\"hi\"\"\",c", ',').unwrap()
+        );
+        assert_eq!(vec!["a,b", "c"], fields("a,b\tc", '\t').unwrap());
+        assert_eq!(vec!["5\" disk"], fields("5\" disk", ',').unwrap());
+        assert!(fields("\"a,b", ',').is_err());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        assert_eq!(json!(12), typed_value("12"));
+        assert_eq!(json!(-1.5), typed_value("-1.5"));
+        assert_eq!(json!(true), typed_value("true"));
+        assert_eq!(json!(null), typed_value(""));
+        assert_eq!(json!("007"), typed_value("007"));
+        assert_eq!(json!("1,000"), typed_value("1,000"));
+        assert_eq!(json!(" 12"), typed_value(" 12"));
+        assert_eq!(json!("NaN"), typed_value("NaN"));
+    }
+
+    #[test]
+    fn test_writing_records() {
+        let row = vec![
+            field_text(&json!("a,b")),
+            field_text(&json!("say \"hi\"")),
+            field_text(&json!(1.5)),
+            field_text(&json!(null)),
+            field_text(&json!({"a": [1, 2]})),
+        ];
+        let text = record(&row, ',');
+        assert_eq!(
+            "\"a,b\",\"say \"\"hi\"\"\",1.5,,\"{\"\"a\"\":[1,2]}\"",
+            text
+        );
+        assert_eq!(row, fields(&text, ',').unwrap());
+        assert_eq!(
+            "a,b\tc",
+            record(&["a,b".to_string(), "c".to_string()], '\t')
+        );
+    }
+}
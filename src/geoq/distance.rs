@@ -1,10 +1,152 @@
+use crate::geoq::{
+    error::Error,
+    geodesic::{self, Unit},
+};
 use geo::algorithm::closest_point::ClosestPoint;
 use geo::algorithm::contains::Contains;
+use geo::algorithm::coords_iter::CoordsIter;
+use geo::algorithm::euclidean_distance::EuclideanDistance;
+use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::algorithm::intersects::Intersects;
+use geo::algorithm::line_intersection::{line_intersection, LineIntersection};
 use geo::algorithm::vincenty_distance::VincentyDistance;
-use geo_types::{Geometry, GeometryCollection, MultiPolygon, Point, Polygon};
+use geo_types::{
+    Geometry, GeometryCollection, Line, LineString, MultiPolygon, Point, Polygon, Rect,
+};
 use std::cmp::Ordering::Equal;
+use std::str::FromStr;
 
-// TODO nearest point for other geom types
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    // Vincenty's formulae on the WGS84 ellipsoid, falling back to
+    // Karney's geodesic solver for nearly antipodal points
+    Vincenty,
+    // Great-circle distance on a sphere
+    Haversine,
+    // Karney's geodesic solver on the WGS84 ellipsoid
+    Geodesic,
+    // Euclidean distance in coordinate units
+    Planar,
+}
+
+pub const METHODS: [&str; 4] = ["vincenty", "haversine", "geodesic", "planar"];
+
+impl FromStr for Method {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vincenty" => Ok(Method::Vincenty),
+            "haversine" => Ok(Method::Haversine),
+            "geodesic" => Ok(Method::Geodesic),
+            "planar" => Ok(Method::Planar),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --method value: {}. Expected one of: {}.",
+                s,
+                METHODS.join(", ")
+            ))),
+        }
+    }
+}
+
+// How to measure distances, and which units to give them in
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    pub method: Method,
+    pub units: Unit,
+}
+
+impl Options {
+    pub fn parse(method: Option<&str>, units: Option<&str>) -> Result<Options, Error> {
+        let method = match method {
+            Some(m) => m.parse()?,
+            None => Method::Vincenty,
+        };
+        if method == Method::Planar && units.is_some() {
+            return Err(Error::InvalidInput(
+                "--units can't be used with --method planar, which measures in coordinate units"
+                    .to_string(),
+            ));
+        }
+        let units: Unit = match units {
+            Some(u) => u.parse()?,
+            None => Unit::Meters,
+        };
+        units.length(0.0)?;
+        Ok(Options { method, units })
+    }
+
+    pub fn distance(&self, a: &Geometry<f64>, b: &Geometry<f64>) -> Option<f64> {
+        self.closest(a, b).map(|(dist, _)| dist)
+    }
+
+    // Bounding boxes which between them hold every point within `radius` of a
+    // bounding box, for finding candidates in spatial indexes (which work in
+    // degrees). A box reaching past the antimeridian is split there, and the
+    // part beyond it wraps around to the other side.
+    pub fn search_boxes(&self, rect: &Rect<f64>, radius: f64) -> Vec<Rect<f64>> {
+        let (min, max) = (rect.min(), rect.max());
+        if self.method == Method::Planar {
+            return vec![Rect::new(
+                (min.x - radius, min.y - radius),
+                (max.x + radius, max.y + radius),
+            )];
+        }
+        // Radius as an angle on a sphere a little smaller than the Earth,
+        // so the boxes are never too small for distances on the ellipsoid
+        let meters = radius / self.units.length(1.0).unwrap_or(1.0);
+        let angle = (meters / 6_300_000.0).to_degrees();
+        let min_y = (min.y - angle).max(-90.0);
+        let max_y = (max.y + angle).min(90.0);
+        let max_lat = min.y.abs().max(max.y.abs());
+        let world = vec![Rect::new((-180.0, min_y), (180.0, max_y))];
+        if max_lat + angle >= 90.0 {
+            // Close enough to a pole to reach any longitude
+            return world;
+        }
+        let dlon = (angle.to_radians().sin() / max_lat.to_radians().cos())
+            .asin()
+            .to_degrees();
+        let min_x = min.x - dlon;
+        let max_x = max.x + dlon;
+        if max_x - min_x >= 360.0 {
+            return world;
+        }
+        let mut boxes = vec![Rect::new(
+            (min_x.max(-180.0), min_y),
+            (max_x.min(180.0), max_y),
+        )];
+        if min_x < -180.0 {
+            boxes.push(Rect::new((min_x + 360.0, min_y), (180.0, max_y)));
+        }
+        if max_x > 180.0 {
+            boxes.push(Rect::new((-180.0, min_y), (max_x - 360.0, max_y)));
+        }
+        boxes
+    }
+
+    // Distance between two geometries, along with the segment joining their closest points
+    pub fn closest(&self, a: &Geometry<f64>, b: &Geometry<f64>) -> Option<(f64, Line<f64>)> {
+        let (p, q) = closest_pair(a, b, self.method)?;
+        let dist = point_distance(&p, &q, self.method);
+        let dist = match self.method {
+            Method::Planar => dist,
+            _ => self.units.length(dist).ok()?,
+        };
+        Some((dist, Line::new(p, q)))
+    }
+}
+
+fn point_distance(a: &Point<f64>, b: &Point<f64>, method: Method) -> f64 {
+    match method {
+        Method::Vincenty => a
+            .vincenty_distance(b)
+            .unwrap_or_else(|_| geodesic::distance(a.0, b.0)),
+        Method::Haversine => a.haversine_distance(b),
+        Method::Geodesic => geodesic::distance(a.0, b.0),
+        Method::Planar => a.euclidean_distance(b),
+    }
+}
 
 fn closest_point_to_poly(point: &Point<f64>, poly: &Polygon<f64>) -> geo::Closest<f64> {
     if poly.contains(point) {
@@ -30,18 +172,16 @@ fn closest_point_to_geom_coll(
         return geo::Closest::Indeterminate;
     }
 
-    let mut measurements: Vec<(geo::Closest<f64>, f64)> =
-        gc.0.iter()
-            .map(|geom| closest_point(point, geom))
-            .map(|closest| match closest {
-                geo::Closest::Indeterminate => (closest, std::f64::INFINITY),
-                geo::Closest::Intersection(_) => (closest, 0.0),
-                geo::Closest::SinglePoint(p) => (
-                    closest,
-                    p.vincenty_distance(point).unwrap_or(std::f64::INFINITY),
-                ),
-            })
-            .collect();
+    let mut measurements: Vec<(geo::Closest<f64>, f64)> = gc
+        .0
+        .iter()
+        .map(|geom| closest_point(point, geom))
+        .map(|closest| match closest {
+            geo::Closest::Indeterminate => (closest, std::f64::INFINITY),
+            geo::Closest::Intersection(_) => (closest, 0.0),
+            geo::Closest::SinglePoint(p) => (closest, point_distance(&p, point, Method::Vincenty)),
+        })
+        .collect();
 
     measurements.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Equal));
 
@@ -63,25 +203,120 @@ fn closest_point(a: &Point<f64>, b: &Geometry<f64>) -> geo::Closest<f64> {
     }
 }
 
-pub fn distance(a: &Point<f64>, b: &Geometry<f64>) -> Option<f64> {
+// Distance from a point to the closest point of a geometry, in meters
+// (or coordinate units for planar distances)
+pub fn distance_by(a: &Point<f64>, b: &Geometry<f64>, method: Method) -> Option<f64> {
     let closest = closest_point(a, b);
     match closest {
         geo::Closest::Intersection(_) => Some(0.0),
-        geo::Closest::SinglePoint(p) => {
-            let vin = p.vincenty_distance(a);
-            match vin {
-                Ok(d) => Some(d),
-                Err(_) => None,
-            }
-        }
+        geo::Closest::SinglePoint(p) => Some(point_distance(&p, a, method)),
         geo::Closest::Indeterminate => None,
     }
 }
 
+pub fn distance(a: &Point<f64>, b: &Geometry<f64>) -> Option<f64> {
+    distance_by(a, b, Method::Vincenty)
+}
+
+fn ring_segments(ls: &LineString<f64>, out: &mut Vec<Line<f64>>) {
+    out.extend(ls.lines());
+}
+
+fn polygon_segments(poly: &Polygon<f64>, out: &mut Vec<Line<f64>>) {
+    ring_segments(poly.exterior(), out);
+    poly.interiors().iter().for_each(|r| ring_segments(r, out));
+}
+
+fn segments(geom: &Geometry<f64>, out: &mut Vec<Line<f64>>) {
+    match geom {
+        Geometry::Line(l) => out.push(*l),
+        Geometry::LineString(ls) => ring_segments(ls, out),
+        Geometry::MultiLineString(mls) => mls.0.iter().for_each(|ls| ring_segments(ls, out)),
+        Geometry::Polygon(poly) => polygon_segments(poly, out),
+        Geometry::MultiPolygon(mp) => mp.0.iter().for_each(|p| polygon_segments(p, out)),
+        Geometry::Triangle(t) => polygon_segments(&t.to_polygon(), out),
+        Geometry::Rect(r) => polygon_segments(&r.to_polygon(), out),
+        Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| segments(g, out)),
+        Geometry::Point(_) | Geometry::MultiPoint(_) => (),
+    }
+}
+
+fn vertices(geom: &Geometry<f64>) -> Vec<Point<f64>> {
+    geom.coords_iter().map(Point).collect()
+}
+
+// A point shared by two intersecting geometries: either a vertex of
+// one which lies on the other, or a point where their edges cross
+fn shared_point(a: &Geometry<f64>, b: &Geometry<f64>) -> Option<Point<f64>> {
+    let on = |p: &Point<f64>, g: &Geometry<f64>| match closest_point(p, g) {
+        geo::Closest::Intersection(_) => true,
+        _ => false,
+    };
+    let vertex = vertices(a)
+        .into_iter()
+        .find(|p| on(p, b))
+        .or_else(|| vertices(b).into_iter().find(|p| on(p, a)));
+    if vertex.is_some() {
+        return vertex;
+    }
+
+    let (mut a_segments, mut b_segments) = (Vec::new(), Vec::new());
+    segments(a, &mut a_segments);
+    segments(b, &mut b_segments);
+    a_segments.iter().find_map(|la| {
+        b_segments
+            .iter()
+            .find_map(|lb| match line_intersection(*la, *lb)? {
+                LineIntersection::SinglePoint { intersection, .. } => Some(Point(intersection)),
+                LineIntersection::Collinear { intersection } => Some(Point(intersection.start)),
+            })
+    })
+}
+
+// The closest pair of points between two geometries, the first on a and the
+// second on b. These are the same point when the geometries intersect.
+pub fn closest_pair(
+    a: &Geometry<f64>,
+    b: &Geometry<f64>,
+    method: Method,
+) -> Option<(Point<f64>, Point<f64>)> {
+    if a.intersects(b) {
+        return shared_point(a, b).map(|p| (p, p));
+    }
+
+    // Unless they intersect, the closest points between two geometries
+    // always include a vertex of one of them
+    let single = |closest: geo::Closest<f64>| match closest {
+        geo::Closest::Intersection(p) | geo::Closest::SinglePoint(p) => Some(p),
+        geo::Closest::Indeterminate => None,
+    };
+    let from_a = vertices(a)
+        .into_iter()
+        .filter_map(|p| single(closest_point(&p, b)).map(|q| (p, q)));
+    let from_b = vertices(b)
+        .into_iter()
+        .filter_map(|q| single(closest_point(&q, a)).map(|p| (p, q)));
+    from_a
+        .chain(from_b)
+        .map(|(p, q)| (p, q, point_distance(&p, &q, method)))
+        .min_by(|x, y| x.2.partial_cmp(&y.2).unwrap_or(Equal))
+        .map(|(p, q, _)| (p, q))
+}
+
+// Minimum distance between two geometries, in meters
+// (or coordinate units for planar distances)
+pub fn geometry_distance(a: &Geometry<f64>, b: &Geometry<f64>, method: Method) -> Option<f64> {
+    closest_pair(a, b, method).map(|(p, q)| point_distance(&p, &q, method))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::geoq::distance::distance;
-    use geo_types::{Geometry, Point, Polygon};
+    use crate::geoq::distance::{
+        closest_pair, distance, distance_by, geometry_distance, Method, Options,
+    };
+    use geo::algorithm::intersects::Intersects;
+    use geo::algorithm::vincenty_distance::VincentyDistance;
+    use geo_types::{polygon, Geometry, LineString, Point, Polygon, Rect};
     use wkt::ToWkt;
 
     #[test]
@@ -117,4 +352,90 @@ mod tests {
             None => assert!(false, "Should get distance"),
         }
     }
+
+    #[test]
+    fn test_methods() {
+        let la = Point::new(-118.2437, 34.0522);
+        let nyg = Geometry::Point(Point::new(-74.0060, 40.7128));
+
+        let haversine = distance_by(&la, &nyg, Method::Haversine).unwrap();
+        assert_eq!((haversine / 1000.0).round(), 3936.);
+        let geodesic = distance_by(&la, &nyg, Method::Geodesic).unwrap();
+        assert_eq!(geodesic.round(), 3944422.);
+        let planar = distance_by(&la, &nyg, Method::Planar).unwrap();
+        assert!((planar - 44.736).abs() < 0.001, "{}", planar);
+
+        let km = Options::parse(None, Some("km")).unwrap();
+        assert_eq!(
+            km.distance(&Geometry::Point(la), &nyg).unwrap().round(),
+            3944.
+        );
+        assert!(Options::parse(Some("planar"), Some("km")).is_err());
+        assert!(Options::parse(None, Some("acres")).is_err());
+    }
+
+    #[test]
+    fn test_nearly_antipodal_points() {
+        // Vincenty's formulae don't converge here
+        let a = Point::new(0.0, 0.0);
+        assert!(a.vincenty_distance(&Point::new(179.7, 0.5)).is_err());
+        let b = Geometry::Point(Point::new(179.7, 0.5));
+        let d = distance(&a, &b).unwrap();
+        assert!((d - 19_944_127.0).abs() < 1.0, "{}", d);
+    }
+
+    #[test]
+    fn test_geometry_to_geometry() {
+        let line = Geometry::LineString(LineString::from(vec![(0.0, 0.0), (0.0, 0.5)]));
+        let poly = Geometry::Polygon(polygon![
+            (x: 1.0, y: 1.0),
+            (x: 2.0, y: 1.0),
+            (x: 2.0, y: 2.0),
+            (x: 1.0, y: 2.0),
+            (x: 1.0, y: 1.0),
+        ]);
+        assert_eq!(
+            Some((Point::new(0.0, 0.5), Point::new(1.0, 1.0))),
+            closest_pair(&line, &poly, Method::Planar)
+        );
+        assert_eq!(
+            Some(1.25f64.sqrt()),
+            geometry_distance(&poly, &line, Method::Planar)
+        );
+        let geodesic = geometry_distance(&line, &poly, Method::Geodesic).unwrap();
+        assert!((geodesic - 124_284.0).abs() < 1.0, "{}", geodesic);
+
+        // Crossing edges, with no vertex of either inside the other
+        let crossing = Geometry::LineString(LineString::from(vec![(0.0, 1.5), (3.0, 1.5)]));
+        let (p, q) = closest_pair(&crossing, &poly, Method::Planar).unwrap();
+        assert_eq!(p, q);
+        assert_eq!(1.5, p.y());
+        assert_eq!(
+            Some(0.0),
+            geometry_distance(&crossing, &poly, Method::Geodesic)
+        );
+    }
+
+    #[test]
+    fn test_search_boxes() {
+        let options = Options::parse(None, Some("km")).unwrap();
+        let point = Rect::new((179.9, 0.0), (179.9, 0.0));
+        let boxes = options.search_boxes(&point, 50.0);
+        assert_eq!(2, boxes.len());
+        assert_eq!(180.0, boxes[0].max().x);
+        assert_eq!(-180.0, boxes[1].min().x);
+        assert!(boxes[1].intersects(&Point::new(-179.9, 0.0)));
+
+        // Boxes reaching a pole cover every longitude
+        let north = Rect::new((10.0, 89.9), (10.0, 89.9));
+        let boxes = options.search_boxes(&north, 50.0);
+        assert_eq!(1, boxes.len());
+        assert_eq!((-180.0, 180.0), (boxes[0].min().x, boxes[0].max().x));
+
+        let planar = Options::parse(Some("planar"), None).unwrap();
+        assert_eq!(
+            vec![Rect::new((0.0, 0.0), (4.0, 4.0))],
+            planar.search_boxes(&Rect::new((1.0, 1.0), (3.0, 3.0)), 1.0)
+        );
+    }
 }
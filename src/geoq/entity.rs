@@ -1,21 +1,24 @@
-use crate::geoq::{bbox, error::Error, input::Input};
+use crate::geoq::{
+    bbox,
+    error::Error,
+    input::{self, Input, InputFormat},
+    zm::Ordinates,
+};
 use geo_types::{Coord, Geometry, LineString, Point, Polygon};
 use geojson::GeoJson;
 use h3ron::ToPolygon;
 use h3ron::{H3Cell, Index};
-use once_cell::sync::Lazy;
-use regex::Regex;
 use serde_json;
 use std::{convert::TryFrom, convert::TryInto, fmt};
 use wkt::ToWkt;
 
-static LATLON_SPLIT: Lazy<Regex> = Lazy::new(|| Regex::new(",|\t").unwrap());
-
 #[derive(Clone)]
 pub enum Entity {
     LatLon(String),
+    LonLat(String),
     Geohash(String),
-    Wkt(String, geo_types::Geometry<f64>),
+    // Z and M values are kept separately, since geo_types geometries are 2D
+    Wkt(String, geo_types::Geometry<f64>, Option<Ordinates>),
     GeoJsonFeature(String, geojson::Feature),
     GeoJsonGeometry(String, geojson::Geometry),
     H3(H3Cell),
@@ -25,8 +28,9 @@ impl fmt::Display for Entity {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Entity::LatLon(ref raw) => write!(f, "LatLon: {}", raw),
+            Entity::LonLat(ref raw) => write!(f, "LonLat: {}", raw),
             Entity::Geohash(ref raw) => write!(f, "Geohash: {}", raw),
-            Entity::Wkt(ref raw, _) => write!(f, "WKT: {}", raw),
+            Entity::Wkt(ref raw, _, _) => write!(f, "WKT: {}", raw),
             Entity::GeoJsonFeature(ref raw, _) => write!(f, "GeoJSON Feature: {}", raw),
             Entity::GeoJsonGeometry(ref raw, _) => write!(f, "GeoJSON Geometry: {}", raw),
             Entity::H3(ref raw) => write!(
@@ -39,10 +43,32 @@ impl fmt::Display for Entity {
     }
 }
 
+// Coordinate rows are validated when they're read, so
+// the first 2 columns will always parse as numbers
+fn coord_pair(raw: &String) -> (f64, f64) {
+    let pieces = input::columns(raw);
+    let a = pieces[0].trim().parse::<f64>().unwrap();
+    let b = pieces[1].trim().parse::<f64>().unwrap();
+    (a, b)
+}
+
+// Columns after the coordinate pair, named by their (1-based) position
+fn extra_columns(raw: &String) -> serde_json::Map<String, serde_json::value::Value> {
+    input::columns(raw)
+        .into_iter()
+        .enumerate()
+        .skip(2)
+        .map(|(i, col)| (format!("col{}", i + 1), col.into()))
+        .collect()
+}
+
 fn latlon_geom(raw: &String) -> geo_types::Geometry<f64> {
-    let pieces = LATLON_SPLIT.split(raw).collect::<Vec<&str>>();
-    let lat = pieces[0].parse::<f64>().unwrap();
-    let lon = pieces[1].parse::<f64>().unwrap();
+    let (lat, lon) = coord_pair(raw);
+    Geometry::Point(Point::new(lon, lat))
+}
+
+fn lonlat_geom(raw: &String) -> geo_types::Geometry<f64> {
+    let (lon, lat) = coord_pair(raw);
     Geometry::Point(Point::new(lon, lat))
 }
 
@@ -73,10 +99,19 @@ fn wkt_entities(raw: &String) -> Result<Vec<Entity>, Error> {
             for wkt_geom in wkts.items {
                 let wkt_raw = wkt_geom.to_string();
                 let geom: Geometry<f64> = wkt_geom.try_into().unwrap();
-                entities.push(Entity::Wkt(wkt_raw, geom))
+                entities.push(Entity::Wkt(wkt_raw, geom, None))
             }
         }
-        Err(_e) => return Err(Error::InvalidWkt),
+        // The wkt crate only reads 2D coordinates, so check for
+        // Z or M values before giving up on the input
+        Err(e) => match Ordinates::split_wkt(raw) {
+            Some((flat, ords)) => {
+                for geom in wkt_entities(&flat)?.into_iter().map(|e| e.geom()) {
+                    entities.push(Entity::Wkt(ords.wkt(&geom), geom, Some(ords.clone())))
+                }
+            }
+            None => return Err(Error::InvalidWkt(e.to_string())),
+        },
     }
     Ok(entities)
 }
@@ -96,13 +131,18 @@ fn parsed_geojson_entities(raw: String, gj: GeoJson) -> Vec<Entity> {
     }
 }
 
-fn geojson_entities(raw: String) -> Result<Vec<Entity>, Error> {
+// Replaces a Feature's raw text with its serialized form, as given for the
+// members of a FeatureCollection
+pub fn reserialize(e: &mut Entity) {
+    if let Entity::GeoJsonFeature(ref mut raw, ref f) = *e {
+        *raw = serde_json::to_string(f).unwrap();
+    }
+}
+
+fn geojson_entities(raw: String) -> Result<Vec<Entity>, (Error, String)> {
     match raw.parse() {
         Ok(gj) => Ok(parsed_geojson_entities(raw, gj)),
-        Err(e) => {
-            eprintln!("Error parsing geojson: {} - {}", raw, e);
-            Err(Error::InvalidGeoJSON)
-        }
+        Err(e) => Err((Error::InvalidGeoJSON(e.to_string()), raw)),
     }
 }
 
@@ -110,9 +150,10 @@ impl Entity {
     pub fn geom(&self) -> geo_types::Geometry<f64> {
         match self {
             Entity::LatLon(ref raw) => latlon_geom(raw),
+            Entity::LonLat(ref raw) => lonlat_geom(raw),
             Entity::Geohash(ref raw) => geohash_geom(raw),
             Entity::H3(ref cell) => h3_geom(cell),
-            Entity::Wkt(_, ref geom) => geom.clone(),
+            Entity::Wkt(_, ref geom, _) => geom.clone(),
             Entity::GeoJsonGeometry(_, gj_geom) => match gj_geom.value.clone() {
                 geojson::Value::GeometryCollection(gj_geoms) => {
                     let geoms: Vec<geo_types::Geometry<f64>> = gj_geoms
@@ -139,6 +180,27 @@ impl Entity {
         wkt.items.pop().unwrap()
     }
 
+    // Z and M values from the input, if it had any
+    pub fn ordinates(&self) -> Option<Ordinates> {
+        match self {
+            Entity::Wkt(_, _, ords) => ords.clone(),
+            Entity::GeoJsonGeometry(_, gj_geom) => Ordinates::from_geojson(&gj_geom.value),
+            Entity::GeoJsonFeature(_, gj_feature) => gj_feature
+                .geometry
+                .as_ref()
+                .and_then(|g| Ordinates::from_geojson(&g.value)),
+            _ => None,
+        }
+    }
+
+    // WKT text for the entity, including any Z and M values
+    pub fn wkt_string(&self) -> String {
+        match self.ordinates() {
+            Some(ords) => ords.wkt(&self.geom()),
+            None => self.wkt().to_string(),
+        }
+    }
+
     pub fn bbox(&self) -> geo::Rect<f64> {
         let geom = self.geom();
         bbox::bbox(&geom)
@@ -146,7 +208,10 @@ impl Entity {
 
     pub fn geojson_geometry(&self) -> geojson::Geometry {
         let geom = self.geom();
-        geojson::Geometry::new(geojson::Value::from(&geom))
+        match self.ordinates() {
+            Some(ords) => geojson::Geometry::new(ords.geojson_value(&geom)),
+            None => geojson::Geometry::new(geojson::Value::from(&geom)),
+        }
     }
 
     pub fn geojson_properties(&self) -> serde_json::Map<String, serde_json::value::Value> {
@@ -158,6 +223,7 @@ impl Entity {
                     serde_json::Map::new()
                 }
             }
+            Entity::LatLon(ref raw) | Entity::LonLat(ref raw) => extra_columns(raw),
             Entity::H3(cell) => {
                 let mut props = serde_json::Map::new();
                 props.insert("resolution".to_string(), cell.resolution().into());
@@ -181,11 +247,28 @@ impl Entity {
         }
     }
 
+    // A Feature with a new geometry (e.g. the result of an operation on this
+    // entity's geometry), keeping the entity's properties, id and foreign members
+    pub fn with_geometry(&self, geom: geojson::Geometry) -> geojson::Feature {
+        let (id, foreign_members) = match self {
+            Entity::GeoJsonFeature(_, f) => (f.id.clone(), f.foreign_members.clone()),
+            _ => (None, None),
+        };
+        geojson::Feature {
+            bbox: None,
+            geometry: Some(geom),
+            id,
+            properties: Some(self.geojson_properties()),
+            foreign_members,
+        }
+    }
+
     pub fn raw(&self) -> String {
         match *self {
             Entity::LatLon(ref raw) => raw.clone(),
+            Entity::LonLat(ref raw) => raw.clone(),
             Entity::Geohash(ref raw) => raw.clone(),
-            Entity::Wkt(ref raw, _) => raw.clone(),
+            Entity::Wkt(ref raw, _, _) => raw.clone(),
             Entity::GeoJsonGeometry(ref raw, _) => raw.clone(),
             Entity::GeoJsonFeature(ref raw, _) => raw.clone(),
             Entity::H3(ref cell) => cell.to_string(),
@@ -195,17 +278,38 @@ impl Entity {
 
 use std::str::FromStr;
 pub fn from_input(i: Input) -> Result<Vec<Entity>, Error> {
+    try_from_input(i).map_err(|(e, _)| e)
+}
+
+// Geometry for a WKT string, for use in tests
+#[cfg(test)]
+pub fn wkt_geom(wkt: &str) -> Geometry<f64> {
+    wkt_entities(&wkt.to_string()).unwrap().remove(0).geom()
+}
+
+// Entities for a line of input in the given format. On failure the line is
+// given back along with the error, so it can be reported without keeping a
+// copy of every input.
+pub fn read(line: String, format: InputFormat) -> Result<Vec<Entity>, (Error, String)> {
+    input::try_read_line_as(line, format).and_then(try_from_input)
+}
+
+fn try_from_input(i: Input) -> Result<Vec<Entity>, (Error, String)> {
     match i {
         Input::LatLon(raw) => Ok(vec![Entity::LatLon(raw)]),
+        Input::LonLat(raw) => Ok(vec![Entity::LonLat(raw)]),
         Input::Geohash(raw) => Ok(vec![Entity::Geohash(raw)]),
-        Input::WKT(raw) => wkt_entities(&raw),
+        Input::WKT(raw) => wkt_entities(&raw).map_err(|e| (e, raw)),
         Input::GeoJSON(raw) => geojson_entities(raw),
         Input::H3(raw) => match H3Cell::from_str(&raw) {
             Ok(cell) => Ok(vec![Entity::H3(cell)]),
-            Err(e) => Err(Error::InvalidInput(format!(
-                "Unable to parse String as H3 Cell: {} - {}",
-                raw, e
-            ))),
+            Err(e) => Err((
+                Error::InvalidInput(format!(
+                    "Unable to parse String as H3 Cell: {} - {}",
+                    raw, e
+                )),
+                raw,
+            )),
         },
     }
 }
@@ -280,6 +384,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entities_for_lonlat() {
+        check(Input::LonLat("34,12".to_string()),
+              vec!["34,12"],
+              vec![Geometry::Point(Point::new(34.0, 12.0))],
+              vec!["POINT(34 12)"],
+              vec!["{\"coordinates\":[34.0,12.0],\"type\":\"Point\"}"],
+              vec![serde_json::Map::new()],
+              vec!["{\"geometry\":{\"coordinates\":[34.0,12.0],\"type\":\"Point\"},\"properties\":{},\"type\":\"Feature\"}"]
+        );
+    }
+
+    #[test]
+    fn entities_with_extra_columns() {
+        let mut props = serde_json::Map::new();
+        props.insert("col3".to_string(), "2020-01-01T00:00:00Z".into());
+        props.insert("col4".to_string(), "abc".into());
+        check(Input::LonLat("34,12,2020-01-01T00:00:00Z,abc".to_string()),
+              vec!["34,12,2020-01-01T00:00:00Z,abc"],
+              vec![Geometry::Point(Point::new(34.0, 12.0))],
+              vec!["POINT(34 12)"],
+              vec!["{\"coordinates\":[34.0,12.0],\"type\":\"Point\"}"],
+              vec![props],
+              vec!["{\"geometry\":{\"coordinates\":[34.0,12.0],\"type\":\"Point\"},\"properties\":{\"col3\":\"2020-01-01T00:00:00Z\",\"col4\":\"abc\"},\"type\":\"Feature\"}"]
+        );
+    }
+
     #[test]
     fn entities_for_geohash() {
         let exp_poly = Polygon::new(
@@ -321,6 +452,24 @@ mod tests {
               vec!["{\"geometry\":{\"coordinates\":[[30.0,10.0],[10.0,30.0],[40.0,40.0]],\"type\":\"LineString\"},\"properties\":{},\"type\":\"Feature\"}"]);
     }
 
+    #[test]
+    fn entities_for_wkt_with_z() {
+        let exp_geom = LineString(vec![Coord::from((30.0, 10.0)), Coord::from((10.0, 30.0))]);
+        check(Input::WKT("LINESTRING Z (30 10 1.5, 10 30 2)".to_string()),
+              vec!["LINESTRING Z(30 10 1.5,10 30 2)"],
+              vec![Geometry::LineString(exp_geom)],
+              vec!["LINESTRING(30 10,10 30)"],
+              vec!["{\"coordinates\":[[30.0,10.0,1.5],[10.0,30.0,2.0]],\"type\":\"LineString\"}"],
+              vec![serde_json::Map::new()],
+              vec!["{\"geometry\":{\"coordinates\":[[30.0,10.0,1.5],[10.0,30.0,2.0]],\"type\":\"LineString\"},\"properties\":{},\"type\":\"Feature\"}"]);
+        let e = entities(&Input::WKT("POINT ZM (1 2 3 4)".to_string())).remove(0);
+        assert_eq!("POINT ZM(1 2 3 4)", e.wkt_string());
+        assert_eq!(
+            "{\"coordinates\":[1.0,2.0,3.0,4.0],\"type\":\"Point\"}",
+            serde_json::to_string(&e.geojson_geometry()).unwrap()
+        );
+    }
+
     #[test]
     fn entities_for_geojson_geometry() {
         let exp_geom = LineString(
@@ -398,4 +547,21 @@ mod tests {
               ]
         )
     }
+
+    #[test]
+    fn feature_with_new_geometry() {
+        let raw = r#"{"type":"Feature","id":"a1","properties":{"pizza":"pie"},"geometry":{"type":"Point","coordinates":[1,2]},"source":"test"}"#;
+        let e = entities(&Input::GeoJSON(raw.to_string())).remove(0);
+        let geom = geojson::Geometry::new(geojson::Value::Point(vec![3.0, 4.0]));
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[3.0,4.0],"type":"Point"},"id":"a1","properties":{"pizza":"pie"},"source":"test","type":"Feature"}"#,
+            serde_json::to_string(&e.with_geometry(geom.clone())).unwrap()
+        );
+
+        let e = entities(&Input::LatLon("12,34,abc".to_string())).remove(0);
+        assert_eq!(
+            r#"{"geometry":{"coordinates":[3.0,4.0],"type":"Point"},"properties":{"col3":"abc"},"type":"Feature"}"#,
+            serde_json::to_string(&e.with_geometry(geom)).unwrap()
+        );
+    }
 }
@@ -1,24 +1,24 @@
-use std::{convert::From, io, str::Utf8Error};
+use std::{convert::From, fmt, io, str::Utf8Error};
 
 use geozero::error::GeozeroError;
 
 #[derive(Debug)]
 pub enum Error {
     // InvalidLatLon,
-    InvalidGeoJSON,
+    InvalidGeoJSON(String),
     NotImplemented,
     UnknownCommand,
     UnknownEntityFormat,
-    InvalidWkt,
+    InvalidWkt(String),
     MissingArgument,
     InvalidNumberFormat(String),
     InputTooLarge,
-    IOError,
-    JSONParseError,
+    IOError(io::Error),
+    JSONParseError(serde_json::Error),
     InvalidJSONType,
     PolygonRequired,
     IPGeolocationError,
-    HTTPError,
+    HTTPError(reqwest::Error),
     TooManyFeatures,
     PointRequired,
     DistanceFailed,
@@ -27,11 +27,127 @@ pub enum Error {
     ShapefileReaderError(String),
     ProgramError(String),
     InvalidInput(String),
+    // An error caused by a specific input, with the (1-based) line
+    // it started on and a snippet of its raw text
+    AtInput {
+        line: usize,
+        snippet: String,
+        source: Box<Error>,
+    },
+}
+
+// Longest piece of an input's raw text to include in error messages
+const SNIPPET_LENGTH: usize = 80;
+
+pub fn snippet(raw: &str) -> String {
+    match raw.char_indices().nth(SNIPPET_LENGTH) {
+        Some((i, _)) => format!("{}...", &raw[0..i]),
+        None => raw.to_string(),
+    }
+}
+
+impl Error {
+    // Attach the location and text of the input which caused this error
+    pub fn at_input(self, line: usize, raw: &str) -> Error {
+        match self {
+            Error::AtInput { .. } => self,
+            _ => Error::AtInput {
+                line,
+                snippet: snippet(raw),
+                source: Box::new(self),
+            },
+        }
+    }
+
+    // Stable identifier for the kind of error, for use in
+    // error reports which may be consumed by other tools
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidGeoJSON(_) => "invalid-geojson",
+            Error::NotImplemented => "not-implemented",
+            Error::UnknownCommand => "unknown-command",
+            Error::UnknownEntityFormat => "unknown-entity-format",
+            Error::InvalidWkt(_) => "invalid-wkt",
+            Error::MissingArgument => "missing-argument",
+            Error::InvalidNumberFormat(_) => "invalid-number-format",
+            Error::InputTooLarge => "input-too-large",
+            Error::IOError(_) => "io-error",
+            Error::JSONParseError(_) => "json-parse-error",
+            Error::InvalidJSONType => "invalid-json-type",
+            Error::PolygonRequired => "polygon-required",
+            Error::IPGeolocationError => "ip-geolocation-error",
+            Error::HTTPError(_) => "http-error",
+            Error::TooManyFeatures => "too-many-features",
+            Error::PointRequired => "point-required",
+            Error::DistanceFailed => "distance-failed",
+            Error::InvalidGeohashPoint => "invalid-geohash-point",
+            Error::NoInputGiven => "no-input-given",
+            Error::ShapefileReaderError(_) => "shapefile-reader-error",
+            Error::ProgramError(_) => "program-error",
+            Error::InvalidInput(_) => "invalid-input",
+            Error::AtInput { source, .. } => source.code(),
+        }
+    }
+
+    // Full message for the error, including its chain of causes
+    pub fn report(&self) -> String {
+        let mut message = format!("{} [{}]", self, self.code());
+        let mut cause = std::error::Error::source(self);
+        while let Some(e) = cause {
+            message.push_str(&format!("\n  caused by: {}", e));
+            cause = e.source();
+        }
+        message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidGeoJSON(msg) => write!(f, "Invalid GeoJSON: {}", msg),
+            Error::NotImplemented => write!(f, "Operation not supported for this input"),
+            Error::UnknownCommand => write!(f, "Unknown command"),
+            Error::UnknownEntityFormat => write!(f, "Unrecognized input format"),
+            Error::InvalidWkt(msg) => write!(f, "Invalid WKT: {}", msg),
+            Error::MissingArgument => write!(f, "Missing required argument"),
+            Error::InvalidNumberFormat(msg) => write!(f, "Invalid number: {}", msg),
+            Error::InputTooLarge => write!(f, "Input too large"),
+            Error::IOError(_) => write!(f, "I/O error"),
+            Error::JSONParseError(_) => write!(f, "Unable to parse JSON"),
+            Error::InvalidJSONType => write!(f, "Unexpected JSON type"),
+            Error::PolygonRequired => write!(f, "Input must be a Polygon or MultiPolygon"),
+            Error::IPGeolocationError => write!(f, "Unable to fetch IP geolocation"),
+            Error::HTTPError(_) => write!(f, "HTTP request failed"),
+            Error::TooManyFeatures => write!(f, "Too many features"),
+            Error::PointRequired => write!(f, "Input must be a Point"),
+            Error::DistanceFailed => write!(f, "Unable to calculate distance"),
+            Error::InvalidGeohashPoint => write!(f, "Geohash input must be a Point"),
+            Error::NoInputGiven => write!(f, "No input given"),
+            Error::ShapefileReaderError(msg) => write!(f, "Error reading shapefile: {}", msg),
+            Error::ProgramError(msg) => write!(f, "{}", msg),
+            Error::InvalidInput(msg) => write!(f, "{}", msg),
+            Error::AtInput { line, snippet, .. } => {
+                write!(f, "Invalid input on line {}: {}", line, snippet)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IOError(e) => Some(e),
+            Error::JSONParseError(e) => Some(e),
+            Error::HTTPError(e) => Some(e),
+            Error::AtInput { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
-    fn from(_: io::Error) -> Self {
-        Error::IOError
+    fn from(e: io::Error) -> Self {
+        Error::IOError(e)
     }
 }
 
@@ -42,14 +158,14 @@ impl From<h3ron::Error> for Error {
 }
 
 impl From<serde_json::Error> for Error {
-    fn from(_: serde_json::Error) -> Self {
-        Error::JSONParseError
+    fn from(e: serde_json::Error) -> Self {
+        Error::JSONParseError(e)
     }
 }
 
 impl From<reqwest::Error> for Error {
-    fn from(_: reqwest::Error) -> Self {
-        Error::HTTPError
+    fn from(e: reqwest::Error) -> Self {
+        Error::HTTPError(e)
     }
 }
 
@@ -64,3 +180,37 @@ impl From<std::str::Utf8Error> for Error {
         Error::ProgramError(format!("{}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{snippet, Error};
+    use std::io;
+
+    #[test]
+    fn test_input_errors_carry_line_and_cause() {
+        let err = Error::InvalidInput("Unable to parse single-line input: pizza".to_string())
+            .at_input(12, "pizza");
+        assert_eq!("invalid-input", err.code());
+        assert_eq!(
+            "Invalid input on line 12: pizza [invalid-input]\n  caused by: Unable to parse single-line input: pizza",
+            err.report()
+        );
+    }
+
+    #[test]
+    fn test_io_errors_keep_source() {
+        let err: Error = io::Error::new(io::ErrorKind::NotFound, "no such file").into();
+        assert_eq!("io-error", err.code());
+        assert_eq!(
+            "I/O error [io-error]\n  caused by: no such file",
+            err.report()
+        );
+    }
+
+    #[test]
+    fn test_long_inputs_are_truncated() {
+        let raw = "a".repeat(100);
+        assert_eq!(format!("{}...", "a".repeat(80)), snippet(&raw));
+        assert_eq!("9q5", snippet("9q5"));
+    }
+}
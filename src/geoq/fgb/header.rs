@@ -1,4 +1,4 @@
-use crate::geoq::{fgb::index, geojson::fvec};
+use crate::geoq::{fgb::index, geojson::fvec, zm::Ordinates};
 
 use super::columns;
 use super::hilbert::BBox;
@@ -53,6 +53,13 @@ fn geometry_type(features: &Vec<BoundedFeature>) -> GeometryType {
     }
 }
 
+fn has_z(features: &Vec<BoundedFeature>) -> bool {
+    features.iter().any(|bf| match &bf.feature.geometry {
+        Some(geom) => Ordinates::from_geojson(&geom.value).is_some(),
+        None => false,
+    })
+}
+
 #[derive(Clone, Debug)]
 pub struct ColSpec {
     pub name: String,
@@ -168,6 +175,7 @@ pub fn write<'a>(
         name: Some(name),
         features_count: features.len().try_into().unwrap(), // not sure when this would fail...i guess 128bit system?
         geometry_type: geometry_type(features),
+        has_z: has_z(features),
         index_node_size: index::NODE_SIZE,
         columns: cols_vec,
         envelope: Some(bounds_vec),
@@ -119,6 +119,9 @@ mod tests {
     const GEOMETRY_COLLECTION: &str = r#"
       {"type":"GeometryCollection","geometries":[{"type":"Point","coordinates":[40,10]},{"type":"LineString","coordinates":[[-118,34],[-119,35]]}]}
     "#;
+    const LINESTRING_Z: &str = r#"
+     {"type": "LineString", "coordinates": [[-118, 34, 100.5], [-119, 35, 200]]}
+    "#;
     const POINT_PROPS: &str = r#"
       {"type":"Feature","properties": {"name": "\"pizza"},"geometry": {"type": "Point", "coordinates": [-118, 34]}}
     "#;
@@ -256,6 +259,19 @@ mod tests {
         assert_eq!(bounds, vec![-118.0, 34.0, -118.0, 34.0]);
     }
 
+    #[test]
+    fn test_header_z() {
+        let ser = write(fvec(LINESTRING_Z));
+        let mut buf = Cursor::new(ser);
+        let res = FgbReader::open(&mut buf).expect("Round trip...");
+        assert!(res.header().has_z());
+
+        let ser = write(fvec(LINESTRING));
+        let mut buf = Cursor::new(ser);
+        let res = FgbReader::open(&mut buf).expect("Round trip...");
+        assert!(!res.header().has_z());
+    }
+
     // This seems to actually work, based on writing a file and comparing to the Node impl
     // But it is behaving strangely in this test environment using the geozero helpers
     // to round-trip it
@@ -0,0 +1,239 @@
+use crate::geoq::error::Error;
+use geo_types::{Coord, Geometry, LineString, Polygon};
+use geographiclib_rs::{DirectGeodesic, Geodesic, InverseGeodesic, PolygonArea, Winding};
+use once_cell::sync::Lazy;
+use std::str::FromStr;
+
+// Karney's algorithms on the WGS84 ellipsoid, via geographiclib
+static WGS84: Lazy<Geodesic> = Lazy::new(Geodesic::wgs84);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Meters,
+    Kilometers,
+    Miles,
+    NauticalMiles,
+    Feet,
+    Hectares,
+    Acres,
+}
+
+pub const UNITS: [&str; 7] = ["m", "km", "mi", "nm", "ft", "ha", "acres"];
+// Units for distances, which can't be given in hectares or acres
+pub const LENGTH_UNITS: [&str; 5] = ["m", "km", "mi", "nm", "ft"];
+
+impl FromStr for Unit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "m" => Ok(Unit::Meters),
+            "km" => Ok(Unit::Kilometers),
+            "mi" => Ok(Unit::Miles),
+            "nm" => Ok(Unit::NauticalMiles),
+            "ft" => Ok(Unit::Feet),
+            "ha" => Ok(Unit::Hectares),
+            "acres" => Ok(Unit::Acres),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --units value: {}. Expected one of: {}.",
+                s,
+                UNITS.join(", ")
+            ))),
+        }
+    }
+}
+
+impl Unit {
+    // Convert a length in meters. Hectares and acres only measure area.
+    pub fn length(&self, meters: f64) -> Result<f64, Error> {
+        match self {
+            Unit::Meters => Ok(meters),
+            Unit::Kilometers => Ok(meters / 1000.0),
+            Unit::Miles => Ok(meters / 1609.344),
+            Unit::NauticalMiles => Ok(meters / 1852.0),
+            Unit::Feet => Ok(meters / 0.3048),
+            Unit::Hectares | Unit::Acres => Err(Error::InvalidInput(
+                "Units ha and acres can only be used for area".to_string(),
+            )),
+        }
+    }
+
+    // Convert an area in square meters. Length units give the
+    // corresponding square unit, e.g. km gives square kilometers.
+    pub fn area(&self, square_meters: f64) -> f64 {
+        match self {
+            Unit::Meters => square_meters,
+            Unit::Kilometers => square_meters / 1_000_000.0,
+            Unit::Miles => square_meters / (1609.344 * 1609.344),
+            Unit::NauticalMiles => square_meters / (1852.0 * 1852.0),
+            Unit::Feet => square_meters / (0.3048 * 0.3048),
+            Unit::Hectares => square_meters / 10_000.0,
+            Unit::Acres => square_meters / 4046.8564224,
+        }
+    }
+}
+
+// Distance in meters between two lon/lat coordinates. Unlike Vincenty's
+// formulae, this converges for all pairs of points, including antipodal ones.
+pub fn distance(a: Coord<f64>, b: Coord<f64>) -> f64 {
+    WGS84.inverse(a.y, a.x, b.y, b.x)
+}
+
+// Azimuthal equidistant projection around a center point, in meters.
+// Distances and azimuths from the center are exact, so circles around it
+// are true geodesic circles, and shapes near it are only slightly distorted.
+pub struct Projection {
+    center: Coord<f64>,
+}
+
+impl Projection {
+    pub fn new(center: Coord<f64>) -> Projection {
+        Projection { center }
+    }
+
+    pub fn project(&self, c: Coord<f64>) -> Coord<f64> {
+        let (s12, azi1, _azi2, _a12): (f64, f64, f64, f64) =
+            WGS84.inverse(self.center.y, self.center.x, c.y, c.x);
+        let azimuth = azi1.to_radians();
+        Coord {
+            x: s12 * azimuth.sin(),
+            y: s12 * azimuth.cos(),
+        }
+    }
+
+    pub fn unproject(&self, c: Coord<f64>) -> Coord<f64> {
+        let s12 = (c.x * c.x + c.y * c.y).sqrt();
+        let azimuth = c.x.atan2(c.y).to_degrees();
+        let (lat, lon): (f64, f64) = WGS84.direct(self.center.y, self.center.x, azimuth, s12);
+        // Wrap longitudes into -180..180, so shapes reaching past the
+        // antimeridian come back on the other side of it
+        Coord {
+            x: (lon + 540.0).rem_euclid(360.0) - 180.0,
+            y: lat,
+        }
+    }
+}
+
+fn coords_length(coords: &[Coord<f64>]) -> f64 {
+    coords
+        .windows(2)
+        .map(|pair| distance(pair[0], pair[1]))
+        .sum()
+}
+
+fn ring_area(ring: &LineString<f64>) -> f64 {
+    let mut pa = PolygonArea::new(&WGS84, Winding::CounterClockwise);
+    let coords = &ring.0;
+    // PolygonArea closes the ring itself
+    let open = match (coords.first(), coords.last()) {
+        (Some(first), Some(last)) if coords.len() > 1 && first == last => {
+            &coords[..coords.len() - 1]
+        }
+        _ => &coords[..],
+    };
+    if open.len() < 3 {
+        return 0.0;
+    }
+    open.iter().for_each(|c| pa.add_point(c.y, c.x));
+    let (_perimeter, area, _count) = pa.compute(true);
+    area.abs()
+}
+
+fn polygon_area(poly: &Polygon<f64>) -> f64 {
+    let holes: f64 = poly.interiors().iter().map(ring_area).sum();
+    (ring_area(poly.exterior()) - holes).max(0.0)
+}
+
+fn polygon_perimeter(poly: &Polygon<f64>) -> f64 {
+    coords_length(&poly.exterior().0)
+        + poly
+            .interiors()
+            .iter()
+            .map(|ring| coords_length(&ring.0))
+            .sum::<f64>()
+}
+
+// Area in square meters. Points and lines have no area.
+pub fn area(geom: &Geometry<f64>) -> f64 {
+    match geom {
+        Geometry::Polygon(poly) => polygon_area(poly),
+        Geometry::MultiPolygon(mp) => mp.0.iter().map(polygon_area).sum(),
+        Geometry::Rect(r) => polygon_area(&r.to_polygon()),
+        Geometry::Triangle(t) => polygon_area(&t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc.0.iter().map(area).sum(),
+        _ => 0.0,
+    }
+}
+
+// Length of lines, or perimeter of polygons (including their holes), in meters
+pub fn length(geom: &Geometry<f64>) -> f64 {
+    match geom {
+        Geometry::Line(l) => coords_length(&[l.start, l.end]),
+        Geometry::LineString(ls) => coords_length(&ls.0),
+        Geometry::MultiLineString(mls) => mls.0.iter().map(|ls| coords_length(&ls.0)).sum(),
+        Geometry::Polygon(poly) => polygon_perimeter(poly),
+        Geometry::MultiPolygon(mp) => mp.0.iter().map(polygon_perimeter).sum(),
+        Geometry::Rect(r) => polygon_perimeter(&r.to_polygon()),
+        Geometry::Triangle(t) => polygon_perimeter(&t.to_polygon()),
+        Geometry::GeometryCollection(gc) => gc.0.iter().map(length).sum(),
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{area, length, Projection, Unit};
+    use geo_types::{polygon, Coord, Geometry, LineString};
+
+    #[test]
+    fn test_projection() {
+        let projection = Projection::new(Coord { x: 179.5, y: 10.0 });
+        let east = projection.project(Coord { x: 179.6, y: 10.0 });
+        assert!((east.x - 10_964.0).abs() < 1.0 && east.y.abs() < 10.0);
+        let back = projection.unproject(east);
+        assert!((back.x - 179.6).abs() < 1e-9 && (back.y - 10.0).abs() < 1e-9);
+        let across = projection.unproject(Coord {
+            x: 110_000.0,
+            y: 0.0,
+        });
+        assert!((-180.0..-179.0).contains(&across.x), "{}", across.x);
+    }
+
+    #[test]
+    fn test_length() {
+        // One degree of longitude along the equator
+        let line = Geometry::LineString(LineString::from(vec![(0.0, 0.0), (1.0, 0.0)]));
+        assert!((length(&line) - 111_319.49).abs() < 0.01);
+        assert!((Unit::Kilometers.length(length(&line)).unwrap() - 111.319).abs() < 0.001);
+        assert!(Unit::Acres.length(1.0).is_err());
+    }
+
+    #[test]
+    fn test_area() {
+        let square = Geometry::Polygon(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 1.0, y: 0.0),
+            (x: 1.0, y: 1.0),
+            (x: 0.0, y: 1.0),
+            (x: 0.0, y: 0.0),
+        ]);
+        let sq_km = Unit::Kilometers.area(area(&square));
+        assert!((sq_km - 12_308.78).abs() < 0.01, "{}", sq_km);
+
+        // Winding doesn't change the area
+        let cw = Geometry::Polygon(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 0.0, y: 1.0),
+            (x: 1.0, y: 1.0),
+            (x: 1.0, y: 0.0),
+            (x: 0.0, y: 0.0),
+        ]);
+        assert_eq!(area(&square), area(&cw));
+
+        assert!(
+            (length(&square) - 443_770.0).abs() < 10.0,
+            "{}",
+            length(&square)
+        );
+    }
+}
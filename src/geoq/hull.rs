@@ -0,0 +1,167 @@
+use geo::algorithm::area::Area;
+use geo::algorithm::concave_hull::ConcaveHull;
+use geo::algorithm::convex_hull::ConvexHull;
+use geo::algorithm::coords_iter::CoordsIter;
+use geo_types::{Coord, Geometry, LineString, MultiPoint, Point, Polygon};
+
+// Once this many points are waiting, a convex hull drops the ones inside it
+const REDUCE_AT: usize = 1024;
+
+// Gathers points from any number of geometries, and wraps a hull around them.
+// Concave hulls are given a concavity: edges of the convex hull are dug into
+// while they're longer than this many times the distance to the nearest point
+// inside, so lower values give tighter shapes. Around 2.0 is a good start.
+pub struct Hull {
+    concavity: Option<f64>,
+    points: Vec<Coord<f64>>,
+}
+
+impl Hull {
+    pub fn new(concavity: Option<f64>) -> Hull {
+        Hull {
+            concavity,
+            points: vec![],
+        }
+    }
+
+    pub fn add(&mut self, geom: &Geometry<f64>) {
+        self.points.extend(geom.coords_iter());
+        if self.concavity.is_none() && self.points.len() >= REDUCE_AT {
+            self.reduce();
+        }
+    }
+
+    // Keeps only the corners of the convex hull, which give the same hull
+    fn reduce(&mut self) {
+        dedup(&mut self.points);
+        if self.points.len() < 3 {
+            return;
+        }
+        let hull = MultiPoint::from(self.points.clone()).convex_hull();
+        if hull.unsigned_area() > 0.0 {
+            self.points = hull.exterior().0.clone();
+        }
+    }
+
+    // A Polygon, or a Point or LineString when all the points lie on one spot or line
+    pub fn finish(mut self) -> Option<Geometry<f64>> {
+        dedup(&mut self.points);
+        match self.points.len() {
+            0 => None,
+            1 => Some(Geometry::Point(Point(self.points[0]))),
+            n => {
+                let points = MultiPoint::from(self.points.clone());
+                let convex = points.convex_hull();
+                if n == 2 || convex.unsigned_area() == 0.0 {
+                    // Sorted points on a line run from one end to the other
+                    let ends = vec![self.points[0], self.points[n - 1]];
+                    return Some(Geometry::LineString(LineString(ends)));
+                }
+                let hull: Polygon<f64> = match self.concavity {
+                    Some(concavity) => points.concave_hull(concavity),
+                    None => convex,
+                };
+                Some(Geometry::Polygon(hull))
+            }
+        }
+    }
+}
+
+fn dedup(points: &mut Vec<Coord<f64>>) {
+    points.sort_by(|a, b| {
+        a.x.partial_cmp(&b.x)
+            .unwrap()
+            .then(a.y.partial_cmp(&b.y).unwrap())
+    });
+    points.dedup();
+}
+
+pub fn hull(geom: &Geometry<f64>, concavity: Option<f64>) -> Option<Geometry<f64>> {
+    let mut hull = Hull::new(concavity);
+    hull.add(geom);
+    hull.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hull, Hull};
+    use geo::algorithm::area::Area;
+    use geo_types::{line_string, point, Geometry, LineString, MultiPoint, Point};
+
+    fn l_shape() -> Geometry<f64> {
+        let mut points = vec![];
+        for i in 0..=10 {
+            for j in 0..=10 {
+                if i <= 2 || j <= 2 {
+                    points.push(Point::new(i as f64, j as f64));
+                }
+            }
+        }
+        Geometry::MultiPoint(MultiPoint(points))
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        match hull(&l_shape(), None) {
+            Some(Geometry::Polygon(p)) => assert_eq!(68.0, p.unsigned_area()),
+            other => panic!("Expected a Polygon, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_concave_hull() {
+        match hull(&l_shape(), Some(1.0)) {
+            Some(Geometry::Polygon(p)) => {
+                assert!(p.unsigned_area() < 68.0);
+                assert!(p.unsigned_area() >= 36.0);
+            }
+            other => panic!("Expected a Polygon, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_degenerate_hulls() {
+        let p = Geometry::Point(point!(x: 1.0, y: 1.0));
+        assert_eq!(Some(p.clone()), hull(&p, None));
+
+        let line = Geometry::LineString(
+            line_string![(x: 2.0, y: 2.0), (x: 0.0, y: 0.0), (x: 1.0, y: 1.0)],
+        );
+        assert_eq!(
+            Some(Geometry::LineString(
+                line_string![(x: 0.0, y: 0.0), (x: 2.0, y: 2.0)]
+            )),
+            hull(&line, None)
+        );
+
+        assert_eq!(None, Hull::new(None).finish());
+    }
+
+    #[test]
+    fn test_reducing_points() {
+        let mut all = Hull::new(None);
+        for i in 0..2000 {
+            let angle = i as f64 * 0.1;
+            all.add(&Geometry::Point(
+                point!(x: angle.cos() * (i % 7) as f64, y: angle.sin() * (i % 7) as f64),
+            ));
+        }
+        all.add(&Geometry::LineString(LineString::from(vec![
+            (-10.0, 0.0),
+            (10.0, 0.0),
+        ])));
+        match all.finish() {
+            Some(Geometry::Polygon(p)) => {
+                assert!(p
+                    .exterior()
+                    .0
+                    .contains(&geo_types::Coord { x: -10.0, y: 0.0 }));
+                assert!(p
+                    .exterior()
+                    .0
+                    .contains(&geo_types::Coord { x: 10.0, y: 0.0 }));
+            }
+            other => panic!("Expected a Polygon, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::geoq::bbox;
+use geo_types::{Geometry, Rect};
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
+
+// Bounding box of an indexed item, along with its position in the index
+struct Entry {
+    envelope: AABB<[f64; 2]>,
+    position: usize,
+}
+
+impl RTreeObject for Entry {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+impl PointDistance for Entry {
+    fn distance_2(&self, point: &[f64; 2]) -> f64 {
+        self.envelope.distance_2(point)
+    }
+}
+
+fn envelope(rect: &Rect<f64>) -> AABB<[f64; 2]> {
+    AABB::from_corners([rect.min().x, rect.min().y], [rect.max().x, rect.max().y])
+}
+
+// An R-tree over the bounding boxes of a set of items, for finding
+// candidates to test before doing more expensive geometry comparisons
+pub struct SpatialIndex<T> {
+    tree: RTree<Entry>,
+    items: Vec<T>,
+}
+
+impl<T> SpatialIndex<T> {
+    pub fn new(items: Vec<T>, geom: fn(&T) -> &Geometry<f64>) -> SpatialIndex<T> {
+        let entries = items
+            .iter()
+            .enumerate()
+            .map(|(position, item)| Entry {
+                envelope: envelope(&bbox::bbox(geom(item))),
+                position,
+            })
+            .collect();
+        SpatialIndex {
+            tree: RTree::bulk_load(entries),
+            items,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // All items, in their original order
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    // Items whose bounding boxes intersect the given one, in their original order
+    pub fn candidates(&self, rect: &Rect<f64>) -> Vec<&T> {
+        self.candidates_in(&[*rect])
+    }
+
+    // Items whose bounding boxes intersect any of the given ones, in their original order
+    pub fn candidates_in(&self, rects: &[Rect<f64>]) -> Vec<&T> {
+        let mut positions: Vec<usize> = rects
+            .iter()
+            .flat_map(|rect| {
+                self.tree
+                    .locate_in_envelope_intersecting(&envelope(rect))
+                    .map(|e| e.position)
+            })
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions.into_iter().map(|p| &self.items[p]).collect()
+    }
+
+    // Items in order of the planar distance from their bounding boxes
+    // to a point, along with that distance
+    pub fn nearest(&self, x: f64, y: f64) -> impl Iterator<Item = (&T, f64)> {
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&[x, y])
+            .map(move |(e, dist_2)| (&self.items[e.position], dist_2.sqrt()))
+    }
+
+    // Items in order of the planar distance from their bounding boxes to the
+    // center of a geometry's bounding box, along with a lower bound on the
+    // planar distance between the two bounding boxes
+    pub fn nearest_to(&self, geom: &Geometry<f64>) -> impl Iterator<Item = (&T, f64)> {
+        let rect = bbox::bbox(geom);
+        let center = rect.center();
+        let half_diagonal = (rect.width().powi(2) + rect.height().powi(2)).sqrt() / 2.0;
+        self.nearest(center.x, center.y)
+            .map(move |(item, dist)| (item, (dist - half_diagonal).max(0.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpatialIndex;
+    use geo_types::{Coord, Geometry, Point, Rect};
+
+    fn index() -> SpatialIndex<(Geometry<f64>, &'static str)> {
+        SpatialIndex::new(
+            vec![
+                (Geometry::Point(Point::new(0.0, 0.0)), "a"),
+                (Geometry::Point(Point::new(5.0, 5.0)), "b"),
+                (Geometry::Point(Point::new(1.0, 1.0)), "c"),
+            ],
+            |item| &item.0,
+        )
+    }
+
+    #[test]
+    fn test_candidates() {
+        let rect = Rect::new(Coord { x: -1.0, y: -1.0 }, Coord { x: 2.0, y: 2.0 });
+        let names: Vec<&str> = index().candidates(&rect).iter().map(|i| i.1).collect();
+        assert_eq!(vec!["a", "c"], names);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let idx = index();
+        let nearest: Vec<(&str, f64)> = idx.nearest(4.0, 5.0).map(|(i, d)| (i.1, d)).collect();
+        assert_eq!(vec![("b", 1.0), ("c", 5.0), ("a", 41.0f64.sqrt())], nearest);
+    }
+}
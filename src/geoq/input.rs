@@ -1,7 +1,8 @@
 use crate::geoq::error::Error;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
 use std::fmt;
+use std::str::FromStr;
 
 static LATLON: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-?\d+\.?\d*[,\t]-?\d+\.?\d*$").unwrap());
 static GH: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^[0-9a-z--a--i--l--o]+$").unwrap());
@@ -14,6 +15,7 @@ static WKT: Lazy<Regex> = Lazy::new(|| {
 #[derive(Debug, Clone)]
 pub enum Input {
     LatLon(String),
+    LonLat(String),
     Geohash(String),
     WKT(String),
     GeoJSON(String),
@@ -24,6 +26,7 @@ impl fmt::Display for Input {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Input::LatLon(ref raw) => write!(f, "LatLon({})", raw),
+            Input::LonLat(ref raw) => write!(f, "LonLat({})", raw),
             Input::Geohash(ref raw) => write!(f, "Geohash({})", raw),
             Input::WKT(ref raw) => write!(f, "WKT({})", raw),
             Input::GeoJSON(ref raw) => write!(f, "GeoJSON({})", raw),
@@ -32,17 +35,130 @@ impl fmt::Display for Input {
     }
 }
 
+// Format used to parse inputs, as configured by the global --input-format
+// option. Auto detects the format of each input by matching it against
+// each of the others in turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Auto,
+    LatLon,
+    LonLat,
+    Geohash,
+    H3,
+    Wkt,
+    GeoJson,
+}
+
+impl FromStr for InputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(InputFormat::Auto),
+            "latlon" => Ok(InputFormat::LatLon),
+            "lonlat" => Ok(InputFormat::LonLat),
+            "geohash" => Ok(InputFormat::Geohash),
+            "h3" => Ok(InputFormat::H3),
+            "wkt" => Ok(InputFormat::Wkt),
+            "geojson" => Ok(InputFormat::GeoJson),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --input-format value: {}. Expected one of: {}.",
+                s,
+                INPUT_FORMATS.join(", ")
+            ))),
+        }
+    }
+}
+
+pub const INPUT_FORMATS: [&str; 7] = [
+    "auto", "latlon", "lonlat", "geohash", "h3", "wkt", "geojson",
+];
+
+static FORMAT: OnceCell<InputFormat> = OnceCell::new();
+
+pub fn set_format(format: InputFormat) -> Result<(), Error> {
+    FORMAT
+        .set(format)
+        .map_err(|_| Error::ProgramError("--input-format was already set".to_string()))
+}
+
+pub fn format() -> InputFormat {
+    *FORMAT.get().unwrap_or(&InputFormat::Auto)
+}
+
+// Read an input in the given format. Forced formats skip detection and
+// give an error if the input isn't valid for that format.
+pub fn read_line_as(line: String, format: InputFormat) -> Result<Input, Error> {
+    try_read_line_as(line, format).map_err(|(e, _)| e)
+}
+
+// Like read_line_as, but gives the line back along with the error, so
+// callers can report it without keeping their own copy of every input
+pub fn try_read_line_as(line: String, format: InputFormat) -> Result<Input, (Error, String)> {
+    match kind(&line, format) {
+        Ok(kind) => Ok(kind(line)),
+        Err(e) => Err((e, line)),
+    }
+}
+
+// Which kind of Input a line should be read as
+fn kind(line: &str, format: InputFormat) -> Result<fn(String) -> Input, Error> {
+    match format {
+        InputFormat::Auto => detect(line),
+        InputFormat::LatLon | InputFormat::LonLat if !is_coordinate_row(line) => {
+            Err(Error::InvalidInput(format!(
+                "Expected comma or tab-separated coordinate pair: {}",
+                line
+            )))
+        }
+        InputFormat::LatLon => Ok(Input::LatLon),
+        InputFormat::LonLat => Ok(Input::LonLat),
+        InputFormat::Geohash if !GH.is_match(line) => Err(Error::InvalidInput(format!(
+            "Expected base 32 geohash: {}",
+            line
+        ))),
+        InputFormat::Geohash => Ok(Input::Geohash),
+        InputFormat::H3 => Ok(Input::H3),
+        InputFormat::Wkt => Ok(Input::WKT),
+        InputFormat::GeoJson => Ok(Input::GeoJSON),
+    }
+}
+
+// Split a delimited coordinate row into its columns. The delimiter
+// (comma or tab) is whichever one appears first.
+pub fn columns(line: &str) -> Vec<&str> {
+    match line.find(|c| c == ',' || c == '\t') {
+        Some(i) => line.split(&line[i..i + 1]).collect(),
+        None => vec![line],
+    }
+}
+
+// Coordinate rows may have any number of extra columns following
+// the coordinate pair, e.g. lat,lon,timestamp,id
+fn is_coordinate_row(line: &str) -> bool {
+    let cols = columns(line);
+    cols.len() >= 2
+        && cols[0..2]
+            .iter()
+            .all(|c| c.trim().parse::<f64>().map(f64::is_finite).unwrap_or(false))
+}
+
+// Read an input, detecting its format
 pub fn read_line(line: String) -> Result<Input, Error> {
-    if LATLON.is_match(&line) {
-        Ok(Input::LatLon(line))
-    } else if H3.is_match(&line) {
-        Ok(Input::H3(line))
-    } else if GH.is_match(&line) {
-        Ok(Input::Geohash(line))
-    } else if JSON.is_match(&line) {
-        Ok(Input::GeoJSON(line))
-    } else if WKT.is_match(&line) {
-        Ok(Input::WKT(line))
+    read_line_as(line, InputFormat::Auto)
+}
+
+fn detect(line: &str) -> Result<fn(String) -> Input, Error> {
+    if LATLON.is_match(line) || is_coordinate_row(line) {
+        Ok(Input::LatLon)
+    } else if H3.is_match(line) {
+        Ok(Input::H3)
+    } else if GH.is_match(line) {
+        Ok(Input::Geohash)
+    } else if JSON.is_match(line) {
+        Ok(Input::GeoJSON)
+    } else if WKT.is_match(line) {
+        Ok(Input::WKT)
     } else {
         Err(Error::InvalidInput(format!(
             "Unable to parse single-line input: {}",
@@ -61,6 +177,14 @@ fn reading_input_formats() {
         Ok(Input::LatLon(_)) => assert!(true),
         _ => assert!(false),
     }
+    match read_line("34.1,-118.2,2021-01-01,abc".to_string()) {
+        Ok(Input::LatLon(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    match read_line("POINT (1 2)".to_string()) {
+        Ok(Input::WKT(_)) => assert!(true),
+        _ => assert!(false),
+    }
 }
 
 #[test]
@@ -74,3 +198,51 @@ fn h3_regex_format() {
     // optional leading 0
     assert!(H3.is_match("08027ffffffffffff"));
 }
+
+#[test]
+fn reading_forced_input_formats() {
+    // All-hex geohashes are detected as H3 by auto
+    match read_line("9bcdef012345678".to_string()) {
+        Ok(Input::H3(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    match read_line_as("9bcdef012345678".to_string(), InputFormat::Geohash) {
+        Ok(Input::Geohash(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    match read_line_as("-118,34".to_string(), InputFormat::LonLat) {
+        Ok(Input::LonLat(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    assert!(read_line_as("9q5".to_string(), InputFormat::LatLon).is_err());
+    assert!(read_line_as("12".to_string(), InputFormat::LatLon).is_err());
+    assert!(read_line_as("12,pizza,34".to_string(), InputFormat::LatLon).is_err());
+    match read_line_as("12,34,2020-01-01,abc".to_string(), InputFormat::LatLon) {
+        Ok(Input::LatLon(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    // Detection also accepts extra columns, but only after a coordinate pair
+    assert!(read_line("12,34,abc".to_string()).is_ok());
+    assert!(read_line("abc,12,34".to_string()).is_err());
+    assert!(read_line_as("12,34".to_string(), InputFormat::Geohash).is_err());
+    match read_line_as("POINT (1 2)".to_string(), InputFormat::GeoJson) {
+        Ok(Input::GeoJSON(_)) => assert!(true),
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn parsing_input_formats() {
+    assert_eq!(InputFormat::Auto, "auto".parse().unwrap());
+    assert_eq!(InputFormat::LonLat, "lonlat".parse().unwrap());
+    assert_eq!(InputFormat::GeoJson, "geojson".parse().unwrap());
+    assert!("pizza".parse::<InputFormat>().is_err());
+}
+
+#[test]
+fn splitting_columns() {
+    assert_eq!(vec!["12", "34"], columns("12,34"));
+    assert_eq!(vec!["12", "34", "a,b"], columns("12\t34\ta,b"));
+    assert_eq!(vec!["12", "34", "a\tb"], columns("12,34,a\tb"));
+    assert_eq!(vec!["12"], columns("12"));
+}
@@ -1,20 +1,35 @@
 #![allow(unreachable_code)]
 #![allow(dead_code)]
 #![allow(unused_imports)]
+pub mod antimeridian;
 pub mod bbox;
 pub mod browser_open;
+pub mod buffer;
 pub mod centroid;
+pub mod clip;
 pub mod commands;
 pub mod contains;
 pub mod coord_count;
+pub mod csv;
 pub mod distance;
 pub mod entity;
 pub mod error;
 pub mod fgb;
+pub mod geodesic;
 pub mod geohash;
 mod geojson;
+pub mod hull;
+pub mod index;
 pub mod input;
+pub mod orient;
+pub mod overlay;
 pub mod par;
+pub mod query;
 pub mod reader;
+pub mod records;
+pub mod rejects;
+pub mod relate;
 pub mod simplify;
 pub mod text;
+pub mod validate;
+pub mod zm;
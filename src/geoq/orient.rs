@@ -0,0 +1,130 @@
+use crate::geoq::error::Error;
+use geojson::{PolygonType, Value};
+use std::str::FromStr;
+
+// Direction for the exterior rings of polygons. Holes are wound the opposite way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Winding {
+    // Counter-clockwise exteriors and clockwise holes, as required by GeoJSON (RFC 7946)
+    CounterClockwise,
+    // Clockwise exteriors and counter-clockwise holes, as used by e.g. shapefiles
+    Clockwise,
+}
+
+pub const WINDINGS: [&str; 2] = ["ccw", "cw"];
+
+impl FromStr for Winding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ccw" => Ok(Winding::CounterClockwise),
+            "cw" => Ok(Winding::Clockwise),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --winding value: {}. Expected one of: {}.",
+                s,
+                WINDINGS.join(", ")
+            ))),
+        }
+    }
+}
+
+// Twice the signed area of a ring: positive when it's counter-clockwise
+fn signed_area(ring: &[Vec<f64>]) -> f64 {
+    ring.windows(2)
+        .map(|pair| pair[0][0] * pair[1][1] - pair[1][0] * pair[0][1])
+        .sum()
+}
+
+fn orient_ring(ring: &mut Vec<Vec<f64>>, ccw: bool) {
+    let area = signed_area(ring);
+    if (ccw && area < 0.0) || (!ccw && area > 0.0) {
+        ring.reverse();
+    }
+}
+
+fn orient_polygon(rings: &mut PolygonType, winding: Winding) {
+    let ccw = winding == Winding::CounterClockwise;
+    for (i, ring) in rings.iter_mut().enumerate() {
+        orient_ring(ring, if i == 0 { ccw } else { !ccw });
+    }
+}
+
+// Rewind the rings of any (Multi)Polygons in a GeoJSON geometry.
+// Positions are only reordered, so any Z or M values are kept.
+pub fn orient(value: &mut Value, winding: Winding) {
+    match value {
+        Value::Polygon(rings) => orient_polygon(rings, winding),
+        Value::MultiPolygon(polys) => polys.iter_mut().for_each(|p| orient_polygon(p, winding)),
+        Value::GeometryCollection(geoms) => {
+            geoms.iter_mut().for_each(|g| orient(&mut g.value, winding))
+        }
+        _ => (),
+    }
+}
+
+pub fn orient_feature(feature: &mut geojson::Feature, winding: Winding) {
+    if let Some(geom) = feature.geometry.as_mut() {
+        orient(&mut geom.value, winding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{orient, Winding};
+    use geojson::Value;
+
+    fn ring(coords: &[[f64; 2]]) -> Vec<Vec<f64>> {
+        coords.iter().map(|c| c.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_orienting_polygons() {
+        let cw_shell = ring(&[[0., 0.], [0., 4.], [4., 4.], [4., 0.], [0., 0.]]);
+        let ccw_hole = ring(&[[1., 1.], [2., 1.], [2., 2.], [1., 2.], [1., 1.]]);
+        let mut ccw_shell = cw_shell.clone();
+        ccw_shell.reverse();
+        let mut cw_hole = ccw_hole.clone();
+        cw_hole.reverse();
+
+        let mut poly = Value::Polygon(vec![cw_shell.clone(), ccw_hole.clone()]);
+        orient(&mut poly, Winding::CounterClockwise);
+        assert_eq!(
+            Value::Polygon(vec![ccw_shell.clone(), cw_hole.clone()]),
+            poly
+        );
+
+        orient(&mut poly, Winding::Clockwise);
+        assert_eq!(
+            Value::Polygon(vec![cw_shell.clone(), ccw_hole.clone()]),
+            poly
+        );
+
+        let mut multi = Value::MultiPolygon(vec![vec![cw_shell.clone()], vec![ccw_shell.clone()]]);
+        orient(&mut multi, Winding::CounterClockwise);
+        assert_eq!(
+            Value::MultiPolygon(vec![vec![ccw_shell.clone()], vec![ccw_shell.clone()]]),
+            multi
+        );
+    }
+
+    #[test]
+    fn test_keeps_extra_ordinates() {
+        let mut poly = Value::Polygon(vec![vec![
+            vec![0., 0., 10.],
+            vec![0., 1., 11.],
+            vec![1., 0., 12.],
+            vec![0., 0., 10.],
+        ]]);
+        orient(&mut poly, Winding::CounterClockwise);
+        assert_eq!(
+            Value::Polygon(vec![vec![
+                vec![0., 0., 10.],
+                vec![1., 0., 12.],
+                vec![0., 1., 11.],
+                vec![0., 0., 10.],
+            ]]),
+            poly
+        );
+    }
+}
@@ -0,0 +1,686 @@
+use crate::geoq::{bbox, index::SpatialIndex};
+use geo::algorithm::euclidean_distance::EuclideanDistance;
+use geo::algorithm::line_intersection::{line_intersection, LineIntersection};
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect,
+};
+use std::collections::HashMap;
+
+// Points closer than this are merged into the same node, to allow for
+// rounding in computed intersections
+const TOLERANCE: f64 = 1e-9;
+
+// Boolean operations between two sets of polygons
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Intersection,
+    Union,
+    // Parts of the first which aren't in the second
+    Difference,
+    // Parts in either one, but not both
+    Xor,
+}
+
+// Where an edge of one geometry lies relative to the other
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Side {
+    Inside,
+    Outside,
+    // On an edge of the other with its interior on the same side
+    Same,
+    // On an edge of the other with its interior on the opposite side
+    Opposite,
+}
+
+// Polygonal parts of a geometry. Points and lines have no area, so are left out.
+pub fn polygons(geom: &Geometry<f64>) -> MultiPolygon<f64> {
+    match geom {
+        Geometry::Polygon(p) => MultiPolygon(vec![p.clone()]),
+        Geometry::MultiPolygon(mp) => mp.clone(),
+        Geometry::Rect(r) => MultiPolygon(vec![r.to_polygon()]),
+        Geometry::Triangle(t) => MultiPolygon(vec![t.to_polygon()]),
+        Geometry::GeometryCollection(gc) => {
+            MultiPolygon(gc.0.iter().flat_map(|g| polygons(g).0).collect())
+        }
+        _ => MultiPolygon(vec![]),
+    }
+}
+
+fn signed_area(coords: &[Coord<f64>]) -> f64 {
+    coords
+        .windows(2)
+        .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+        .sum::<f64>()
+        / 2.0
+}
+
+fn ring_segments(ring: &LineString<f64>, counter_clockwise: bool, out: &mut Vec<Line<f64>>) {
+    let mut coords = ring.0.clone();
+    if (signed_area(&coords) > 0.0) != counter_clockwise {
+        coords.reverse();
+    }
+    out.extend(
+        coords
+            .windows(2)
+            .filter(|w| w[0] != w[1])
+            .map(|w| Line::new(w[0], w[1])),
+    );
+}
+
+// Edges of polygon rings, with exteriors counter-clockwise and holes
+// clockwise, so the interior of the polygon is always on the left
+fn oriented_segments(mp: &MultiPolygon<f64>) -> Vec<Line<f64>> {
+    let mut segments = Vec::new();
+    for poly in mp.0.iter() {
+        ring_segments(poly.exterior(), true, &mut segments);
+        for hole in poly.interiors() {
+            ring_segments(hole, false, &mut segments);
+        }
+    }
+    segments
+}
+
+fn same(a: Coord<f64>, b: Coord<f64>) -> bool {
+    (a.x - b.x).abs() < TOLERANCE && (a.y - b.y).abs() < TOLERANCE
+}
+
+fn on_segment(c: Coord<f64>, l: &Line<f64>) -> bool {
+    Point(c).euclidean_distance(l) < TOLERANCE
+}
+
+fn padded_rect(l: &Line<f64>) -> Rect<f64> {
+    let rect = bbox::bbox(&Geometry::Line(*l));
+    let pad = Coord {
+        x: TOLERANCE,
+        y: TOLERANCE,
+    };
+    Rect::new(rect.min() - pad, rect.max() + pad)
+}
+
+fn segment_index(segments: &[Line<f64>]) -> SpatialIndex<(usize, Geometry<f64>)> {
+    let items = segments
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (i, Geometry::Line(*l)))
+        .collect();
+    SpatialIndex::new(items, |item| &item.1)
+}
+
+// Break a segment into pieces at the given points along it
+fn pieces(l: &Line<f64>, mut cuts: Vec<Coord<f64>>, out: &mut Vec<Line<f64>>) {
+    let position = |c: &Coord<f64>| (c.x - l.start.x) * l.dx() + (c.y - l.start.y) * l.dy();
+    cuts.sort_by(|a, b| position(a).partial_cmp(&position(b)).unwrap());
+    cuts.dedup_by(|a, b| same(*a, *b));
+    out.extend(cuts.windows(2).map(|w| Line::new(w[0], w[1])));
+}
+
+// Cut the segments of a and b wherever they meet, so that no two pieces
+// cross, and pieces either coincide or only meet at their ends
+fn split(a: &[Line<f64>], b: &[Line<f64>]) -> (Vec<Line<f64>>, Vec<Line<f64>>) {
+    let index = segment_index(b);
+    let mut a_cuts: Vec<Vec<Coord<f64>>> = a.iter().map(|l| vec![l.start, l.end]).collect();
+    let mut b_cuts: Vec<Vec<Coord<f64>>> = b.iter().map(|l| vec![l.start, l.end]).collect();
+
+    for (i, la) in a.iter().enumerate() {
+        for (j, _) in index.candidates(&padded_rect(la)) {
+            let lb = &b[*j];
+            let mut shared = Vec::new();
+            match line_intersection(*la, *lb) {
+                Some(LineIntersection::SinglePoint { intersection, .. }) => {
+                    shared.push(intersection)
+                }
+                Some(LineIntersection::Collinear { intersection }) => {
+                    shared.push(intersection.start);
+                    shared.push(intersection.end);
+                }
+                None => (),
+            }
+            // Ends which just miss the other segment through rounding
+            shared.extend([lb.start, lb.end].iter().filter(|c| on_segment(**c, la)));
+            shared.extend([la.start, la.end].iter().filter(|c| on_segment(**c, lb)));
+            a_cuts[i].extend(shared.iter());
+            b_cuts[*j].extend(shared.iter());
+        }
+    }
+
+    let (mut a_pieces, mut b_pieces) = (Vec::new(), Vec::new());
+    for (l, cuts) in a.iter().zip(a_cuts) {
+        pieces(l, cuts, &mut a_pieces);
+    }
+    for (l, cuts) in b.iter().zip(b_cuts) {
+        pieces(l, cuts, &mut b_pieces);
+    }
+    (a_pieces, b_pieces)
+}
+
+// Canonical ids for the points where pieces start and end, merging
+// points within the tolerance of each other
+struct Nodes {
+    coords: Vec<Coord<f64>>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Nodes {
+    fn new() -> Nodes {
+        Nodes {
+            coords: Vec::new(),
+            grid: HashMap::new(),
+        }
+    }
+
+    fn id(&mut self, c: Coord<f64>) -> usize {
+        let cell = (
+            (c.x / TOLERANCE).floor() as i64,
+            (c.y / TOLERANCE).floor() as i64,
+        );
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(ids) = self.grid.get(&(cell.0 + dx, cell.1 + dy)) {
+                    if let Some(id) = ids.iter().find(|id| same(self.coords[**id], c)) {
+                        return *id;
+                    }
+                }
+            }
+        }
+        self.coords.push(c);
+        let id = self.coords.len() - 1;
+        self.grid.entry(cell).or_insert_with(Vec::new).push(id);
+        id
+    }
+}
+
+// Whether a point is inside a set of polygon edges, by counting
+// crossings of a ray heading east from it
+fn inside(
+    c: Coord<f64>,
+    segments: &[Line<f64>],
+    index: &SpatialIndex<(usize, Geometry<f64>)>,
+) -> bool {
+    let ray = Rect::new(
+        c,
+        Coord {
+            x: f64::MAX,
+            y: c.y,
+        },
+    );
+    index
+        .candidates(&ray)
+        .iter()
+        .filter(|(i, _)| {
+            let l = &segments[*i];
+            if (l.start.y > c.y) == (l.end.y > c.y) {
+                return false;
+            }
+            let x = l.start.x + (c.y - l.start.y) * l.dx() / l.dy();
+            c.x < x
+        })
+        .count()
+        % 2
+        == 1
+}
+
+fn sides(
+    pieces: &[(usize, usize)],
+    nodes: &Nodes,
+    other: &[(usize, usize)],
+    other_segments: &[Line<f64>],
+) -> Vec<Side> {
+    let index = segment_index(other_segments);
+    let other: std::collections::HashSet<&(usize, usize)> = other.iter().collect();
+    pieces
+        .iter()
+        .map(|&(s, e)| {
+            if other.contains(&(s, e)) {
+                Side::Same
+            } else if other.contains(&(e, s)) {
+                Side::Opposite
+            } else {
+                let (a, b) = (nodes.coords[s], nodes.coords[e]);
+                let mid = Coord {
+                    x: (a.x + b.x) / 2.0,
+                    y: (a.y + b.y) / 2.0,
+                };
+                if inside(mid, other_segments, &index) {
+                    Side::Inside
+                } else {
+                    Side::Outside
+                }
+            }
+        })
+        .collect()
+}
+
+// Whether to keep an edge of the first geometry in the result,
+// and if so whether to reverse it
+fn select_first(side: Side, op: Operation) -> Option<bool> {
+    match (op, side) {
+        (Operation::Union, Side::Outside) | (Operation::Union, Side::Same) => Some(false),
+        (Operation::Intersection, Side::Inside) | (Operation::Intersection, Side::Same) => {
+            Some(false)
+        }
+        (Operation::Difference, Side::Outside) | (Operation::Difference, Side::Opposite) => {
+            Some(false)
+        }
+        (Operation::Xor, Side::Outside) | (Operation::Xor, Side::Opposite) => Some(false),
+        (Operation::Xor, Side::Inside) => Some(true),
+        _ => None,
+    }
+}
+
+// The same for the second geometry. Edges shared by both are
+// already taken from the first.
+fn select_second(side: Side, op: Operation) -> Option<bool> {
+    match (op, side) {
+        (Operation::Union, Side::Outside) => Some(false),
+        (Operation::Intersection, Side::Inside) => Some(false),
+        (Operation::Difference, Side::Inside) => Some(true),
+        (Operation::Xor, Side::Outside) | (Operation::Xor, Side::Opposite) => Some(false),
+        (Operation::Xor, Side::Inside) => Some(true),
+        _ => None,
+    }
+}
+
+// Angle turned clockwise from one direction to another, in (0, 2π]
+fn clockwise_angle(from: Coord<f64>, to: Coord<f64>) -> f64 {
+    let mut angle = from.y.atan2(from.x) - to.y.atan2(to.x);
+    while angle <= 0.0 {
+        angle += 2.0 * std::f64::consts::PI;
+    }
+    angle
+}
+
+// Follow the selected edges around into closed rings. Where several edges
+// leave a node, take the one turning furthest left, so each ring traces a
+// single face, and rings which touch at a point are kept apart.
+fn trace(edges: &[(usize, usize)], nodes: &Nodes) -> Vec<Vec<Coord<f64>>> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, (s, _)) in edges.iter().enumerate() {
+        outgoing.entry(*s).or_insert_with(Vec::new).push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let first = edges[start].0;
+        let mut ring = vec![nodes.coords[first]];
+        let mut current = start;
+        let closed = loop {
+            let (s, e) = edges[current];
+            if e == first {
+                break true;
+            }
+            ring.push(nodes.coords[e]);
+            let back = nodes.coords[s] - nodes.coords[e];
+            let next = outgoing.get(&e).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|i| !used[**i])
+                    .map(|i| {
+                        let out = nodes.coords[edges[*i].1] - nodes.coords[e];
+                        (*i, clockwise_angle(back, out))
+                    })
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                    .map(|(i, _)| i)
+            });
+            match next {
+                Some(i) => {
+                    used[i] = true;
+                    current = i;
+                }
+                None => break false,
+            }
+        };
+        if closed && ring.len() >= 3 {
+            ring.push(ring[0]);
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+fn ring_contains(ring: &[Coord<f64>], c: Coord<f64>) -> bool {
+    ring.windows(2)
+        .filter(|w| {
+            let (a, b) = (w[0], w[1]);
+            (a.y > c.y) != (b.y > c.y) && c.x < a.x + (c.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+// Drop nodes in the middle of straight runs, left over from cutting edges
+fn without_collinear(ring: Vec<Coord<f64>>) -> Vec<Coord<f64>> {
+    let open = &ring[..ring.len() - 1];
+    let n = open.len();
+    let mut kept: Vec<Coord<f64>> = (0..n)
+        .filter(|&i| {
+            let (prev, c, next) = (open[(i + n - 1) % n], open[i], open[(i + 1) % n]);
+            let (d1, d2) = (c - prev, next - c);
+            let cross = d1.x * d2.y - d1.y * d2.x;
+            let scale = (d1.x.abs() + d1.y.abs()) * (d2.x.abs() + d2.y.abs());
+            cross.abs() > TOLERANCE * scale || d1.x * d2.x + d1.y * d2.y < 0.0
+        })
+        .map(|i| open[i])
+        .collect();
+    if let Some(first) = kept.first().cloned() {
+        kept.push(first);
+    }
+    kept
+}
+
+// Counter-clockwise rings are shells, and clockwise ones are holes,
+// which go in the smallest shell around them
+fn assemble(rings: Vec<Vec<Coord<f64>>>) -> MultiPolygon<f64> {
+    let mut shells: Vec<(Vec<Coord<f64>>, f64, Vec<LineString<f64>>)> = Vec::new();
+    let mut holes: Vec<Vec<Coord<f64>>> = Vec::new();
+    for ring in rings.into_iter().map(without_collinear) {
+        if ring.len() < 4 {
+            continue;
+        }
+        let area = signed_area(&ring);
+        if area > TOLERANCE {
+            shells.push((ring, area, Vec::new()));
+        } else if area < -TOLERANCE {
+            holes.push(ring);
+        }
+    }
+    for hole in holes {
+        let sample = Coord {
+            x: (hole[0].x + hole[1].x) / 2.0,
+            y: (hole[0].y + hole[1].y) / 2.0,
+        };
+        let shell = shells
+            .iter_mut()
+            .filter(|(ring, _, _)| ring_contains(ring, sample))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        if let Some((_, _, shell_holes)) = shell {
+            shell_holes.push(LineString(hole));
+        }
+    }
+    MultiPolygon(
+        shells
+            .into_iter()
+            .map(|(ring, _, holes)| Polygon::new(LineString(ring), holes))
+            .collect(),
+    )
+}
+
+// Computes a boolean operation by cutting the edges of both inputs where they
+// meet, choosing the pieces which bound the result based on which side of the
+// other input they fall on, and tracing those pieces back into rings
+pub fn overlay(a: &MultiPolygon<f64>, b: &MultiPolygon<f64>, op: Operation) -> MultiPolygon<f64> {
+    let disjoint = a.0.is_empty()
+        || b.0.is_empty()
+        || !bbox::intersects(
+            &bbox::bbox(&Geometry::MultiPolygon(a.clone())),
+            &bbox::bbox(&Geometry::MultiPolygon(b.clone())),
+        );
+    if disjoint {
+        return match op {
+            Operation::Intersection => MultiPolygon(vec![]),
+            Operation::Difference => a.clone(),
+            Operation::Union | Operation::Xor => {
+                MultiPolygon(a.0.iter().chain(b.0.iter()).cloned().collect())
+            }
+        };
+    }
+
+    let (a_segments, b_segments) = (oriented_segments(a), oriented_segments(b));
+    let (a_pieces, b_pieces) = split(&a_segments, &b_segments);
+    let mut nodes = Nodes::new();
+    let mut ids = |pieces: &[Line<f64>]| -> Vec<(usize, usize)> {
+        pieces
+            .iter()
+            .map(|l| (nodes.id(l.start), nodes.id(l.end)))
+            .filter(|(s, e)| s != e)
+            .collect()
+    };
+    let (a_edges, b_edges) = (ids(&a_pieces), ids(&b_pieces));
+
+    let mut selected = Vec::new();
+    let a_sides = sides(&a_edges, &nodes, &b_edges, &b_segments);
+    for (&(s, e), side) in a_edges.iter().zip(a_sides) {
+        match select_first(side, op) {
+            Some(false) => selected.push((s, e)),
+            Some(true) => selected.push((e, s)),
+            None => (),
+        }
+    }
+    let b_sides = sides(&b_edges, &nodes, &a_edges, &a_segments);
+    for (&(s, e), side) in b_edges.iter().zip(b_sides) {
+        match select_second(side, op) {
+            Some(false) => selected.push((s, e)),
+            Some(true) => selected.push((e, s)),
+            None => (),
+        }
+    }
+    selected.sort_unstable();
+    selected.dedup();
+
+    assemble(trace(&selected, &nodes))
+}
+
+// Unions a set of polygons, merging them in pairs so that each
+// overlay only handles a share of the edges
+pub fn union_all(mut polys: Vec<MultiPolygon<f64>>) -> MultiPolygon<f64> {
+    while polys.len() > 1 {
+        polys = polys
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => overlay(a, b, Operation::Union),
+                _ => pair[0].clone(),
+            })
+            .collect();
+    }
+    polys.pop().unwrap_or_else(|| MultiPolygon(vec![]))
+}
+
+fn lines(geom: &Geometry<f64>, out: &mut Vec<LineString<f64>>) {
+    match geom {
+        Geometry::Line(l) => out.push(LineString(vec![l.start, l.end])),
+        Geometry::LineString(ls) => out.push(ls.clone()),
+        Geometry::MultiLineString(mls) => out.extend(mls.0.iter().cloned()),
+        Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| lines(g, out)),
+        _ => (),
+    }
+}
+
+fn points(geom: &Geometry<f64>, out: &mut Vec<Point<f64>>) {
+    match geom {
+        Geometry::Point(p) => out.push(*p),
+        Geometry::MultiPoint(mp) => out.extend(mp.0.iter()),
+        Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| points(g, out)),
+        _ => (),
+    }
+}
+
+// Whether a point is inside a set of polygon edges, or on one of them
+fn covered(
+    c: Coord<f64>,
+    segments: &[Line<f64>],
+    index: &SpatialIndex<(usize, Geometry<f64>)>,
+) -> bool {
+    let near = padded_rect(&Line::new(c, c));
+    index
+        .candidates(&near)
+        .iter()
+        .any(|(i, _)| on_segment(c, &segments[*i]))
+        || inside(c, segments, index)
+}
+
+// Parts of a line either covered by a set of polygon edges, or outside them
+fn clip_line(
+    ls: &LineString<f64>,
+    segments: &[Line<f64>],
+    index: &SpatialIndex<(usize, Geometry<f64>)>,
+    keep_covered: bool,
+) -> Vec<LineString<f64>> {
+    let line_segments: Vec<Line<f64>> = ls.lines().filter(|l| l.start != l.end).collect();
+    let (pieces, _) = split(&line_segments, segments);
+    let mut clipped: Vec<Vec<Coord<f64>>> = Vec::new();
+    for piece in pieces {
+        let mid = Coord {
+            x: (piece.start.x + piece.end.x) / 2.0,
+            y: (piece.start.y + piece.end.y) / 2.0,
+        };
+        if covered(mid, segments, index) != keep_covered {
+            continue;
+        }
+        match clipped.last_mut() {
+            Some(line) if same(line[line.len() - 1], piece.start) => line.push(piece.end),
+            _ => clipped.push(vec![piece.start, piece.end]),
+        }
+    }
+    clipped.into_iter().map(LineString).collect()
+}
+
+// Applies a boolean operation between any geometry and a set of polygons.
+// Lines and points are kept where they're covered by the polygons for an
+// intersection, and where they're outside them otherwise, since the polygons
+// already cover the rest of a union. Gives None when nothing is left.
+pub fn overlay_geometry(
+    geom: &Geometry<f64>,
+    other: &MultiPolygon<f64>,
+    op: Operation,
+) -> Option<Geometry<f64>> {
+    let mut parts: Vec<Geometry<f64>> = Vec::new();
+    let mut areas = overlay(&polygons(geom), other, op);
+    match areas.0.len() {
+        0 => (),
+        1 => parts.push(Geometry::Polygon(areas.0.remove(0))),
+        _ => parts.push(Geometry::MultiPolygon(areas)),
+    }
+
+    let segments = oriented_segments(other);
+    let index = segment_index(&segments);
+    let keep_covered = op == Operation::Intersection;
+
+    let (mut input_lines, mut input_points) = (Vec::new(), Vec::new());
+    lines(geom, &mut input_lines);
+    points(geom, &mut input_points);
+    let mut clipped: Vec<LineString<f64>> = input_lines
+        .iter()
+        .flat_map(|ls| clip_line(ls, &segments, &index, keep_covered))
+        .collect();
+    match clipped.len() {
+        0 => (),
+        1 => parts.push(Geometry::LineString(clipped.remove(0))),
+        _ => parts.push(Geometry::MultiLineString(MultiLineString(clipped))),
+    }
+    let mut kept: Vec<Point<f64>> = input_points
+        .into_iter()
+        .filter(|p| covered(p.0, &segments, &index) == keep_covered)
+        .collect();
+    match kept.len() {
+        0 => (),
+        1 => parts.push(Geometry::Point(kept.remove(0))),
+        _ => parts.push(Geometry::MultiPoint(MultiPoint(kept))),
+    }
+
+    match parts.len() {
+        0 => None,
+        1 => parts.pop(),
+        _ => Some(Geometry::GeometryCollection(GeometryCollection(parts))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overlay, overlay_geometry, union_all, Operation};
+    use geo::algorithm::area::Area;
+    use geo_types::{line_string, point, polygon, Geometry, MultiPolygon};
+
+    fn square(x: f64, y: f64, size: f64) -> MultiPolygon<f64> {
+        MultiPolygon(vec![polygon![
+            (x: x, y: y),
+            (x: x + size, y: y),
+            (x: x + size, y: y + size),
+            (x: x, y: y + size),
+            (x: x, y: y),
+        ]])
+    }
+
+    fn area(mp: &MultiPolygon<f64>) -> f64 {
+        mp.unsigned_area()
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let (a, b) = (square(0.0, 0.0, 2.0), square(1.0, 1.0, 2.0));
+        let intersection = overlay(&a, &b, Operation::Intersection);
+        assert_eq!(1, intersection.0.len());
+        assert_eq!(1.0, area(&intersection));
+        let union = overlay(&a, &b, Operation::Union);
+        assert_eq!(1, union.0.len());
+        assert_eq!(7.0, area(&union));
+        assert_eq!(3.0, area(&overlay(&a, &b, Operation::Difference)));
+        assert_eq!(6.0, area(&overlay(&a, &b, Operation::Xor)));
+    }
+
+    #[test]
+    fn test_holes_and_shared_edges() {
+        let (outer, inner) = (square(0.0, 0.0, 4.0), square(1.0, 1.0, 2.0));
+        let donut = overlay(&outer, &inner, Operation::Difference);
+        assert_eq!(1, donut.0.len());
+        assert_eq!(1, donut.0[0].interiors().len());
+        assert_eq!(12.0, area(&donut));
+        assert_eq!(4.0, area(&overlay(&donut, &outer, Operation::Xor)));
+
+        let adjacent = overlay(
+            &square(0.0, 0.0, 1.0),
+            &square(1.0, 0.0, 1.0),
+            Operation::Union,
+        );
+        assert_eq!(1, adjacent.0.len());
+        assert_eq!(5, adjacent.0[0].exterior().0.len());
+        assert_eq!(2.0, area(&adjacent));
+        assert!(overlay(&outer, &outer, Operation::Difference).0.is_empty());
+    }
+
+    #[test]
+    fn test_union_all() {
+        let squares = (0..10).map(|i| square(i as f64 * 0.5, 0.0, 1.0)).collect();
+        let union = union_all(squares);
+        assert_eq!(1, union.0.len());
+        assert_eq!(5.5, area(&union));
+
+        let corners = union_all(vec![square(0.0, 0.0, 1.0), square(1.0, 1.0, 1.0)]);
+        assert_eq!(2, corners.0.len());
+    }
+
+    #[test]
+    fn test_clipping_lines_and_points() {
+        let line = Geometry::LineString(line_string![(x: -1.0, y: 0.5), (x: 3.0, y: 0.5)]);
+        let clipped = overlay_geometry(&line, &square(0.0, 0.0, 2.0), Operation::Intersection);
+        assert_eq!(
+            Some(Geometry::LineString(
+                line_string![(x: 0.0, y: 0.5), (x: 2.0, y: 0.5)]
+            )),
+            clipped
+        );
+        match overlay_geometry(&line, &square(0.0, 0.0, 2.0), Operation::Difference) {
+            Some(Geometry::MultiLineString(mls)) => assert_eq!(2, mls.0.len()),
+            other => panic!("Expected a MultiLineString, got {:?}", other),
+        }
+        match overlay_geometry(&line, &square(0.0, 0.0, 2.0), Operation::Union) {
+            Some(Geometry::GeometryCollection(gc)) => assert_eq!(2, gc.0.len()),
+            other => panic!("Expected a GeometryCollection, got {:?}", other),
+        }
+
+        let inside = Geometry::Point(point!(x: 1.0, y: 1.0));
+        assert_eq!(
+            Some(inside.clone()),
+            overlay_geometry(&inside, &square(0.0, 0.0, 2.0), Operation::Intersection)
+        );
+        assert_eq!(
+            None,
+            overlay_geometry(&inside, &square(0.0, 0.0, 2.0), Operation::Difference)
+        );
+    }
+}
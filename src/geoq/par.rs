@@ -1,7 +1,9 @@
 use crate::geoq::{
     entity::{self, Entity},
     error::Error,
-    input, reader,
+    input,
+    records::Records,
+    rejects,
 };
 use num_cpus;
 use std::io;
@@ -14,32 +16,21 @@ use std::{
     thread::{self, JoinHandle},
 };
 
-enum WorkerInput {
-    Item(String),
+enum WorkerInput<T> {
+    Item(usize, T),
     Done,
 }
 
-enum WorkerOutput {
-    Item(Result<Vec<String>, Error>),
-    Done,
-}
-
-pub struct LineReader<'a> {
-    reader: &'a mut dyn BufRead,
+// An input which failed, along with what's needed to report it
+struct Rejected {
+    line: usize,
+    raw: String,
+    error: Error,
 }
 
-impl<'a> LineReader<'a> {
-    pub fn new(reader: &'a mut dyn BufRead) -> LineReader<'a> {
-        LineReader { reader }
-    }
-}
-
-impl<'a> Iterator for LineReader<'a> {
-    type Item = String;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        reader::read_line(self.reader)
-    }
+enum WorkerOutput {
+    Item(Result<Vec<String>, Rejected>),
+    Done,
 }
 
 // fn handle_line<F>(line: String, handler: F) -> Result<(), Error>
@@ -53,9 +44,9 @@ impl<'a> Iterator for LineReader<'a> {
 //     Ok(())
 // }
 
-pub fn for_stdin_entity<F: 'static>(handler: F) -> Result<(), Error>
+pub fn for_stdin_entity<F>(handler: F) -> Result<(), Error>
 where
-    F: Send + Sync + Fn(Entity) -> Result<Vec<String>, Error>,
+    F: Send + Sync + 'static + Fn(&Entity) -> Result<Vec<String>, Error>,
 {
     let stdin = io::stdin();
     let mut stdin_reader = stdin.lock();
@@ -63,56 +54,65 @@ where
 }
 
 const WORKER_BUF_SIZE: usize = 5000;
-pub fn for_entity_par<'a, F: 'static>(input: &'a mut dyn BufRead, handler: F) -> Result<(), Error>
+pub fn for_entity_par<'a, F>(input: &'a mut dyn BufRead, handler: F) -> Result<(), Error>
 where
-    F: Send + Sync + Fn(Entity) -> Result<Vec<String>, Error>,
+    F: Send + Sync + 'static + Fn(&Entity) -> Result<Vec<String>, Error>,
+{
+    let format = input::format();
+    for_record_par(Records::new(input).tagged(), move |record| {
+        let mut entities = entity::read(record.text, format)?;
+        // Collection members are given in the same form as when
+        // the whole collection was parsed at once
+        if record.member {
+            entities.iter_mut().for_each(entity::reserialize);
+        }
+        let mut results = Vec::new();
+        for e in entities {
+            match handler(&e) {
+                Ok(lines) => results.extend(lines),
+                Err(error) => return Err((error, e.raw())),
+            }
+        }
+        Ok(results)
+    })
+}
+
+// Process raw text records, given with their starting line numbers,
+// across a pool of workers. Outputs are printed in the same order
+// as the records they came from. Handlers which fail give back the
+// raw text to report, so it's only kept for inputs which fail.
+pub fn for_record_par<I, T, F>(records: I, handler: F) -> Result<(), Error>
+where
+    I: Iterator<Item = (usize, T)>,
+    T: Send + 'static,
+    F: Send + Sync + 'static + Fn(T) -> Result<Vec<String>, (Error, String)>,
 {
     let num_workers = num_cpus::get();
-    let mut input_channels: Vec<SyncSender<WorkerInput>> = vec![];
+    let mut input_channels: Vec<SyncSender<WorkerInput<T>>> = vec![];
     let mut threads: Vec<JoinHandle<_>> = vec![];
     let mut output_channels: Vec<Receiver<WorkerOutput>> = vec![];
     let handler_arc = Arc::new(handler);
 
     (0..num_workers).for_each(|_| {
-        let (input_sender, input_receiver) = sync_channel(WORKER_BUF_SIZE);
+        let (input_sender, input_receiver) = sync_channel::<WorkerInput<T>>(WORKER_BUF_SIZE);
         let (output_sender, output_receiver) = sync_channel(WORKER_BUF_SIZE);
 
         let handler = handler_arc.clone();
 
-        let t = thread::spawn(move || {
-            loop {
-                match input_receiver.recv() {
-                    Err(RecvError) => continue,
-                    Ok(WorkerInput::Item(line)) => {
-                        // TODO figure out how to make this work with arc
-                        // output_sender.send(WorkerOutput::Item(handle_line(line, *handler)));
-
-                        match input::read_line(line) {
-                            Err(e) => output_sender.send(WorkerOutput::Item(Err(e))).unwrap(),
-                            Ok(input) => match entity::from_input(input) {
-                                Err(e) => output_sender.send(WorkerOutput::Item(Err(e))).unwrap(),
-                                Ok(entities) => {
-                                    let mut results = Vec::new();
-                                    for e in entities {
-                                        match handler(e) {
-                                            Err(e) => {
-                                                output_sender
-                                                    .send(WorkerOutput::Item(Err(e)))
-                                                    .unwrap();
-                                                break;
-                                            }
-                                            Ok(lines) => results.extend(lines),
-                                        }
-                                    }
-                                    output_sender.send(WorkerOutput::Item(Ok(results))).unwrap();
-                                }
-                            },
-                        }
-                    }
-                    Ok(WorkerInput::Done) => {
-                        output_sender.send(WorkerOutput::Done).unwrap();
-                        break;
-                    }
+        let t = thread::spawn(move || loop {
+            match input_receiver.recv() {
+                Err(RecvError) => continue,
+                Ok(WorkerInput::Item(line_number, line)) => {
+                    let output = handler(line).map_err(|(error, raw)| Rejected {
+                        line: line_number,
+                        raw,
+                        error,
+                    });
+                    output_sender.send(WorkerOutput::Item(output)).unwrap();
+                }
+                Ok(WorkerInput::Done) => {
+                    output_sender.send(WorkerOutput::Done).unwrap();
+                    break;
                 }
             }
         });
@@ -133,9 +133,13 @@ where
                             println!("{}", l);
                         }
                     }
-                    Ok(WorkerOutput::Item(Err(e))) => {
-                        eprintln!("Application error: {:?}", e);
-                        ::std::process::exit(1);
+                    Ok(WorkerOutput::Item(Err(rejected))) => {
+                        if let Err(e) =
+                            rejects::reject(rejected.line, &rejected.raw, rejected.error)
+                        {
+                            eprintln!("geoq exited with error: {}", e.report());
+                            ::std::process::exit(1);
+                        }
                     }
                     Ok(WorkerOutput::Done) => {
                         output_channels.remove(i);
@@ -146,10 +150,9 @@ where
         }
     });
 
-    let reader = LineReader::new(input);
-    for (i, line) in reader.enumerate() {
+    for (i, (line_number, line)) in records.enumerate() {
         input_channels[i % num_workers]
-            .send(WorkerInput::Item(line))
+            .send(WorkerInput::Item(line_number, line))
             .unwrap();
     }
     (0..num_workers).for_each(|i| input_channels[i].send(WorkerInput::Done).unwrap());
@@ -186,4 +189,18 @@ mod tests {
         });
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn test_par_multi_line_entities() {
+        let mut input = r#"{
+  "type": "Polygon",
+  "coordinates": [[[-117.87, 34.77], [-117.69, 34.77], [-117.69, 34.90], [-117.87, 34.77]]]
+}
+34.2277,-118.2623
+"#
+        .as_bytes();
+
+        let res = for_entity_par(&mut input, move |entity| Ok(vec![entity.raw()]));
+        assert!(res.is_ok());
+    }
 }
@@ -0,0 +1,237 @@
+use crate::geoq::{
+    bbox, contains,
+    distance::Options,
+    index::SpatialIndex,
+    relate::{relate, Pattern},
+};
+use geo::algorithm::intersects::Intersects;
+use geo_types::Geometry;
+
+// Spatial relationships between an input and a query feature,
+// as used by 'filter' and 'join'
+#[derive(Clone, Copy, Debug)]
+pub enum Predicate {
+    // The input intersects the query
+    Intersects,
+    // The input falls within the query
+    Contains,
+    // The query falls within the input
+    Within,
+    // The input is less than a distance (in the units of the options) from the query
+    DWithin(f64, Options),
+    // The input and query meet only at their boundaries
+    Touches,
+    // The input and query share some interior points, but neither contains the other,
+    // and their intersection has a lower dimension than one of them
+    Crosses,
+    // The input and query share some interior points, but neither contains the other,
+    // and their intersection has the same dimension as both
+    Overlaps,
+    // The input and query don't meet at all
+    Disjoint,
+    // The input and query are topologically equal
+    Equals,
+    // The DE-9IM matrix of the query and input matches a pattern
+    Relate(Pattern),
+}
+
+impl Predicate {
+    // Whether only queries which intersect an input can match it, so the
+    // candidates for a match can be found from their bounding boxes
+    fn requires_intersection(&self) -> bool {
+        match self {
+            Predicate::Disjoint => false,
+            Predicate::Relate(pattern) => pattern.requires_intersection(),
+            _ => true,
+        }
+    }
+}
+
+struct Query<T> {
+    position: usize,
+    geom: Geometry<f64>,
+    value: T,
+}
+
+// A set of query features held in a spatial index, along with
+// some value for each one, like its properties
+pub struct QuerySet<T> {
+    index: SpatialIndex<Query<T>>,
+}
+
+fn is_polygonal(geom: &Geometry<f64>) -> bool {
+    match geom {
+        Geometry::Polygon(_) | Geometry::MultiPolygon(_) | Geometry::Rect(_) => true,
+        Geometry::Triangle(_) => true,
+        _ => false,
+    }
+}
+
+fn is_match(query: &Geometry<f64>, input: &Geometry<f64>, predicate: &Predicate) -> bool {
+    match predicate {
+        Predicate::Intersects => query.intersects(input),
+        Predicate::Contains if is_polygonal(query) => contains::contains_any(query, input),
+        Predicate::Contains => relate(query, input).is_contains(),
+        Predicate::Within if is_polygonal(input) => contains::contains_any(input, query),
+        Predicate::Within => relate(query, input).is_within(),
+        Predicate::DWithin(radius, options) => match options.distance(query, input) {
+            Some(d) => d < *radius,
+            None => false,
+        },
+        Predicate::Touches => relate(query, input).is_touches(),
+        Predicate::Crosses => relate(query, input).is_crosses(),
+        Predicate::Overlaps => relate(query, input).is_overlaps(),
+        Predicate::Disjoint => !query.intersects(input),
+        Predicate::Equals => relate(query, input).is_equal(),
+        Predicate::Relate(pattern) => pattern.matches(&relate(query, input)),
+    }
+}
+
+impl<T> QuerySet<T> {
+    pub fn new(queries: Vec<(Geometry<f64>, T)>) -> QuerySet<T> {
+        let queries = queries
+            .into_iter()
+            .enumerate()
+            .map(|(position, (geom, value))| Query {
+                position,
+                geom,
+                value,
+            })
+            .collect();
+        QuerySet {
+            index: SpatialIndex::new(queries, |q| &q.geom),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    // Queries which could match the input, based on their bounding boxes
+    fn candidates<'a>(
+        &'a self,
+        input: &Geometry<f64>,
+        predicate: &Predicate,
+    ) -> Box<dyn Iterator<Item = &'a Query<T>> + 'a> {
+        match *predicate {
+            Predicate::DWithin(radius, options) => {
+                let boxes = options.search_boxes(&bbox::bbox(input), radius);
+                Box::new(self.index.candidates_in(&boxes).into_iter())
+            }
+            _ if !predicate.requires_intersection() => Box::new(self.index.items()),
+            _ => Box::new(self.index.candidates(&bbox::bbox(input)).into_iter()),
+        }
+    }
+
+    pub fn any(&self, input: &Geometry<f64>, predicate: &Predicate) -> bool {
+        self.candidates(input, predicate)
+            .any(|q| is_match(&q.geom, input, predicate))
+    }
+
+    // Values for the queries matching the input, in the order the queries were given
+    pub fn matches(&self, input: &Geometry<f64>, predicate: &Predicate) -> Vec<&T> {
+        let mut found: Vec<&Query<T>> = self
+            .candidates(input, predicate)
+            .filter(|q| is_match(&q.geom, input, predicate))
+            .collect();
+        found.sort_by_key(|q| q.position);
+        found.into_iter().map(|q| &q.value).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Predicate, QuerySet};
+    use crate::geoq::distance::Options;
+    use geo_types::{line_string, polygon, Geometry, Point};
+
+    fn square(x: f64, y: f64, size: f64) -> Geometry<f64> {
+        Geometry::Polygon(polygon![
+            (x: x, y: y),
+            (x: x + size, y: y),
+            (x: x + size, y: y + size),
+            (x: x, y: y + size),
+            (x: x, y: y),
+        ])
+    }
+
+    #[test]
+    fn test_matches() {
+        let queries = QuerySet::new(vec![
+            (square(0.0, 0.0, 10.0), "big"),
+            (square(20.0, 0.0, 1.0), "far"),
+            (square(1.0, 1.0, 1.0), "small"),
+        ]);
+        let point = Geometry::Point(Point::new(1.5, 1.5));
+        assert_eq!(
+            vec![&"big", &"small"],
+            queries.matches(&point, &Predicate::Intersects)
+        );
+        assert_eq!(
+            vec![&"big", &"small"],
+            queries.matches(&point, &Predicate::Contains)
+        );
+        assert!(queries.matches(&point, &Predicate::Within).is_empty());
+        assert_eq!(
+            vec![&"small"],
+            queries.matches(&square(0.5, 0.5, 5.0), &Predicate::Within)
+        );
+
+        let planar = Options::parse(Some("planar"), None).unwrap();
+        let near = Geometry::Point(Point::new(19.0, 0.5));
+        assert!(!queries.any(&near, &Predicate::DWithin(1.0, planar)));
+        assert_eq!(
+            vec![&"big", &"far"],
+            queries.matches(&near, &Predicate::DWithin(10.0, planar))
+        );
+    }
+
+    #[test]
+    fn test_dwithin_across_antimeridian() {
+        let queries = QuerySet::new(vec![
+            (Geometry::Point(Point::new(170.0, 0.0)), "west"),
+            (Geometry::Point(Point::new(-179.9, 0.0)), "east"),
+        ]);
+        let options = Options::parse(None, None).unwrap();
+        let input = Geometry::Point(Point::new(179.9, 0.0));
+        assert_eq!(
+            vec![&"east"],
+            queries.matches(&input, &Predicate::DWithin(50_000.0, options))
+        );
+    }
+
+    #[test]
+    fn test_relate_matches() {
+        let queries = QuerySet::new(vec![
+            (square(0.0, 0.0, 10.0), "big"),
+            (square(20.0, 0.0, 1.0), "far"),
+            (square(10.0, 0.0, 1.0), "adjacent"),
+        ]);
+        assert_eq!(
+            vec![&"adjacent"],
+            queries.matches(&square(10.0, 0.0, 1.0), &Predicate::Equals)
+        );
+        assert_eq!(
+            vec![&"big"],
+            queries.matches(&square(10.0, 0.0, 1.0), &Predicate::Touches)
+        );
+        assert_eq!(
+            vec![&"far", &"adjacent"],
+            queries.matches(&square(9.0, 5.0, 2.0), &Predicate::Disjoint)
+        );
+        let line = Geometry::LineString(line_string![(x: 5.0, y: 0.5), (x: 15.0, y: 0.5)]);
+        assert_eq!(
+            vec![&"big", &"adjacent"],
+            queries.matches(&line, &Predicate::Crosses)
+        );
+        let pattern = "2********".parse().unwrap();
+        assert_eq!(
+            vec![&"big"],
+            queries.matches(&square(9.0, 5.0, 2.0), &Predicate::Relate(pattern))
+        );
+        assert_eq!(
+            vec![&"big"],
+            queries.matches(&square(9.0, 5.0, 2.0), &Predicate::Overlaps)
+        );
+    }
+}
@@ -2,24 +2,40 @@ extern crate geo_types;
 
 use crate::geoq::entity::{self, Entity};
 use crate::geoq::error::Error;
-use crate::geoq::input;
+use crate::geoq::input::{self, InputFormat};
+use crate::geoq::records::Records;
+use crate::geoq::rejects;
 use std::collections::VecDeque;
 use std::io;
 use std::io::BufRead;
 use std::iter::FromIterator;
 
 pub struct Reader<'a> {
-    reader: &'a mut dyn BufRead,
+    records: Records<'a>,
     entities: VecDeque<Entity>,
+    line: usize,
+    format: InputFormat,
 }
 
 impl<'a> Reader<'a> {
+    // Reader for inputs in the format given by --input-format
     pub fn new(reader: &'a mut dyn BufRead) -> Reader<'a> {
+        Reader::with_format(reader, input::format())
+    }
+
+    pub fn with_format(reader: &'a mut dyn BufRead, format: InputFormat) -> Reader<'a> {
         Reader {
-            reader,
+            records: Records::new(reader),
             entities: VecDeque::new(),
+            line: 0,
+            format,
         }
     }
+
+    // Input line number of the most recently read entity
+    pub fn line(&self) -> usize {
+        self.line
+    }
 }
 
 pub fn read_line(buf_read: &mut dyn BufRead) -> Option<String> {
@@ -40,24 +56,28 @@ impl<'a> Iterator for Reader<'a> {
             return Some(Ok(entity));
         }
 
-        while let Some(line) = read_line(&mut *self.reader) {
-            match input::read_line(line) {
-                Ok(i) => match entity::from_input(i) {
-                    Ok(e_vec) => {
-                        let mut entities = VecDeque::from_iter(e_vec);
-                        if entities.is_empty() {
-                            continue;
-                        } else {
-                            self.entities.append(&mut entities);
-                            let e = self.entities.pop_front().unwrap();
-                            return Some(Ok(e));
-                        }
+        while let Some((line_number, line)) = self.records.next() {
+            self.line = line_number;
+            let member = self.records.in_collection();
+            match entity::read(line, self.format) {
+                Ok(mut e_vec) => {
+                    // See par::for_entity_par
+                    if member {
+                        e_vec.iter_mut().for_each(entity::reserialize);
                     }
+                    let mut entities = VecDeque::from_iter(e_vec);
+                    if entities.is_empty() {
+                        continue;
+                    } else {
+                        self.entities.append(&mut entities);
+                        let e = self.entities.pop_front().unwrap();
+                        return Some(Ok(e));
+                    }
+                }
+                Err((e, raw)) => match rejects::reject(line_number, &raw, e) {
+                    Ok(()) => continue,
                     Err(e) => return Some(Err(e)),
                 },
-                Err(e) => {
-                    return Some(Err(e));
-                }
             }
         }
         None
@@ -76,25 +96,23 @@ where
 
 pub fn for_entity<F>(mut handler: F) -> Result<(), Error>
 where
-    F: FnMut(Entity) -> Result<(), Error>,
+    F: FnMut(&Entity) -> Result<(), Error>,
 {
-    entities(|e_iter| {
-        for e_res in e_iter {
-            match e_res {
-                Err(e) => return Err(e),
-                Ok(entity) => {
-                    if let Err(e) = handler(entity) {
-                        return Err(e);
-                    }
-                }
-            }
+    let stdin = io::stdin();
+    let mut stdin_reader = stdin.lock();
+    let mut reader = Reader::new(&mut stdin_reader);
+    while let Some(e_res) = reader.next() {
+        let entity = e_res?;
+        if let Err(e) = handler(&entity) {
+            rejects::reject(reader.line(), &entity.raw(), e)?;
         }
-        Ok(())
-    })
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::geoq::input::InputFormat;
     use crate::geoq::reader::Reader;
 
     #[test]
@@ -128,4 +146,62 @@ mod tests {
         assert_eq!("9q5", a.unwrap().raw());
         assert_eq!("9q4", b.unwrap().raw());
     }
+
+    #[test]
+    fn test_reading_pretty_printed_geojson() {
+        let mut pointer = r#"{
+  "type": "Point",
+  "coordinates": [125.6, 10.1]
+}
+9q5
+{
+  "type": "Feature",
+  "properties": {"name": "{not a brace}"},
+  "geometry": {
+    "type": "Point",
+    "coordinates": [
+      34.0,
+      12.0
+    ]
+  }
+}"#
+        .as_bytes();
+        let mut reader = Reader::new(&mut pointer);
+        let a = reader.next().unwrap().unwrap();
+        let b = reader.next().unwrap().unwrap();
+        let c = reader.next().unwrap().unwrap();
+        assert!(reader.next().is_none());
+        assert_eq!(r#"{"type": "Point","coordinates": [125.6, 10.1]}"#, a.raw());
+        assert_eq!("9q5", b.raw());
+        assert_eq!("POINT(34 12)", c.wkt().to_string());
+    }
+
+    #[test]
+    fn test_reading_pretty_printed_feature_collection() {
+        let mut pointer = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [34.0, 12.0]}},
+    {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [78.0, 56.0]}}
+  ]
+}
+"#
+        .as_bytes();
+        let reader = Reader::new(&mut pointer);
+        assert_eq!(2, reader.count());
+    }
+
+    #[test]
+    fn test_reading_with_format() {
+        let mut pointer = "9bcdef012345678\n-118,34".as_bytes();
+        let mut reader = Reader::with_format(&mut pointer, InputFormat::Geohash);
+        let gh = reader.next().unwrap().unwrap();
+        assert_eq!("Geohash: 9bcdef012345678", format!("{}", gh));
+        assert!(reader.next().unwrap().is_err());
+
+        let mut pointer = "-118,34".as_bytes();
+        let mut reader = Reader::with_format(&mut pointer, InputFormat::LonLat);
+        let point = reader.next().unwrap().unwrap();
+        assert_eq!("POINT(-118 34)", point.wkt().to_string());
+    }
 }
@@ -0,0 +1,447 @@
+use crate::geoq::reader;
+use std::io::{BufRead, Cursor};
+
+// Splits an input stream into the raw text records which geoq parses as entities.
+//
+// Most inputs are 1 per line, but JSON objects (e.g. pretty-printed GeoJSON) are
+// read until they are balanced, however many lines that takes. Newlines and
+// indentation between JSON tokens are dropped, so each object comes out as a
+// single-line record.
+//
+// FeatureCollections get special handling: rather than reading the whole collection
+// into memory, once the "features" array is found each of its members is handed
+// out as its own record. This keeps memory bounded by the size of the largest
+// feature rather than the size of the collection. Only objects whose "type" of
+// "FeatureCollection" comes before their "features" are streamed this way.
+//
+// An object which is still open at the end of the input is given up on: its
+// first line is handed out alone (to be rejected as invalid), and the lines
+// after it are read again as records of their own.
+//
+// Each record is given along with the (1-based) line number where it starts.
+pub struct Records<'a> {
+    reader: Box<dyn BufRead + 'a>,
+    in_features: bool,
+    line: usize,
+}
+
+// A record's text, along with whether it was a member of a streamed FeatureCollection
+pub struct Record {
+    pub text: String,
+    pub member: bool,
+}
+
+enum Scanned {
+    Value(Vec<u8>),
+    FeaturesStart,
+    // Everything read for a value which never closed
+    Unterminated(Vec<u8>),
+}
+
+// Accumulates JSON bytes while dropping line breaks and the
+// whitespace at the start and end of each line
+#[derive(Default)]
+struct Compact {
+    buf: Vec<u8>,
+    pending_space: Vec<u8>,
+    line_start: bool,
+}
+
+impl Compact {
+    fn push(&mut self, b: u8) {
+        if self.line_start {
+            self.pending_space.clear();
+        } else {
+            self.buf.append(&mut self.pending_space);
+        }
+        self.line_start = false;
+        self.buf.push(b);
+    }
+
+    fn space(&mut self, b: u8) {
+        self.pending_space.push(b);
+    }
+
+    fn newline(&mut self) {
+        self.pending_space.clear();
+        self.line_start = true;
+    }
+}
+
+impl<'a> Records<'a> {
+    pub fn new(reader: &'a mut dyn BufRead) -> Records<'a> {
+        Records {
+            reader: Box::new(reader),
+            in_features: false,
+            line: 0,
+        }
+    }
+
+    // Whether the most recent record was a member of a streamed FeatureCollection
+    pub fn in_collection(&self) -> bool {
+        self.in_features
+    }
+
+    // Records paired with whether they came from a streamed FeatureCollection
+    pub fn tagged(mut self) -> impl Iterator<Item = (usize, Record)> + 'a {
+        std::iter::from_fn(move || {
+            let (line, text) = self.next()?;
+            let member = self.in_collection();
+            Some((line, Record { text, member }))
+        })
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        let line = reader::read_line(&mut self.reader);
+        if line.is_some() {
+            self.line += 1;
+        }
+        line
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        match self.reader.fill_buf() {
+            Ok(buf) if !buf.is_empty() => Some(buf[0]),
+            _ => None,
+        }
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\r') = self.peek() {
+            self.reader.consume(1);
+        }
+    }
+
+    // Read a single JSON value. When stream_features is set and the "features"
+    // member of a top-level FeatureCollection is reached, stop before reading it
+    // so the array items can be read one at a time.
+    fn scan(&mut self, stream_features: bool) -> Scanned {
+        let mut out = Compact::default();
+        let mut raw: Vec<u8> = Vec::new();
+        let mut is_collection = false;
+        let mut depth: usize = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut string_start = 0;
+        let mut last_string: Option<Vec<u8>> = None;
+        let mut key: Option<Vec<u8>> = None;
+
+        while let Some(b) = self.peek() {
+            // Scalars (e.g. a stray null in a features array) end at the next delimiter
+            if !in_string && depth == 0 && !out.buf.is_empty() {
+                if let b',' | b']' | b'}' | b'\n' = b {
+                    break;
+                }
+            }
+            self.reader.consume(1);
+            raw.push(b);
+            if b == b'\n' {
+                self.line += 1;
+            }
+
+            if in_string {
+                out.push(b);
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                    if depth == 1 {
+                        let string = out.buf[string_start + 1..out.buf.len() - 1].to_vec();
+                        if key.as_deref() == Some(b"type") && string == b"FeatureCollection" {
+                            is_collection = true;
+                        }
+                        last_string = Some(string);
+                    }
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => {
+                    out.push(b);
+                    in_string = true;
+                    string_start = out.buf.len() - 1;
+                }
+                b'[' if stream_features
+                    && is_collection
+                    && depth == 1
+                    && key.as_deref() == Some(b"features") =>
+                {
+                    return Scanned::FeaturesStart;
+                }
+                b'{' | b'[' => {
+                    out.push(b);
+                    depth += 1;
+                }
+                b'}' | b']' => {
+                    out.push(b);
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                b':' => {
+                    out.push(b);
+                    if depth == 1 {
+                        key = last_string.take();
+                    }
+                }
+                b',' => {
+                    out.push(b);
+                    if depth == 1 {
+                        key = None;
+                    }
+                }
+                b'\n' => out.newline(),
+                b' ' | b'\t' | b'\r' => out.space(b),
+                _ => out.push(b),
+            }
+        }
+        if depth > 0 || in_string {
+            Scanned::Unterminated(raw)
+        } else {
+            Scanned::Value(out.buf)
+        }
+    }
+
+    // Hands out the first line of an unterminated value as a record, and
+    // sets up the lines after it to be read again
+    fn give_up(&mut self, line: usize, mut raw: Vec<u8>) -> (usize, String) {
+        let rest = match raw.iter().position(|b| *b == b'\n') {
+            Some(i) => raw.split_off(i + 1),
+            None => vec![],
+        };
+        self.reader = Box::new(Cursor::new(rest));
+        self.line = line;
+        self.in_features = false;
+        let first = String::from_utf8_lossy(&raw).trim().to_string();
+        (line, first)
+    }
+
+    // Discard the remainder of an object which has already been
+    // read up to the given depth
+    fn skip_to_depth_zero(&mut self, mut depth: usize) {
+        let mut in_string = false;
+        let mut escaped = false;
+        while depth > 0 {
+            let b = match self.peek() {
+                Some(b) => b,
+                None => return,
+            };
+            self.reader.consume(1);
+            if b == b'\n' {
+                self.line += 1;
+            }
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => depth -= 1,
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn next_feature(&mut self) -> Option<(usize, String)> {
+        loop {
+            match self.peek() {
+                None => {
+                    self.in_features = false;
+                    return None;
+                }
+                Some(b']') => {
+                    // End of the features array -- skip any remaining members
+                    // of the FeatureCollection along with the rest of its line
+                    self.reader.consume(1);
+                    self.skip_to_depth_zero(1);
+                    self.read_line();
+                    self.in_features = false;
+                    return None;
+                }
+                Some(b'\n') => {
+                    self.reader.consume(1);
+                    self.line += 1;
+                }
+                Some(b',') | Some(b' ') | Some(b'\t') | Some(b'\r') => self.reader.consume(1),
+                Some(_) => {
+                    let line = self.line + 1;
+                    match self.scan(false) {
+                        Scanned::Value(buf) => {
+                            return Some((line, String::from_utf8_lossy(&buf).into_owned()))
+                        }
+                        Scanned::Unterminated(raw) => return Some(self.give_up(line, raw)),
+                        Scanned::FeaturesStart => (),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = (usize, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.in_features {
+            if let Some(feature) = self.next_feature() {
+                return Some(feature);
+            }
+        }
+
+        self.skip_inline_whitespace();
+        let line = self.line + 1;
+        match self.peek()? {
+            b'{' => match self.scan(true) {
+                Scanned::Value(buf) => {
+                    let mut record = String::from_utf8_lossy(&buf).into_owned();
+                    // Keep anything trailing the object on its line, so
+                    // that e.g. '{...} junk' is still reported as invalid
+                    if let Some(rest) = self.read_line() {
+                        record.push_str(&rest);
+                    }
+                    Some((line, record))
+                }
+                Scanned::FeaturesStart => {
+                    self.in_features = true;
+                    self.next()
+                }
+                Scanned::Unterminated(raw) => Some(self.give_up(line, raw)),
+            },
+            _ => self.read_line().map(|record| (line, record)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Records;
+
+    fn records(input: &str) -> Vec<String> {
+        let mut pointer = input.as_bytes();
+        Records::new(&mut pointer).map(|(_, r)| r).collect()
+    }
+
+    fn line_numbers(input: &str) -> Vec<usize> {
+        let mut pointer = input.as_bytes();
+        Records::new(&mut pointer).map(|(l, _)| l).collect()
+    }
+
+    #[test]
+    fn test_single_line_records() {
+        assert_eq!(
+            vec![
+                "9q5",
+                "12,34",
+                "{\"type\": \"Point\", \"coordinates\": [1, 2]}"
+            ],
+            records("9q5\n12,34\n{\"type\": \"Point\", \"coordinates\": [1, 2]}\n")
+        );
+    }
+
+    #[test]
+    fn test_multi_line_object() {
+        let input = "{\n  \"type\": \"Point\",\n  \"coordinates\": [\n    1,\n    2\n  ]\n}\n9q5\n";
+        assert_eq!(
+            vec!["{\"type\": \"Point\",\"coordinates\": [1,2]}", "9q5"],
+            records(input)
+        );
+    }
+
+    #[test]
+    fn test_braces_in_strings() {
+        let input = "{\"properties\": {\"a\": \"}\\\"{[\"}}\n9q5";
+        assert_eq!(
+            vec!["{\"properties\": {\"a\": \"}\\\"{[\"}}", "9q5"],
+            records(input)
+        );
+    }
+
+    #[test]
+    fn test_streams_feature_collection() {
+        let input = r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "properties": {"features": [1]}, "geometry": null}, {"type": "Feature", "properties": {}, "geometry": null}], "bbox": [1, 2, 3, 4]}
+9q5
+"#;
+        assert_eq!(
+            vec![
+                r#"{"type": "Feature", "properties": {"features": [1]}, "geometry": null}"#,
+                r#"{"type": "Feature", "properties": {}, "geometry": null}"#,
+                "9q5"
+            ],
+            records(input)
+        );
+    }
+
+    #[test]
+    fn test_streams_pretty_printed_feature_collection() {
+        let input = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "properties": {},
+      "geometry": {"type": "Point", "coordinates": [1, 2]}
+    },
+    {
+      "type": "Feature",
+      "properties": {},
+      "geometry": {"type": "Point", "coordinates": [3, 4]}
+    }
+  ]
+}
+{"type":"FeatureCollection","features":[]}
+"#;
+        assert_eq!(
+            vec![
+                r#"{"type": "Feature","properties": {},"geometry": {"type": "Point", "coordinates": [1, 2]}}"#,
+                r#"{"type": "Feature","properties": {},"geometry": {"type": "Point", "coordinates": [3, 4]}}"#,
+            ],
+            records(input)
+        );
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let input = "9q5\n{\n  \"type\": \"Point\",\n  \"coordinates\": [1, 2]\n}\n12,34\n{\"type\": \"FeatureCollection\", \"features\": [\n  {\"a\": 1},\n  {\"b\": 2}\n]}\n9q4\n";
+        assert_eq!(vec![1, 2, 6, 8, 9, 11], line_numbers(input));
+    }
+
+    #[test]
+    fn test_features_key_outside_collection() {
+        let input = r#"{"type": "Feature", "features": [1, 2], "properties": {}, "geometry": null}
+9q5
+"#;
+        assert_eq!(
+            vec![
+                r#"{"type": "Feature", "features": [1, 2], "properties": {}, "geometry": null}"#,
+                "9q5"
+            ],
+            records(input)
+        );
+    }
+
+    #[test]
+    fn test_unterminated_object() {
+        let input = "9q5\n{\"type\": \"Point\",\n12,34\n9q4\n";
+        assert_eq!(
+            vec!["9q5", "{\"type\": \"Point\",", "12,34", "9q4"],
+            records(input)
+        );
+        assert_eq!(vec![1, 2, 3, 4], line_numbers(input));
+    }
+
+    #[test]
+    fn test_trailing_content_is_kept() {
+        assert_eq!(vec!["{\"a\": 1}junk"], records("{\"a\": 1} junk\n"));
+    }
+}
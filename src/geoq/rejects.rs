@@ -0,0 +1,120 @@
+use crate::geoq::error::{self, Error};
+use once_cell::sync::{Lazy, OnceCell};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// Handling for inputs which can't be read or processed, as configured
+// by the global --on-error option.
+//
+// abort: exit on the first invalid input (default)
+// skip: silently drop invalid inputs
+// stderr: drop invalid inputs and report each one to STDERR
+// reject-file=PATH: drop invalid inputs and write their raw text to PATH
+#[derive(Debug, Clone, PartialEq)]
+pub enum Policy {
+    Abort,
+    Skip,
+    Stderr,
+    RejectFile(String),
+}
+
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(Policy::Abort),
+            "skip" => Ok(Policy::Skip),
+            "stderr" => Ok(Policy::Stderr),
+            _ if s.starts_with("reject-file=") && s.len() > "reject-file=".len() => {
+                Ok(Policy::RejectFile(s["reject-file=".len()..].to_string()))
+            }
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid --on-error value: {}. Expected abort, skip, stderr, or reject-file=PATH.",
+                s
+            ))),
+        }
+    }
+}
+
+static POLICY: OnceCell<Policy> = OnceCell::new();
+static REJECTED: AtomicUsize = AtomicUsize::new(0);
+static REJECT_FILE: Lazy<Mutex<Option<BufWriter<File>>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_policy(policy: Policy) -> Result<(), Error> {
+    if let Policy::RejectFile(ref path) = policy {
+        let f = File::create(path)?;
+        *REJECT_FILE.lock().unwrap() = Some(BufWriter::new(f));
+    }
+    POLICY
+        .set(policy)
+        .map_err(|_| Error::ProgramError("--on-error policy was already set".to_string()))
+}
+
+pub fn policy() -> &'static Policy {
+    POLICY.get().unwrap_or(&Policy::Abort)
+}
+
+// Apply the error policy to an input which failed. Gives the
+// error back, tagged with the input's line and text, if
+// processing should stop.
+pub fn reject(line: usize, raw: &str, err: Error) -> Result<(), Error> {
+    let err = err.at_input(line, raw);
+    match policy() {
+        Policy::Abort => return Err(err),
+        Policy::Skip => (),
+        Policy::Stderr => eprintln!("Skipped: {}", err.report()),
+        Policy::RejectFile(_) => {
+            if let Some(f) = REJECT_FILE.lock().unwrap().as_mut() {
+                writeln!(f, "{}", raw)?;
+            }
+        }
+    }
+    REJECTED.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+pub fn rejected_count() -> usize {
+    REJECTED.load(Ordering::SeqCst)
+}
+
+// Flush the reject file (if any) and report how many inputs were dropped
+pub fn summary() -> Result<(), Error> {
+    if let Some(f) = REJECT_FILE.lock().unwrap().as_mut() {
+        f.flush()?;
+    }
+    let count = rejected_count();
+    if count > 0 {
+        match policy() {
+            Policy::RejectFile(path) => {
+                eprintln!(
+                    "geoq rejected {} invalid input(s), written to {}",
+                    count, path
+                )
+            }
+            _ => eprintln!("geoq skipped {} invalid input(s)", count),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+
+    #[test]
+    fn test_parsing_policies() {
+        assert_eq!(Policy::Abort, "abort".parse().unwrap());
+        assert_eq!(Policy::Skip, "skip".parse().unwrap());
+        assert_eq!(Policy::Stderr, "stderr".parse().unwrap());
+        assert_eq!(
+            Policy::RejectFile("/tmp/bad.txt".to_string()),
+            "reject-file=/tmp/bad.txt".parse().unwrap()
+        );
+        assert!("reject-file=".parse::<Policy>().is_err());
+        assert!("pizza".parse::<Policy>().is_err());
+    }
+}
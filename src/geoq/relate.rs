@@ -0,0 +1,408 @@
+use crate::geoq::error::Error;
+use geo::algorithm::contains::Contains;
+use geo::algorithm::euclidean_distance::EuclideanDistance;
+use geo::algorithm::line_intersection::{line_intersection, LineIntersection};
+use geo_types::{Coord, Geometry, Line, LineString, Point, Polygon};
+use std::fmt;
+use std::str::FromStr;
+
+// Coordinates closer than this are treated as the same point, to allow for
+// rounding in computed intersections
+const TOLERANCE: f64 = 1e-9;
+
+// Where a point lies relative to a geometry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Location {
+    Interior = 0,
+    Boundary = 1,
+    Exterior = 2,
+}
+
+// A DE-9IM intersection matrix, giving the dimension of the intersection
+// between the interior, boundary and exterior of one geometry and those of
+// another, or None where they don't intersect
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntersectionMatrix([[Option<u8>; 3]; 3]);
+
+// A DE-9IM pattern, like "T*F**F***", which intersection matrices can be
+// checked against. Each entry is one of:
+// T - intersects, with any dimension
+// F - doesn't intersect
+// * - anything
+// 0, 1, 2 - intersects, with the given dimension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pattern([u8; 9]);
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars = s.trim().to_uppercase().into_bytes();
+        if chars.len() != 9 || chars.iter().any(|c| !b"TF*012".contains(c)) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid DE-9IM pattern: {}. Expected 9 characters, each one of T, F, *, 0, 1 or 2.",
+                s
+            )));
+        }
+        let mut pattern = [0; 9];
+        pattern.copy_from_slice(&chars);
+        Ok(Pattern(pattern))
+    }
+}
+
+impl Pattern {
+    pub fn matches(&self, matrix: &IntersectionMatrix) -> bool {
+        self.0.iter().enumerate().all(|(i, c)| {
+            let dim = matrix.0[i / 3][i % 3];
+            match c {
+                b'T' => dim.is_some(),
+                b'F' => dim.is_none(),
+                b'*' => true,
+                d => dim == Some(d - b'0'),
+            }
+        })
+    }
+
+    // Whether only intersecting geometries can match, i.e. whether it
+    // requires the interiors or boundaries of the two to meet
+    pub fn requires_intersection(&self) -> bool {
+        [0, 1, 3, 4]
+            .iter()
+            .any(|&i| self.0[i] != b'F' && self.0[i] != b'*')
+    }
+}
+
+fn is(matrix: &IntersectionMatrix, pattern: &str) -> bool {
+    pattern.parse::<Pattern>().unwrap().matches(matrix)
+}
+
+impl IntersectionMatrix {
+    pub fn get(&self, a: Location, b: Location) -> Option<u8> {
+        self.0[a as usize][b as usize]
+    }
+
+    fn add(&mut self, a: Location, b: Location, dim: u8) {
+        let entry = &mut self.0[a as usize][b as usize];
+        if entry.map_or(true, |d| d < dim) {
+            *entry = Some(dim);
+        }
+    }
+
+    // Dimensions of the two geometries: their interiors have the dimension of
+    // the geometry, and must meet some part of the other one
+    fn dimensions(&self) -> (Option<u8>, Option<u8>) {
+        let a = self.0[0].iter().max().cloned().flatten();
+        let b = (0..3).map(|i| self.0[i][0]).max().flatten();
+        (a, b)
+    }
+
+    pub fn is_equal(&self) -> bool {
+        is(self, "T*F**FFF*")
+    }
+
+    pub fn is_disjoint(&self) -> bool {
+        is(self, "FF*FF****")
+    }
+
+    pub fn is_intersects(&self) -> bool {
+        !self.is_disjoint()
+    }
+
+    pub fn is_touches(&self) -> bool {
+        is(self, "FT*******") || is(self, "F**T*****") || is(self, "F***T****")
+    }
+
+    pub fn is_within(&self) -> bool {
+        is(self, "T*F**F***")
+    }
+
+    pub fn is_contains(&self) -> bool {
+        is(self, "T*****FF*")
+    }
+
+    pub fn is_crosses(&self) -> bool {
+        match self.dimensions() {
+            (Some(1), Some(1)) => is(self, "0********"),
+            (Some(a), Some(b)) if a < b => is(self, "T*T******"),
+            (Some(a), Some(b)) if a > b => is(self, "T*****T**"),
+            _ => false,
+        }
+    }
+
+    pub fn is_overlaps(&self) -> bool {
+        match self.dimensions() {
+            (Some(1), Some(1)) => is(self, "1*T***T**"),
+            (Some(a), Some(b)) if a == b => is(self, "T*T***T**"),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for IntersectionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.0.iter() {
+            for dim in row.iter() {
+                match dim {
+                    Some(d) => write!(f, "{}", d)?,
+                    None => write!(f, "F")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// A geometry broken down into its points, lines and polygons
+struct Parts {
+    points: Vec<Coord<f64>>,
+    lines: Vec<LineString<f64>>,
+    polygons: Vec<Polygon<f64>>,
+}
+
+impl Parts {
+    fn new(geom: &Geometry<f64>) -> Parts {
+        let mut parts = Parts {
+            points: Vec::new(),
+            lines: Vec::new(),
+            polygons: Vec::new(),
+        };
+        parts.add(geom);
+        parts
+    }
+
+    fn add(&mut self, geom: &Geometry<f64>) {
+        match geom {
+            Geometry::Point(p) => self.points.push(p.0),
+            Geometry::MultiPoint(mp) => self.points.extend(mp.0.iter().map(|p| p.0)),
+            Geometry::Line(l) => self.lines.push(LineString(vec![l.start, l.end])),
+            Geometry::LineString(ls) => self.lines.push(ls.clone()),
+            Geometry::MultiLineString(mls) => self.lines.extend(mls.0.iter().cloned()),
+            Geometry::Polygon(p) => self.polygons.push(p.clone()),
+            Geometry::MultiPolygon(mp) => self.polygons.extend(mp.0.iter().cloned()),
+            Geometry::Rect(r) => self.polygons.push(r.to_polygon()),
+            Geometry::Triangle(t) => self.polygons.push(t.to_polygon()),
+            Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| self.add(g)),
+        }
+    }
+
+    fn segments(&self) -> Vec<Line<f64>> {
+        let mut segments: Vec<Line<f64>> = Vec::new();
+        for ls in self.lines.iter() {
+            segments.extend(ls.lines());
+        }
+        for poly in self.polygons.iter() {
+            segments.extend(poly.exterior().lines());
+            poly.interiors()
+                .iter()
+                .for_each(|ring| segments.extend(ring.lines()));
+        }
+        segments
+    }
+
+    fn vertices(&self) -> Vec<Coord<f64>> {
+        let mut vertices = self.points.clone();
+        for ls in self.lines.iter() {
+            vertices.extend(ls.0.iter());
+        }
+        for poly in self.polygons.iter() {
+            vertices.extend(poly.exterior().0.iter());
+            poly.interiors()
+                .iter()
+                .for_each(|ring| vertices.extend(ring.0.iter()));
+        }
+        vertices
+    }
+
+    // Where a point lies, following the OGC "mod 2" rule: points on an odd
+    // number of component boundaries are on the boundary, so the shared
+    // edges of adjacent polygons, or the shared ends of joined lines, are
+    // in the interior
+    fn locate(&self, c: Coord<f64>) -> Location {
+        let point = Point(c);
+        let on_line = |ls: &LineString<f64>| ls.lines().any(|l| on_segment(c, &l));
+        let mut boundaries = 0;
+        let mut inside = self.points.iter().any(|p| same(*p, c));
+
+        for ls in self.lines.iter() {
+            let closed = ls.0.len() > 2 && same(ls.0[0], ls.0[ls.0.len() - 1]);
+            let ends = [ls.0.first(), ls.0.last()];
+            if !closed && ends.iter().any(|end| end.map_or(false, |e| same(*e, c))) {
+                boundaries += 1;
+            } else if on_line(ls) {
+                inside = true;
+            }
+        }
+
+        for poly in self.polygons.iter() {
+            if on_line(poly.exterior()) || poly.interiors().iter().any(on_line) {
+                boundaries += 1;
+            } else if poly.contains(&point) {
+                inside = true;
+            }
+        }
+
+        if boundaries % 2 == 1 {
+            Location::Boundary
+        } else if boundaries > 0 || inside {
+            Location::Interior
+        } else {
+            Location::Exterior
+        }
+    }
+}
+
+fn same(a: Coord<f64>, b: Coord<f64>) -> bool {
+    (a.x - b.x).abs() < TOLERANCE && (a.y - b.y).abs() < TOLERANCE
+}
+
+fn on_segment(c: Coord<f64>, l: &Line<f64>) -> bool {
+    Point(c).euclidean_distance(l) < TOLERANCE
+}
+
+// Cut each segment at the points where it meets the other geometry
+fn split(segments: &[Line<f64>], other: &Parts, nodes: &mut Vec<Coord<f64>>) -> Vec<Line<f64>> {
+    let others = other.segments();
+    let mut pieces = Vec::new();
+    for l in segments.iter() {
+        let mut cuts: Vec<Coord<f64>> = vec![l.start, l.end];
+        cuts.extend(other.points.iter().filter(|c| on_segment(**c, l)));
+        for other in others.iter() {
+            match line_intersection(*l, *other) {
+                Some(LineIntersection::SinglePoint { intersection, .. }) => cuts.push(intersection),
+                Some(LineIntersection::Collinear { intersection }) => {
+                    cuts.push(intersection.start);
+                    cuts.push(intersection.end);
+                }
+                None => (),
+            }
+        }
+        nodes.extend(cuts.iter());
+        let position = |c: &Coord<f64>| (c.x - l.start.x) * l.dx() + (c.y - l.start.y) * l.dy();
+        cuts.sort_by(|a, b| position(a).partial_cmp(&position(b)).unwrap());
+        cuts.dedup_by(|a, b| same(*a, *b));
+        pieces.extend(cuts.windows(2).map(|w| Line::new(w[0], w[1])));
+    }
+    pieces
+}
+
+// Computes the DE-9IM matrix by locating sample points from each part of the
+// arrangement formed by overlaying the two geometries: every node where they
+// meet, the middle of every edge between those nodes, and points just to
+// either side of each edge, which cover every face
+pub fn relate(a: &Geometry<f64>, b: &Geometry<f64>) -> IntersectionMatrix {
+    let (a, b) = (Parts::new(a), Parts::new(b));
+    let mut nodes = a.vertices();
+    nodes.extend(b.vertices());
+    let mut edges = split(&a.segments(), &b, &mut nodes);
+    edges.extend(split(&b.segments(), &a, &mut nodes));
+
+    let mut matrix = IntersectionMatrix([[None; 3]; 3]);
+    matrix.add(Location::Exterior, Location::Exterior, 2);
+    let mut sample = |c: Coord<f64>, dim: u8| matrix.add(a.locate(c), b.locate(c), dim);
+
+    for node in nodes {
+        sample(node, 0);
+    }
+    for edge in edges.iter() {
+        let mid = Coord {
+            x: (edge.start.x + edge.end.x) / 2.0,
+            y: (edge.start.y + edge.end.y) / 2.0,
+        };
+        sample(mid, 1);
+
+        // Step off the edge by less than the distance to any other one, so
+        // the sample lands in a face next to this edge
+        let clearance = edges
+            .iter()
+            .map(|other| Point(mid).euclidean_distance(other))
+            .filter(|d| *d > TOLERANCE)
+            .fold(f64::INFINITY, f64::min);
+        let length = (edge.dx().powi(2) + edge.dy().powi(2)).sqrt();
+        if length < TOLERANCE {
+            continue;
+        }
+        let step = (clearance / 2.0).min(length) / length;
+        for side in [-1.0, 1.0].iter() {
+            let c = Coord {
+                x: mid.x - side * edge.dy() * step,
+                y: mid.y + side * edge.dx() * step,
+            };
+            sample(c, 2);
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{relate, Pattern};
+    use crate::geoq::entity::wkt_geom;
+
+    fn matrix(a: &str, b: &str) -> String {
+        relate(&wkt_geom(a), &wkt_geom(b)).to_string()
+    }
+
+    const SQUARE: &str = "POLYGON((0 0,2 0,2 2,0 2,0 0))";
+
+    #[test]
+    fn test_intersection_matrices() {
+        assert_eq!(
+            "212101212",
+            matrix(SQUARE, "POLYGON((1 1,3 1,3 3,1 3,1 1))")
+        );
+        assert_eq!(
+            "2FFF1FFF2",
+            matrix(SQUARE, "POLYGON((2 2,0 2,0 0,2 0,2 2))")
+        );
+        assert_eq!(
+            "FF2F11212",
+            matrix(SQUARE, "POLYGON((2 0,4 0,4 2,2 2,2 0))")
+        );
+        assert_eq!(
+            "212FF1FF2",
+            matrix(SQUARE, "POLYGON((0.5 0.5,1 0.5,1 1,0.5 0.5))")
+        );
+        assert_eq!("0FFFFF212", matrix("POINT(1 1)", SQUARE));
+        assert_eq!("F0FFFF212", matrix("POINT(2 1)", SQUARE));
+        assert_eq!("1010F0212", matrix("LINESTRING(1 1,3 1)", SQUARE));
+        assert_eq!(
+            "0F1FF0102",
+            matrix("LINESTRING(0 0,2 2)", "LINESTRING(0 2,2 0)")
+        );
+        assert_eq!("0FFFFFFF2", matrix("POINT(0 0)", "POINT(0 0)"));
+        assert_eq!("FF0FFF0F2", matrix("POINT(0 0)", "POINT(1 1)"));
+    }
+
+    #[test]
+    fn test_named_predicates() {
+        let rel = |a: &str, b: &str| relate(&wkt_geom(a), &wkt_geom(b));
+        assert!(rel(SQUARE, "POLYGON((0 0,0 2,2 2,2 0,0 0))").is_equal());
+        assert!(rel(SQUARE, "POLYGON((2 0,4 0,4 2,2 2,2 0))").is_touches());
+        assert!(rel(SQUARE, "POLYGON((1 1,3 1,3 3,1 3,1 1))").is_overlaps());
+        assert!(rel("LINESTRING(1 1,3 1)", SQUARE).is_crosses());
+        assert!(rel("LINESTRING(0 0,2 2)", "LINESTRING(0 2,2 0)").is_crosses());
+        assert!(rel("LINESTRING(0 0,2 0)", SQUARE).is_touches());
+        assert!(rel("POINT(1 1)", SQUARE).is_within());
+        assert!(rel(SQUARE, "LINESTRING(0.5 0.5,1 1)").is_contains());
+        assert!(rel("POINT(5 5)", SQUARE).is_disjoint());
+        assert!(!rel("POINT(2 1)", SQUARE).is_within());
+    }
+
+    #[test]
+    fn test_patterns() {
+        let m = relate(&wkt_geom("POINT(1 1)"), &wkt_geom(SQUARE));
+        assert!("T*F**F***".parse::<Pattern>().unwrap().matches(&m));
+        assert!("0fffff2**".parse::<Pattern>().unwrap().matches(&m));
+        assert!(!"FF*FF****".parse::<Pattern>().unwrap().matches(&m));
+        assert!("T********"
+            .parse::<Pattern>()
+            .unwrap()
+            .requires_intersection());
+        assert!(!"FF*FF****"
+            .parse::<Pattern>()
+            .unwrap()
+            .requires_intersection());
+        assert!("T*F**F**".parse::<Pattern>().is_err());
+        assert!("T*F**F**X".parse::<Pattern>().is_err());
+    }
+}
@@ -17,6 +17,54 @@ $ echo '{"latitude":12.0,"longitude":34.0,"key":"val"}' | geoq json munge
 {"geometry":{"coordinates":[34.0,12.0],"type":"Point"}, "properties":{"key":"val"},"type":"Feature"}
 "#;
 
+pub const CSV_READ_ABOUT: &str = "Convert CSV or TSV rows with a header to GeoJSON Features";
+pub const CSV_READ_AFTER_HELP: &str = r#"
+Reads a header row followed by data rows, and outputs each row as a
+GeoJSON Feature.
+
+Unless --lat-col and --lon-col or --wkt-col are given, geometries are
+found using the same checks as `geoq json munge`, matching column
+names case-insensitively:
+
+* latitude and longitude under the lat, latitude, lon, longitude, or lng columns
+* WKT strings under the geometry or wkt columns
+* GeoJSON geometries as strings under the geometry or geojson columns
+
+The remaining columns become the Feature's properties. Numbers and
+booleans are given JSON types, and empty values are null.
+
+Fields may be quoted with '"' to include delimiters, quotes (as '""'),
+or line breaks.
+
+Example:
+
+$ printf 'name,lat,lon
+pizza,12,34
+' | geoq csv read
+{"geometry":{"coordinates":[34.0,12.0],"type":"Point"},"properties":{"name":"pizza"},"type":"Feature"}
+"#;
+pub const CSV_DELIMITER_ARG_HELP: &str = "Field delimiter: a single character, or 'tab'. \
+Defaults to tab if the header has tabs but no commas, otherwise comma.";
+
+pub const CSV_WRITE_ABOUT: &str = "Write features as CSV rows, with properties as columns";
+pub const CSV_WRITE_AFTER_HELP: &str = r#"
+Outputs a header row, then a row for each input with its geometry
+(as WKT, or as lat and lon columns with --geometry latlon) followed
+by a column for each property.
+
+Unless --columns is given, the property columns are the union of the
+property names in the first 1000 inputs (see --sample). Properties
+which only appear after the sample are left out. Missing and null
+properties are written as empty fields, and nested objects or
+arrays as JSON.
+
+Example:
+
+$ echo '{"type":"Feature","properties":{"name":"pizza"},"geometry":{"type":"Point","coordinates":[34,12]}}' | geoq csv write --geometry latlon
+lat,lon,name
+12,34,pizza
+"#;
+
 pub const READ_AFTER_HELP: &str = r#"
 Geoq reads the following geospatial text formats:
 
@@ -25,11 +73,11 @@ Geoq reads the following geospatial text formats:
 - GeoJSON
 - Geohashes (Base32-encoded)
 
-Values must be submitted **1 per line**. GeoJSON data
-in particular is often pretty-printed by other tools, so
-take care to ensure your GeoJSON is compacted before feeding
-it to Geoq. The 'jq' program is useful for compacting
-pretty-printed json: 'jq -cr .'
+Values must be submitted **1 per line**. The exception is
+GeoJSON, which is often pretty-printed by other tools: when
+a line begins with '{', geoq keeps reading until the JSON
+object is closed, so multi-line GeoJSON objects (including
+whole FeatureCollections) can be read directly.
 
 Geoq will detect the format of values as they are read,
 so additional arguments are not needed to specify formats.
@@ -42,7 +90,10 @@ type.
 Most entities will be read as 1-per-line. However special
 handling is given to GeoJSON 'FeatureCollections'. These
 will be "unrolled" by Geoq's processing, and treated as
-if their features had been passed individually.
+if their features had been passed individually. Features
+are read from the collection one at a time, so even very
+large collections can be processed without loading the
+whole collection into memory.
 
 You can also use this command ('geoq read') to test out
 input formats and see how geoq reads the values you feed it:
@@ -66,11 +117,49 @@ See 'geoq read --help' for more information on supported
 input formats.
 "#;
 
+pub const ON_ERROR_ARG_HELP: &str = r"How to handle invalid inputs:
+abort (default) - exit on the first invalid input
+skip - silently skip invalid inputs
+stderr - skip invalid inputs and report each one to STDERR
+reject-file=PATH - skip invalid inputs and write them to PATH
+
+When inputs are skipped, a count is reported to STDERR at the end.
+";
+
+pub const INPUT_FORMAT_ARG_HELP: &str = r"Format of inputs read from STDIN:
+auto (default) - detect the format of each input
+latlon - comma or tab-separated Lat,Lon pairs
+lonlat - comma or tab-separated Lon,Lat pairs
+geohash - base 32 geohashes
+h3 - H3 cells as hexadecimal strings
+wkt - Well-Known Text
+geojson - GeoJSON geometries, features, or feature collections
+
+Use a specific format when detection gets it wrong, e.g. for
+geohashes which look like H3 cells.
+
+Any columns after the coordinates of a latlon or lonlat row are
+kept as GeoJSON properties named col3, col4, etc.
+";
+
 pub const CENTROID_ABOUT: &str = "Print centroid of the given geometry";
 pub const CENTROID_AFTER_HELP: &str = r"
-Output is given as a GeoJSON Point.
+Output is given as a GeoJSON Point, or as a GeoJSON Feature keeping the
+input's properties with --geojson.
+
+The centroid of a curved or hollow shape, like a C-shaped polygon, can fall
+outside of it. Use --point-on-surface for a point guaranteed to fall on each
+geometry instead, e.g. for placing labels:
+
+geoq centroid --point-on-surface --geojson < neighborhoods.geojson
+
+Use --all to give a single centroid for every input together, weighted by
+their areas. Lines only count when there are no polygons, and points only
+when there are no lines or polygons.
 ";
 
+pub const CENTROID_GEODESIC_ARG_HELP: &str = "Find centroids on a sphere rather than a plane, by averaging 3D vectors. More accurate for geometries spanning large areas or the antimeridian.";
+
 pub const WHEREAMI_ABOUT: &str = "Get IP-based current lat/lon.";
 pub const WHEREAMI_AFTER_HELP: &str = r"
 Get IP-based current lat/lon.
@@ -88,12 +177,13 @@ pub const DISTANCE_ABOUT: &str =
 pub const DISTANCE_QUERY_ARG_HELP: &str = r"
 Feature to measure from.
 
-Query Feature must be a POINT, and can be provided as
+Query Feature can be any geometry, and can be provided as
 Lat/Lon, WKT, or GeoJSON.
 ";
 
 pub const DISTANCE_AFTER_HELP: &str = r"
 Gives the distance in meters between each input Feature and the given QUERY Feature.
+Use --units for other units, and --method to choose how distance is measured.
 
 Output is given in the format:
 
@@ -106,10 +196,15 @@ e.g.
 This enables the output to be processed with other unix commands
 like 'sort', 'cut', etc.
 
-Distances will be given between the QUERY point and the closest
-point on each input Feature.
+With --segment, the line joining the closest points (from the QUERY to the
+input) is included as WKT:
 
-Distance for Features that cover the QUERY point (like a Polygon containing the point) will be 0.
+<Distance><TAB><Segment><TAB><Feature>
+
+Distances will be given between the closest points of the QUERY and
+each input Feature.
+
+Distance for Features that intersect the QUERY (like a Polygon containing a point) will be 0.
 ";
 
 pub const FILTER_AFTER_HELP: &str = r"
@@ -133,14 +228,226 @@ Or, to check against inputs from the command line:
 geoq filter intersects 9q5
 
 geoq filter contains 'Polygon ((30 10, 10 30, 40 40, 30 10))'
+
+Predicates compare each query with each input, and work for any
+geometry types:
+
+intersects - the input and query meet at all
+contains - the input falls within the query
+within - the query falls within the input
+touches - they meet, but only at their boundaries
+crosses - their interiors meet, in fewer dimensions than one of them has
+overlaps - their interiors meet in the same dimension as both, but neither contains the other
+disjoint - they don't meet at all
+equals - they're topologically equal
+relate - their DE-9IM matrix matches a pattern (see 'geoq filter relate --help')
+dwithin - the input is within a distance of the query
+
+Or use 'geoq filter bbox' to check inputs against a bounding box, without
+any query entities.
+
+Queries are loaded into a spatial index (an R-tree), so each input is only
+checked against queries whose bounding boxes are nearby. Large query files,
+with many thousands of features, are fine.
 ";
 
+pub const FILTER_RELATE_AFTER_HELP: &str = r"
+Checks the DE-9IM intersection matrix of each QUERY entity with each input
+against a PATTERN. The matrix gives the dimension of the intersection
+between the interior, boundary and exterior of the query (rows) and those
+of the input (columns), read across the rows:
+
+           input
+           I  B  E
+query  I  [0][1][2]
+       B  [3][4][5]
+       E  [6][7][8]
+
+Each of the 9 characters of the pattern is one of:
+T - the parts intersect
+F - the parts don't intersect
+* - anything
+0, 1, 2 - the parts intersect, with this dimension (point, line or area)
+
+For example, inputs which fall inside a query without touching its boundary:
+
+geoq filter relate 'T**FF*FF*' 'POLYGON((0 0,10 0,10 10,0 10,0 0))'
+
+The named predicates are all special cases of this:
+
+contains - T*****FF*
+within   - T*F**F***
+equals   - T*F**FFF*
+disjoint - FF*FF****
+touches  - FT******* or F**T***** or F***T****
+";
+
+pub const FILTER_BBOX_AFTER_HELP: &str = r"
+A quick way to slice inputs by area. The BBOX is given in the same
+format as 'geoq fgb read --bbox', i.e. minX,minY,maxX,maxY:
+
+geoq filter bbox -125,32,-114,42 < points.txt
+
+Only bounding boxes are compared, which is much cheaper than checking
+'geoq filter intersects' against the box as a polygon. By default inputs
+are kept when their bounding boxes overlap the BBOX, so some which come
+close to it without touching it may be included. With --strict, inputs are
+only kept when they fall entirely within the BBOX, which is exact.
+";
+
+pub const JOIN_ABOUT: &str = "Add properties from matching query features to each input.";
+pub const JOIN_AFTER_HELP: &str = r"
+Like 'geoq filter', but rather than only passing matching inputs through,
+each is output as a GeoJSON Feature with the properties of the query
+features it matches added to its own.
+
+Queries can be provided as either a file input using the --query-file
+argument, or as a positional argument on the command line, and are loaded
+into a spatial index.
+
+Inputs match query features which:
+intersects - intersect them
+contains - contain them (the same as 'geoq filter contains')
+within - fall within them
+dwithin - are within a --radius of them, using --method and --units as in 'geoq measure distance'
+
+Use --mode to choose how properties from matching query features are combined:
+first - properties of the first match, in the order queries were given (default)
+all - an array of values from every match for each property
+count - the number of matches, as a 'count' property
+
+Properties from query features replace input properties with the same name,
+so use --prefix to tell them apart, e.g. '--prefix zone_'.
+
+Inputs which match no query features are left out, unless --left is given.
+
+For example, to tag points with the zone they fall in:
+
+geoq join --query-file zones.geojson contains < points.geojson
+";
+pub const JOIN_MODE_ARG_HELP: &str =
+    "How to combine properties of matching query features: first, all or count (default first)";
+
 pub const FILTER_DWITHIN_AFTER_HELP: &str = r"
 Example:
 
 geoq filter dwithin --radius 100 9q5
 
-Note: Currently only Point geometries are supported as candidates.
+Distances are measured between the closest points of each feature and the
+QUERY, as in 'geoq measure distance', and accept the same --method and
+--units options, e.g.:
+
+geoq filter dwithin --radius 2 --units mi 9q5
+";
+
+pub const DISTANCE_METHOD_ARG_HELP: &str = r"How to measure distances (default vincenty):
+vincenty - Vincenty's formulae on the WGS84 ellipsoid. Falls back to the geodesic method for nearly antipodal points, where these don't converge
+haversine - great-circle distance on a sphere, which is faster but less accurate
+geodesic - Karney's geodesic algorithm on the WGS84 ellipsoid
+planar - straight-line distance in coordinate units, for projected data. Can't be combined with --units
+";
+
+pub const DISTANCE_SEGMENT_ARG_HELP: &str =
+    "Include the segment joining the closest points of the two features, as a WKT LineString.";
+
+pub const DISTANCE_UNITS_ARG_HELP: &str = "Units for distances (default m)";
+
+pub const BUFFER_ABOUT: &str = "Grow (or shrink) geometries by a distance in meters.";
+
+pub const BUFFER_AFTER_HELP: &str = r"Reads features from STDIN, and outputs their buffers as GeoJSON
+Features, keeping their properties.
+
+Distances are in meters, or in --units if given. Each geometry is buffered
+in an azimuthal equidistant projection centered on its bounding box, so
+buffers around points are exact geodesic circles, while those around long
+lines or large polygons become less accurate far from their centers.
+
+Negative distances shrink polygons, and inputs with nothing left
+afterward, including points and lines, are left out.
+
+For example, to find the H3 cells within 1km of a point:
+
+echo 'POINT(-73.98 40.75)' | geoq buffer 1 --units km | geoq h3 covering 9
+
+Use --segments to set how many straight segments are used to draw each
+quarter of a circle, --cap for the shape of the ends of lines, and --join
+for the shape of corners.
+";
+
+pub const BUFFER_CAP_ARG_HELP: &str = "Shape of the ends of buffered lines: round (default), flat, or square (extended past the end by the distance)";
+
+pub const BUFFER_JOIN_ARG_HELP: &str =
+    "Shape of the outside of buffered corners: round (default), mitre, or bevel";
+
+pub const CLIP_ABOUT: &str = "Cut geometries to a bounding box.";
+
+pub const CLIP_AFTER_HELP: &str = r"Reads features from STDIN, and outputs the parts of them within a
+bounding box as GeoJSON Features, keeping their properties. Inputs which
+fall entirely outside the box are left out.
+
+The box is given in the same format as 'geoq fgb read --bbox', i.e.
+minX,minY,maxX,maxY:
+
+geoq clip -125,32,-114,42 < roads.geojson
+
+Lines which leave and re-enter the box are split into MultiLineStrings,
+and polygons into MultiPolygons.
+";
+
+pub const HULL_ABOUT: &str = "Wrap geometries in convex or concave hulls.";
+
+pub const HULL_AFTER_HELP: &str = r"Reads features from STDIN, and outputs the hull around each as a
+GeoJSON Feature, keeping its properties. Inputs whose points all fall on
+one spot or along one line give a Point or LineString instead.
+
+By default hulls are convex. Use --concave for a tighter shape which
+follows the inputs' outlines, e.g. around a cloud of points:
+
+geoq hull --all --concave 2 < stores.txt
+
+Use --all to give a single hull around every input, or --group-by to give
+one per value of a GeoJSON property, with that property set. Groups are
+output in the order they first appear.
+";
+
+pub const HULL_CONCAVE_ARG_HELP: &str = "Give concave hulls: edges are dug into while they're longer than RATIO times the distance to the nearest point inside, so lower values give tighter shapes. Around 2.0 is a good start.";
+
+pub const OVERLAY_ABOUT: &str = "Clip geometries against query features with boolean operations.";
+
+pub const OVERLAY_AFTER_HELP: &str = r"Reads features from STDIN, and outputs the result of combining each
+with the query features as GeoJSON Features, keeping their properties.
+
+Query features can be given either as a positional argument or with
+--query-file. Their polygons are merged together, so overlapping query
+features act as a single area:
+
+* intersection - keep the parts of each input inside the query features
+* difference - remove the parts of each input inside the query features
+* union - merge each input with all of the query features
+* xor - keep the parts inside either each input or the query features, but not both
+
+Points and lines in the inputs are kept where they fall inside the query
+features for an intersection, and where they fall outside them otherwise.
+Inputs with nothing left afterward are left out.
+
+For example, to cut a park out of a neighborhood:
+
+echo 'POLYGON((0 0, 4 0, 4 4, 0 4, 0 0))' | geoq overlay difference 'POLYGON((1 1, 2 1, 2 2, 1 2, 1 1))'
+";
+
+pub const DISSOLVE_ABOUT: &str = "Merge all input polygons into a single geometry.";
+
+pub const DISSOLVE_AFTER_HELP: &str = r"Reads features from STDIN, and outputs the union of all their polygons
+as a GeoJSON Feature. Points and lines are left out.
+
+Use --group-by to merge inputs separately for each value of a GeoJSON
+property, giving one Feature per value with that property set. Groups are
+output in the order they first appear, and inputs without the property are
+merged into a group of their own.
+
+For example, to merge counties into states:
+
+cat counties.geojson | geoq dissolve --group-by state
 ";
 
 pub const SIMPLIFY_ABOUT: &str = "Simplify geometries.";
@@ -176,6 +483,150 @@ pub const MEASURE_COORDS_ABOUT: &str =
 pub const MEASURE_COORDS_GEOJSON_ARG_HELP: &str =
     "Give result as an embedded property in a GeoJSON feature, rather than as a single number.";
 
+pub const MEASURE_AREA_ABOUT: &str = "Measure the geodesic area of polygons.";
+pub const MEASURE_AREA_AFTER_HELP: &str = r"
+Area is measured on the WGS84 ellipsoid using Karney's geodesic algorithms,
+with holes subtracted. Points and lines have an area of 0.
+
+Results are in square meters by default. Use --units to pick another unit:
+km, mi, nm and ft give square kilometers, miles, nautical miles and feet,
+while ha and acres give hectares and acres.
+
+With --geojson, the result is added to each feature as the property 'area'.
+";
+
+pub const MEASURE_LENGTH_ABOUT: &str =
+    "Measure the geodesic length of lines, or perimeter of polygons.";
+pub const MEASURE_LENGTH_AFTER_HELP: &str = r"
+Length is measured along geodesics on the WGS84 ellipsoid, using Karney's
+algorithms. For polygons this is the perimeter, including any holes.
+Points have a length of 0.
+
+Results are in meters by default. Use --units for km, mi, nm or ft.
+
+With --geojson, the result is added to each feature as the property 'length'.
+";
+
+pub const MEASURE_UNITS_ARG_HELP: &str = "Units for the result (default m)";
+
+pub const MEASURE_ELEVATION_ABOUT: &str =
+    "Measure elevation gain, loss, and 3D length of LineStrings with Z values.";
+pub const MEASURE_ELEVATION_GEOJSON_ARG_HELP: &str =
+    "Give results as embedded properties in a GeoJSON feature, rather than as tab-separated numbers.";
+pub const MEASURE_ELEVATION_AFTER_HELP: &str = r"
+Reads LineStrings or MultiLineStrings with Z (elevation) values, in meters,
+from WKT (e.g. 'LINESTRING Z (...)') or GeoJSON (3-element positions).
+
+Output is given in the format:
+
+<Gain><TAB><Loss><TAB><3D Length>
+
+Gain and loss are the total climb and descent along the line. 3D length
+combines the great-circle distance between each pair of vertices with the
+change in elevation between them.
+
+With --geojson, these are added to each feature as the properties
+elevation_gain, elevation_loss, and length_3d.
+
+Inputs without Z values, or which aren't (Multi-)LineStrings, are errors.
+";
+
+pub const VALIDATE_ABOUT: &str = "Check geometries for validity issues, or repair them with --fix.";
+pub const VALIDATE_FIX_ARG_HELP: &str =
+    "Output repaired geometries as GeoJSON Features, rather than reporting issues.";
+pub const VALIDATE_AFTER_HELP: &str = r"
+Reads features from STDIN and checks each one for:
+
+* Non-finite coordinates (NaN or infinity)
+* Duplicate consecutive points
+* Lines or rings with too few points
+* Unclosed polygon rings
+* Self-intersections, including holes crossing their shell
+* Wrong ring orientation (GeoJSON expects counter-clockwise exterior rings and clockwise holes)
+* Holes outside their shell
+
+Output is given in the format:
+
+<valid|invalid><TAB><Issues><TAB><Feature>
+
+e.g.
+
+invalid	Self-intersection at POINT(1 1)	POLYGON((0 0,2 2,2 0,0 2,0 0))
+
+Where multiple issues are found they are separated by '; '.
+
+With --fix, each feature is instead output as a GeoJSON Feature with its
+geometry repaired: non-finite coordinates and duplicate points are removed,
+rings are closed and oriented, holes outside their shell are dropped, and
+self-intersecting rings are split where they cross (so a Polygon may become
+a MultiPolygon). Holes which cross their shell are left as they are.
+";
+
+pub const WINDING_ARG_HELP: &str = r"Rewind polygon rings:
+ccw - counter-clockwise exterior rings and clockwise holes (RFC 7946)
+cw - clockwise exterior rings and counter-clockwise holes
+";
+
+pub const ORIENT_ABOUT: &str = "Rewind polygon rings to a consistent orientation.";
+pub const ORIENT_AFTER_HELP: &str = r"
+Reads features from STDIN and outputs them as GeoJSON Features, with the
+rings of any Polygons and MultiPolygons rewound.
+
+By default rings follow the right-hand rule required by GeoJSON (RFC 7946):
+exterior rings are counter-clockwise and holes are clockwise. Use
+'--winding cw' for the opposite convention, as used by shapefiles.
+
+Properties, and any Z or M values, are kept as they are.
+
+The 'gj' and 'shp' commands also accept --winding to rewind their output.
+";
+
+pub const NEAREST_ABOUT: &str = "Find the nearest QUERY features to each input feature.";
+pub const NEAREST_AFTER_HELP: &str = r"
+Reads features from STDIN and, for each one, finds the closest of a set of
+QUERY features, given either as a positional argument or with --query-file
+(or its alias --file).
+Query features are loaded into a spatial index, so large query sets are fine.
+
+Distances are between the closest points of each pair of features, as in
+'geoq measure distance', and accept the same --method and --units options.
+
+Output has one line for each of the --count nearest query features, nearest
+first, in the format:
+
+<Distance><TAB><Query Feature><TAB><Feature>
+
+e.g. the 3 nearest warehouses to each store:
+
+geoq nearest --query-file warehouses.geojson --count 3 < stores.geojson
+
+With --geojson, each input is instead output once, as a GeoJSON Feature with
+a 'nearest' property listing the properties of its nearest query features,
+each with an added 'distance'.
+";
+pub const NEAREST_COUNT_ARG_HELP: &str = "Number of nearest query features to give (default 1)";
+pub const NEAREST_GEOJSON_ARG_HELP: &str =
+    "Give results as a 'nearest' property of each input GeoJSON feature, rather than as tab-separated lines.";
+
+pub const SPLIT_ANTIMERIDIAN_ABOUT: &str = "Cut geometries which cross the antimeridian.";
+pub const SPLIT_ANTIMERIDIAN_AFTER_HELP: &str = r"
+Reads features from STDIN and outputs them as GeoJSON Features, with any
+LineStrings or Polygons which cross the antimeridian (180th meridian) cut
+into pieces on either side of it, as recommended by RFC 7946 section 3.1.9.
+
+A segment is taken to cross the antimeridian when its longitudes differ by
+more than 180 degrees, e.g. LINESTRING(170 0,-170 10) is split into
+MULTILINESTRING((170 0,180 5),(-180 5,-170 10)).
+
+Geometries which don't cross are output as they are. Polygons surrounding
+a pole are left as they are.
+
+'geoq gh covering', 'geoq h3 covering' and 'geoq filter' split their
+geometries in the same way before using them.
+";
+
+pub const BBOX_ANTIMERIDIAN_ARG_HELP: &str = "Allow bboxes to cross the antimeridian, in which case the west edge is greater than the east edge (RFC 7946 section 5.2). Without --embed, these are output as a MultiPolygon with a part on either side of the antimeridian.";
+
 pub const BBOX_AFTER_HELP: &str = "Generate bbox for a geometry";
 
 pub const H3_POLYFILL_HELP: &str = r"
@@ -0,0 +1,484 @@
+use geo::algorithm::area::Area;
+use geo::algorithm::coordinate_position::{CoordPos, CoordinatePosition};
+use geo::algorithm::line_intersection::{line_intersection, LineIntersection};
+use geo::algorithm::orient::{Direction, Orient};
+use geo::algorithm::winding_order::Winding;
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPolygon, Polygon,
+};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Problem {
+    NonFiniteCoordinate,
+    DuplicatePoint,
+    TooFewPoints,
+    UnclosedRing,
+    SelfIntersection,
+    WrongOrientation,
+    HoleOutsideShell,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::NonFiniteCoordinate => write!(f, "Non-finite coordinate"),
+            Problem::DuplicatePoint => write!(f, "Duplicate consecutive point"),
+            Problem::TooFewPoints => write!(f, "Too few points"),
+            Problem::UnclosedRing => write!(f, "Unclosed ring"),
+            Problem::SelfIntersection => write!(f, "Self-intersection"),
+            Problem::WrongOrientation => write!(f, "Wrong ring orientation"),
+            Problem::HoleOutsideShell => write!(f, "Hole outside shell"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub problem: Problem,
+    pub location: Option<Coord<f64>>,
+}
+
+impl Issue {
+    fn new(problem: Problem, location: Option<Coord<f64>>) -> Issue {
+        Issue { problem, location }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some(c) => write!(f, "{} at POINT({} {})", self.problem, c.x, c.y),
+            None => write!(f, "{}", self.problem),
+        }
+    }
+}
+
+// Validity issues for a geometry, following the OGC Simple Features rules,
+// plus the GeoJSON (RFC 7946) ring orientation: exterior rings
+// counter-clockwise and holes clockwise.
+//
+// Rings are always closed once they're read into a geometry, so unclosed
+// rings have to be found from the input itself (see unclosed_rings).
+pub fn issues(geom: &Geometry<f64>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    check_geometry(geom, &mut issues);
+    issues
+}
+
+fn check_geometry(geom: &Geometry<f64>, issues: &mut Vec<Issue>) {
+    match geom {
+        Geometry::Point(p) => check_coords(&[p.0], issues),
+        Geometry::Line(l) => check_line_string(&LineString(vec![l.start, l.end]), issues),
+        Geometry::LineString(ls) => check_line_string(ls, issues),
+        Geometry::Polygon(poly) => check_polygon(poly, issues),
+        Geometry::MultiPoint(mp) => mp.0.iter().for_each(|p| check_coords(&[p.0], issues)),
+        Geometry::MultiLineString(mls) => mls.0.iter().for_each(|ls| check_line_string(ls, issues)),
+        Geometry::MultiPolygon(mp) => mp.0.iter().for_each(|poly| check_polygon(poly, issues)),
+        Geometry::GeometryCollection(gc) => gc.0.iter().for_each(|g| check_geometry(g, issues)),
+        Geometry::Rect(r) => check_polygon(&r.to_polygon(), issues),
+        Geometry::Triangle(t) => check_polygon(&t.to_polygon(), issues),
+    }
+}
+
+fn finite(c: &Coord<f64>) -> bool {
+    c.x.is_finite() && c.y.is_finite()
+}
+
+// Coordinates with non-finite values and consecutive duplicates removed
+fn cleaned(coords: &[Coord<f64>]) -> Vec<Coord<f64>> {
+    let mut out: Vec<Coord<f64>> = Vec::with_capacity(coords.len());
+    for c in coords.iter().filter(|c| finite(c)) {
+        if out.last() != Some(c) {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+fn check_coords(coords: &[Coord<f64>], issues: &mut Vec<Issue>) {
+    for c in coords.iter().filter(|c| !finite(c)) {
+        issues.push(Issue::new(Problem::NonFiniteCoordinate, Some(*c)));
+    }
+}
+
+fn check_duplicates(coords: &[Coord<f64>], issues: &mut Vec<Issue>) {
+    for pair in coords.windows(2) {
+        if pair[0] == pair[1] {
+            issues.push(Issue::new(Problem::DuplicatePoint, Some(pair[1])));
+        }
+    }
+}
+
+fn check_line_string(ls: &LineString<f64>, issues: &mut Vec<Issue>) {
+    check_coords(&ls.0, issues);
+    check_duplicates(&ls.0, issues);
+    if cleaned(&ls.0).len() < 2 {
+        issues.push(Issue::new(Problem::TooFewPoints, ls.0.first().cloned()));
+    }
+}
+
+// Checks a ring on its own, giving the cleaned ring if it's
+// long enough to check against the other rings of its polygon
+fn check_ring(
+    ring: &LineString<f64>,
+    exterior: bool,
+    issues: &mut Vec<Issue>,
+) -> Option<LineString<f64>> {
+    check_coords(&ring.0, issues);
+    check_duplicates(&ring.0, issues);
+    let clean = LineString(cleaned(&ring.0));
+    if clean.0.len() < 4 {
+        issues.push(Issue::new(Problem::TooFewPoints, ring.0.first().cloned()));
+        return None;
+    }
+
+    for (_, _, points) in ring_nodes(&clean.0[0..clean.0.len() - 1]) {
+        for p in points {
+            issues.push(Issue::new(Problem::SelfIntersection, Some(p)));
+        }
+    }
+
+    let wrong_way = if exterior {
+        clean.is_cw()
+    } else {
+        clean.is_ccw()
+    };
+    if wrong_way {
+        issues.push(Issue::new(Problem::WrongOrientation, Some(clean.0[0])));
+    }
+    Some(clean)
+}
+
+fn check_polygon(poly: &Polygon<f64>, issues: &mut Vec<Issue>) {
+    let shell = check_ring(poly.exterior(), true, issues);
+    let holes: Vec<LineString<f64>> = poly
+        .interiors()
+        .iter()
+        .filter_map(|ring| check_ring(ring, false, issues))
+        .collect();
+
+    let rings: Vec<&LineString<f64>> = shell.iter().chain(holes.iter()).collect();
+    for (i, a) in rings.iter().enumerate() {
+        for b in rings.iter().skip(i + 1) {
+            for p in crossings(a, b) {
+                issues.push(Issue::new(Problem::SelfIntersection, Some(p)));
+            }
+        }
+    }
+
+    if let Some(shell) = shell {
+        let outer = Polygon::new(shell, vec![]);
+        for hole in holes.iter().filter(|h| outside(&outer, h)) {
+            issues.push(Issue::new(Problem::HoleOutsideShell, Some(hole.0[0])));
+        }
+    }
+}
+
+// Points where two rings cross or overlap. Rings of a
+// valid polygon can touch at single points, but not cross.
+fn crossings(a: &LineString<f64>, b: &LineString<f64>) -> Vec<Coord<f64>> {
+    let mut points = Vec::new();
+    for la in a.lines() {
+        for lb in b.lines() {
+            match line_intersection(la, lb) {
+                Some(LineIntersection::SinglePoint {
+                    intersection,
+                    is_proper: true,
+                }) => points.push(intersection),
+                Some(LineIntersection::Collinear { intersection }) => {
+                    points.push(intersection.start)
+                }
+                _ => (),
+            }
+        }
+    }
+    points
+}
+
+// Whether a ring lies outside of a polygon, i.e. none of its points are in the polygon's interior
+fn outside(poly: &Polygon<f64>, ring: &LineString<f64>) -> bool {
+    ring.0
+        .iter()
+        .all(|c| poly.coordinate_position(c) != CoordPos::Inside)
+}
+
+// Intersections between the segments of a ring, given as its open list of
+// coordinates (without the closing coordinate). Gives the indices of each
+// pair of segments and the points where they meet: anywhere for segments
+// which aren't next to each other, and only where they overlap for those
+// which are, e.g. when the ring doubles back on itself.
+fn ring_nodes(coords: &[Coord<f64>]) -> Vec<(usize, usize, Vec<Coord<f64>>)> {
+    let n = coords.len();
+    let segment = |i: usize| Line::new(coords[i], coords[(i + 1) % n]);
+    let mut nodes = Vec::new();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+            let points = match line_intersection(segment(i), segment(j)) {
+                Some(LineIntersection::SinglePoint { intersection, .. }) if !adjacent => {
+                    vec![intersection]
+                }
+                Some(LineIntersection::Collinear { intersection }) => {
+                    let shared = if j == i + 1 { coords[j] } else { coords[i] };
+                    vec![intersection.start, intersection.end]
+                        .into_iter()
+                        .filter(|c| !adjacent || *c != shared)
+                        .collect()
+                }
+                _ => vec![],
+            };
+            if !points.is_empty() {
+                nodes.push((i, j, points));
+            }
+        }
+    }
+    nodes
+}
+
+// Repair a geometry: non-finite coordinates and duplicate points are
+// removed, self-intersecting rings are split into separate rings where
+// they cross, holes outside of their shell are dropped, and rings are
+// oriented counter-clockwise (exterior) or clockwise (holes).
+//
+// A Polygon which is split into several is given as a MultiPolygon.
+// Holes which cross their shell are kept as they are.
+pub fn fix(geom: &Geometry<f64>) -> Geometry<f64> {
+    match geom {
+        Geometry::LineString(ls) => Geometry::LineString(LineString(cleaned(&ls.0))),
+        Geometry::MultiLineString(mls) => Geometry::MultiLineString(MultiLineString(
+            mls.0.iter().map(|ls| LineString(cleaned(&ls.0))).collect(),
+        )),
+        Geometry::Polygon(poly) => {
+            let mut polys = fix_polygon(poly);
+            if polys.len() == 1 {
+                Geometry::Polygon(polys.remove(0))
+            } else {
+                Geometry::MultiPolygon(MultiPolygon(polys))
+            }
+        }
+        Geometry::MultiPolygon(mp) => {
+            Geometry::MultiPolygon(MultiPolygon(mp.0.iter().flat_map(fix_polygon).collect()))
+        }
+        Geometry::GeometryCollection(gc) => {
+            Geometry::GeometryCollection(GeometryCollection(gc.0.iter().map(fix).collect()))
+        }
+        _ => geom.clone(),
+    }
+}
+
+fn fix_polygon(poly: &Polygon<f64>) -> Vec<Polygon<f64>> {
+    let holes: Vec<LineString<f64>> = poly.interiors().iter().flat_map(simple_rings).collect();
+    simple_rings(poly.exterior())
+        .into_iter()
+        .map(|shell| {
+            let outer = Polygon::new(shell.clone(), vec![]);
+            let inner = holes
+                .iter()
+                .filter(|h| !outside(&outer, h))
+                .cloned()
+                .collect();
+            Polygon::new(shell, inner).orient(Direction::Default)
+        })
+        .collect()
+}
+
+// Split a ring into rings which don't intersect themselves, by adding
+// a vertex everywhere it crosses itself and then separating each loop
+// which starts and ends at the same vertex. Loops with no area are dropped.
+fn simple_rings(ring: &LineString<f64>) -> Vec<LineString<f64>> {
+    let mut coords = cleaned(&ring.0);
+    if coords.len() > 1 && coords.first() == coords.last() {
+        coords.pop();
+    }
+    if coords.len() < 3 {
+        return vec![];
+    }
+
+    let mut extra: Vec<Vec<Coord<f64>>> = vec![Vec::new(); coords.len()];
+    for (i, j, points) in ring_nodes(&coords) {
+        extra[i].extend(points.iter().cloned());
+        extra[j].extend(points.iter().cloned());
+    }
+
+    let n = coords.len();
+    let mut noded = Vec::new();
+    for (i, mut points) in extra.into_iter().enumerate() {
+        let start = coords[i];
+        let end = coords[(i + 1) % n];
+        let dist = |c: &Coord<f64>| (c.x - start.x).powi(2) + (c.y - start.y).powi(2);
+        points.retain(|c| *c != start && *c != end);
+        points.sort_by(|a, b| dist(a).partial_cmp(&dist(b)).unwrap());
+        points.dedup();
+        noded.push(start);
+        noded.extend(points);
+    }
+
+    split_loops(&noded)
+        .into_iter()
+        .filter(|r| Polygon::new(r.clone(), vec![]).unsigned_area() > 0.0)
+        .collect()
+}
+
+fn split_loops(coords: &[Coord<f64>]) -> Vec<LineString<f64>> {
+    let mut loops = Vec::new();
+    let mut path: Vec<Coord<f64>> = Vec::new();
+    for c in coords.iter().chain(coords.first()) {
+        match path.iter().position(|p| p == c) {
+            Some(i) => {
+                let mut ring = path.split_off(i);
+                ring.push(*c);
+                path.push(*c);
+                if ring.len() >= 4 {
+                    loops.push(LineString(ring));
+                }
+            }
+            None => path.push(*c),
+        }
+    }
+    loops
+}
+
+fn unclosed(rings: Vec<Vec<Coord<f64>>>) -> Vec<Issue> {
+    rings
+        .into_iter()
+        .filter(|r| r.len() > 1 && r.first() != r.last())
+        .map(|r| Issue::new(Problem::UnclosedRing, r.last().cloned()))
+        .collect()
+}
+
+fn position_coord(p: &[f64]) -> Coord<f64> {
+    Coord {
+        x: p.get(0).cloned().unwrap_or(f64::NAN),
+        y: p.get(1).cloned().unwrap_or(f64::NAN),
+    }
+}
+
+fn geojson_rings(value: &geojson::Value) -> Vec<Vec<Coord<f64>>> {
+    use geojson::Value;
+    let ring = |r: &Vec<Vec<f64>>| r.iter().map(|p| position_coord(p)).collect();
+    match value {
+        Value::Polygon(rings) => rings.iter().map(ring).collect(),
+        Value::MultiPolygon(polys) => polys.iter().flatten().map(ring).collect(),
+        Value::GeometryCollection(geoms) => {
+            geoms.iter().flat_map(|g| geojson_rings(&g.value)).collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn wkt_rings(geom: &wkt::Geometry<f64>) -> Vec<Vec<Coord<f64>>> {
+    let ring =
+        |r: &wkt::types::LineString<f64>| r.0.iter().map(|c| Coord { x: c.x, y: c.y }).collect();
+    match geom {
+        wkt::Geometry::Polygon(poly) => poly.0.iter().map(ring).collect(),
+        wkt::Geometry::MultiPolygon(mp) => {
+            mp.0.iter()
+                .flat_map(|poly| poly.0.iter().map(ring))
+                .collect()
+        }
+        wkt::Geometry::GeometryCollection(gc) => gc.0.iter().flat_map(wkt_rings).collect(),
+        _ => vec![],
+    }
+}
+
+// Unclosed rings in a GeoJSON geometry
+pub fn geojson_unclosed_rings(value: &geojson::Value) -> Vec<Issue> {
+    unclosed(geojson_rings(value))
+}
+
+// Unclosed rings in WKT text
+pub fn wkt_unclosed_rings(raw: &str) -> Vec<Issue> {
+    match wkt::Wkt::<f64>::from_str(raw) {
+        Ok(wkt) => unclosed(wkt.items.iter().flat_map(wkt_rings).collect()),
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fix, geojson_unclosed_rings, issues, wkt_unclosed_rings, Problem};
+    use crate::geoq::entity::wkt_geom;
+    use serde_json::json;
+
+    fn problems(wkt: &str) -> Vec<String> {
+        issues(&wkt_geom(wkt))
+            .iter()
+            .map(|i| i.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_geometries() {
+        assert!(problems("POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,1 2,2 2,2 1,1 1))").is_empty());
+        assert!(problems("LINESTRING(0 0,1 1,1 0,0 1)").is_empty());
+        assert!(problems("POINT(1 2)").is_empty());
+    }
+
+    #[test]
+    fn test_ring_issues() {
+        assert_eq!(
+            vec!["Self-intersection at POINT(1 1)"],
+            problems("POLYGON((0 0,2 2,2 0,0 2,0 0))")
+        );
+        assert_eq!(
+            vec!["Wrong ring orientation at POINT(0 0)"],
+            problems("POLYGON((0 0,0 4,4 4,4 0,0 0))")
+        );
+        assert_eq!(
+            vec!["Duplicate consecutive point at POINT(4 0)"],
+            problems("POLYGON((0 0,4 0,4 0,4 4,0 0))")
+        );
+        assert_eq!(
+            vec!["Hole outside shell at POINT(5 5)"],
+            problems("POLYGON((0 0,4 0,4 4,0 4,0 0),(5 5,5 6,6 6,6 5,5 5))")
+        );
+        assert_eq!(
+            vec![
+                "Self-intersection at POINT(4 3)",
+                "Self-intersection at POINT(4 1)"
+            ],
+            problems("POLYGON((0 0,4 0,4 4,0 4,0 0),(3 1,3 3,5 3,5 1,3 1))")
+        );
+        assert_eq!(
+            vec!["Too few points at POINT(0 0)"],
+            problems("POLYGON((0 0,1 1,0 0))")
+        );
+        let infinite = issues(&wkt_geom("LINESTRING(0 0,1e999 1)"));
+        assert_eq!(Problem::NonFiniteCoordinate, infinite[0].problem);
+    }
+
+    #[test]
+    fn test_unclosed_rings() {
+        let value: geojson::Value = serde_json::from_value::<geojson::Geometry>(
+            json!({"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1]]]}),
+        )
+        .unwrap()
+        .value;
+        assert_eq!(
+            "Unclosed ring at POINT(1 1)",
+            geojson_unclosed_rings(&value)[0].to_string()
+        );
+        assert_eq!(1, wkt_unclosed_rings("POLYGON((0 0,1 0,1 1))").len());
+        assert!(wkt_unclosed_rings("POLYGON((0 0,1 0,1 1,0 0))").is_empty());
+    }
+
+    #[test]
+    fn test_fixing() {
+        let fixed = fix(&wkt_geom("POLYGON((0 0,2 2,2 0,0 2,0 0))"));
+        assert_eq!(
+            wkt_geom("MULTIPOLYGON(((1 1,2 0,2 2,1 1)),((0 0,1 1,0 2,0 0)))"),
+            fixed
+        );
+        assert!(issues(&fixed).is_empty());
+
+        let fixed = fix(&wkt_geom(
+            "POLYGON((0 0,0 4,4 4,4 4,4 0,0 0),(5 5,5 6,6 6,6 5,5 5),(1 1,2 1,2 2,1 2,1 1))",
+        ));
+        assert_eq!(
+            wkt_geom("POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,1 2,2 2,2 1,1 1))"),
+            fixed
+        );
+    }
+}
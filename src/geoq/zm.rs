@@ -0,0 +1,511 @@
+use geo::algorithm::haversine_distance::HaversineDistance;
+use geo_types::{Coord, Geometry, LineString, Point, Polygon};
+
+// Z (elevation) and M (measure) values for the coordinates of a geometry.
+//
+// geo_types geometries are 2D only, so these are kept alongside them, one
+// entry per input vertex in the order they were read. Geometries are written
+// out by walking their coordinates in the same order and matching each to
+// the next stored vertex with the same X/Y position. Matching this way,
+// rather than purely by index, means they still line up after operations
+// which keep a subset of the original vertices, like simplification, while
+// vertices which repeat an X/Y position keep their own values.
+//
+// Coordinates which don't come from the input (e.g. new vertices added
+// by clipping) are given 0 for Z and M.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ordinates {
+    pub has_z: bool,
+    pub has_m: bool,
+    values: Vec<(Coord<f64>, f64, f64)>,
+}
+
+// Walks the stored vertices alongside the coordinates of a geometry
+struct Vertices<'a> {
+    ords: &'a Ordinates,
+    next: usize,
+}
+
+impl<'a> Vertices<'a> {
+    // Z and M for the next vertex at this position, searching on from the
+    // last match and wrapping around to the start
+    fn take(&mut self, c: Coord<f64>) -> (f64, f64) {
+        let values = &self.ords.values;
+        let n = values.len();
+        for i in 0..n {
+            let j = (self.next + i) % n;
+            let (xy, z, m) = values[j];
+            if xy == c {
+                self.next = j + 1;
+                return (z, m);
+            }
+        }
+        (0.0, 0.0)
+    }
+}
+
+impl Ordinates {
+    fn insert(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) {
+        self.has_z |= z.is_some();
+        self.has_m |= m.is_some();
+        self.values
+            .push((Coord { x, y }, z.unwrap_or(0.0), m.unwrap_or(0.0)));
+    }
+
+    fn vertices(&self) -> Vertices<'_> {
+        Vertices {
+            ords: self,
+            next: 0,
+        }
+    }
+
+    // Z for the first vertex at this position
+    pub fn z(&self, c: Coord<f64>) -> f64 {
+        self.vertices().take(c).0
+    }
+
+    // M for the first vertex at this position
+    pub fn m(&self, c: Coord<f64>) -> f64 {
+        self.vertices().take(c).1
+    }
+
+    // Ordinates from GeoJSON positions with 3 (x, y, z) or
+    // 4 (x, y, z, m) elements. None if all positions are 2D.
+    pub fn from_geojson(value: &geojson::Value) -> Option<Ordinates> {
+        let mut ords = Ordinates::default();
+        ords.add_geojson(value);
+        if ords.has_z {
+            Some(ords)
+        } else {
+            None
+        }
+    }
+
+    fn add_position(&mut self, p: &[f64]) {
+        if p.len() >= 2 {
+            self.insert(p[0], p[1], p.get(2).cloned(), p.get(3).cloned());
+        }
+    }
+
+    fn add_geojson(&mut self, value: &geojson::Value) {
+        use geojson::Value;
+        match value {
+            Value::Point(p) => self.add_position(p),
+            Value::MultiPoint(ps) | Value::LineString(ps) => {
+                ps.iter().for_each(|p| self.add_position(p))
+            }
+            Value::MultiLineString(lines) | Value::Polygon(lines) => {
+                lines.iter().flatten().for_each(|p| self.add_position(p))
+            }
+            Value::MultiPolygon(polys) => polys
+                .iter()
+                .flatten()
+                .flatten()
+                .for_each(|p| self.add_position(p)),
+            Value::GeometryCollection(geoms) => {
+                geoms.iter().for_each(|g| self.add_geojson(&g.value))
+            }
+        }
+    }
+
+    // Split WKT with Z and/or M values (e.g. 'POINT Z (1 2 3)' or
+    // 'LINESTRING (1 2 3, 4 5 6)') into plain 2D WKT, which can be
+    // parsed as usual, and the extra ordinates. None if the text has
+    // no Z or M values, or can't be read.
+    pub fn split_wkt(raw: &str) -> Option<(String, Ordinates)> {
+        let mut ords = Ordinates::default();
+        let mut out = String::with_capacity(raw.len());
+        let mut tuple: Vec<&str> = Vec::new();
+        let mut dims: Option<String> = None;
+        let mut chars = raw.char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            if c.is_ascii_alphabetic() {
+                let mut end = i + 1;
+                while let Some((j, c)) = chars.peek() {
+                    if c.is_ascii_alphabetic() {
+                        end = j + 1;
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let word = raw[i..end].to_ascii_uppercase();
+                let (word, tag) = split_dimension_tag(&word);
+                if !word.is_empty() {
+                    if word != "EMPTY" {
+                        dims = None;
+                    }
+                    if out.ends_with(|c: char| c.is_ascii_alphabetic()) {
+                        out.push(' ');
+                    }
+                    out.push_str(word);
+                }
+                if let Some(tag) = tag {
+                    dims = Some(tag.to_string());
+                }
+            } else if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' {
+                let mut end = i + 1;
+                while let Some((j, c)) = chars.peek() {
+                    if c.is_ascii_digit() || "+-.eE".contains(*c) {
+                        end = j + 1;
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tuple.push(&raw[i..end]);
+            } else if c == ',' || c == ')' {
+                if !tuple.is_empty() {
+                    ords.add_wkt_tuple(&tuple, dims.as_deref())?;
+                    out.push_str(&tuple[0..2].join(" "));
+                    tuple.clear();
+                }
+                out.push(c);
+            } else if c == '(' {
+                out.push(c);
+            } else if !c.is_whitespace() {
+                return None;
+            }
+        }
+
+        if ords.has_z || ords.has_m {
+            Some((out, ords))
+        } else {
+            None
+        }
+    }
+
+    fn add_wkt_tuple(&mut self, tuple: &[&str], dims: Option<&str>) -> Option<()> {
+        let nums: Vec<f64> = tuple
+            .iter()
+            .map(|n| n.parse::<f64>().ok())
+            .collect::<Option<Vec<f64>>>()?;
+        let (z, m) = match (dims, nums.len()) {
+            (_, 2) => (None, None),
+            (Some("M"), 3) => (None, Some(nums[2])),
+            (Some("Z"), 3) | (None, 3) => (Some(nums[2]), None),
+            (Some("ZM"), 4) | (None, 4) => (Some(nums[2]), Some(nums[3])),
+            _ => return None,
+        };
+        self.insert(nums[0], nums[1], z, m);
+        Some(())
+    }
+
+    fn tag(&self) -> &'static str {
+        match (self.has_z, self.has_m) {
+            (true, true) => " ZM",
+            (true, false) => " Z",
+            (false, true) => " M",
+            (false, false) => "",
+        }
+    }
+
+    // WKT for the geometry, with the Z and M values for each coordinate
+    pub fn wkt(&self, geom: &Geometry<f64>) -> String {
+        self.vertices().wkt(geom)
+    }
+
+    // GeoJSON geometry value for the geometry, with the Z and M values for each position
+    pub fn geojson_value(&self, geom: &Geometry<f64>) -> geojson::Value {
+        self.vertices().geojson_value(geom)
+    }
+}
+
+impl<'a> Vertices<'a> {
+    fn coord_wkt(&mut self, c: &Coord<f64>) -> String {
+        let (z, m) = self.take(*c);
+        let mut s = format!("{} {}", c.x, c.y);
+        if self.ords.has_z {
+            s.push_str(&format!(" {}", z));
+        }
+        if self.ords.has_m {
+            s.push_str(&format!(" {}", m));
+        }
+        s
+    }
+
+    fn coords_wkt(&mut self, coords: &[Coord<f64>]) -> String {
+        coords
+            .iter()
+            .map(|c| self.coord_wkt(c))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    fn polygon_wkt(&mut self, poly: &Polygon<f64>) -> String {
+        std::iter::once(poly.exterior())
+            .chain(poly.interiors().iter())
+            .map(|ring| format!("({})", self.coords_wkt(&ring.0)))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    fn wkt(&mut self, geom: &Geometry<f64>) -> String {
+        let tag = self.ords.tag();
+        match geom {
+            Geometry::Point(p) => format!("POINT{}({})", tag, self.coord_wkt(&p.0)),
+            Geometry::Line(l) => self.wkt(&Geometry::LineString(LineString(vec![l.start, l.end]))),
+            Geometry::LineString(ls) => format!("LINESTRING{}({})", tag, self.coords_wkt(&ls.0)),
+            Geometry::Polygon(poly) => format!("POLYGON{}({})", tag, self.polygon_wkt(poly)),
+            Geometry::MultiPoint(mp) => format!(
+                "MULTIPOINT{}({})",
+                tag,
+                mp.0.iter()
+                    .map(|p| format!("({})", self.coord_wkt(&p.0)))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Geometry::MultiLineString(mls) => format!(
+                "MULTILINESTRING{}({})",
+                tag,
+                mls.0
+                    .iter()
+                    .map(|ls| format!("({})", self.coords_wkt(&ls.0)))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Geometry::MultiPolygon(mp) => format!(
+                "MULTIPOLYGON{}({})",
+                tag,
+                mp.0.iter()
+                    .map(|poly| format!("({})", self.polygon_wkt(poly)))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Geometry::GeometryCollection(gc) => format!(
+                "GEOMETRYCOLLECTION({})",
+                gc.0.iter()
+                    .map(|g| self.wkt(g))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Geometry::Rect(r) => self.wkt(&Geometry::Polygon(r.to_polygon())),
+            Geometry::Triangle(t) => self.wkt(&Geometry::Polygon(t.to_polygon())),
+        }
+    }
+
+    // GeoJSON positions only have room for M after Z,
+    // so M values are dropped if there's no Z.
+    fn position(&mut self, c: &Coord<f64>) -> Vec<f64> {
+        let (z, m) = self.take(*c);
+        let mut p = vec![c.x, c.y];
+        if self.ords.has_z {
+            p.push(z);
+            if self.ords.has_m {
+                p.push(m);
+            }
+        }
+        p
+    }
+
+    fn positions(&mut self, ls: &LineString<f64>) -> Vec<Vec<f64>> {
+        ls.0.iter().map(|c| self.position(c)).collect()
+    }
+
+    fn rings(&mut self, poly: &Polygon<f64>) -> Vec<Vec<Vec<f64>>> {
+        std::iter::once(poly.exterior())
+            .chain(poly.interiors().iter())
+            .map(|ring| self.positions(ring))
+            .collect()
+    }
+
+    fn geojson_value(&mut self, geom: &Geometry<f64>) -> geojson::Value {
+        use geojson::Value;
+        match geom {
+            Geometry::Point(p) => Value::Point(self.position(&p.0)),
+            Geometry::Line(l) => {
+                Value::LineString(vec![self.position(&l.start), self.position(&l.end)])
+            }
+            Geometry::LineString(ls) => Value::LineString(self.positions(ls)),
+            Geometry::Polygon(poly) => Value::Polygon(self.rings(poly)),
+            Geometry::MultiPoint(mp) => {
+                Value::MultiPoint(mp.0.iter().map(|p| self.position(&p.0)).collect())
+            }
+            Geometry::MultiLineString(mls) => {
+                Value::MultiLineString(mls.0.iter().map(|ls| self.positions(ls)).collect())
+            }
+            Geometry::MultiPolygon(mp) => {
+                Value::MultiPolygon(mp.0.iter().map(|p| self.rings(p)).collect())
+            }
+            Geometry::GeometryCollection(gc) => Value::GeometryCollection(
+                gc.0.iter()
+                    .map(|g| geojson::Geometry::new(self.geojson_value(g)))
+                    .collect(),
+            ),
+            Geometry::Rect(r) => Value::Polygon(self.rings(&r.to_polygon())),
+            Geometry::Triangle(t) => Value::Polygon(self.rings(&t.to_polygon())),
+        }
+    }
+}
+
+// Elevation changes along a line, using Z values as meters
+#[derive(Debug, Default, PartialEq)]
+pub struct Profile {
+    pub gain: f64,
+    pub loss: f64,
+    // Length in meters, counting both the horizontal (great-circle)
+    // distance and the change in elevation for each segment
+    pub length_3d: f64,
+}
+
+impl Profile {
+    fn add_line(&mut self, ls: &LineString<f64>, vertices: &mut Vertices) {
+        let zs: Vec<f64> = ls.0.iter().map(|c| vertices.take(*c).0).collect();
+        for (line, z) in ls.lines().zip(zs.windows(2)) {
+            let dz = z[1] - z[0];
+            let horizontal = Point(line.start).haversine_distance(&Point(line.end));
+            if dz > 0.0 {
+                self.gain += dz;
+            } else {
+                self.loss -= dz;
+            }
+            self.length_3d += (horizontal * horizontal + dz * dz).sqrt();
+        }
+    }
+}
+
+// Elevation profile for a (Multi-)LineString. None for other geometries.
+pub fn profile(geom: &Geometry<f64>, ords: &Ordinates) -> Option<Profile> {
+    let mut profile = Profile::default();
+    let mut vertices = ords.vertices();
+    match geom {
+        Geometry::LineString(ls) => profile.add_line(ls, &mut vertices),
+        Geometry::MultiLineString(mls) => mls
+            .0
+            .iter()
+            .for_each(|ls| profile.add_line(ls, &mut vertices)),
+        _ => return None,
+    }
+    Some(profile)
+}
+
+// Separate a Z, M, or ZM tag from a WKT word, for both the
+// 'POINT Z' and 'POINTZ' spellings. Gives the remaining word
+// (possibly empty) and the tag.
+fn split_dimension_tag(word: &str) -> (&str, Option<&str>) {
+    for tag in &["ZM", "Z", "M"] {
+        if word.ends_with(tag) {
+            let rest = &word[0..word.len() - tag.len()];
+            if rest.is_empty() || is_geometry_type(rest) {
+                return (rest, Some(tag));
+            }
+        }
+    }
+    (word, None)
+}
+
+fn is_geometry_type(word: &str) -> bool {
+    matches!(
+        word,
+        "POINT"
+            | "LINESTRING"
+            | "LINEARRING"
+            | "POLYGON"
+            | "MULTIPOINT"
+            | "MULTILINESTRING"
+            | "MULTIPOLYGON"
+            | "GEOMETRYCOLLECTION"
+            | "TRIANGLE"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{profile, Ordinates};
+    use crate::geoq::entity::wkt_geom;
+    use geo_types::{Coord, Geometry, LineString, Point};
+    use serde_json::json;
+
+    #[test]
+    fn test_splitting_wkt() {
+        let (wkt, ords) = Ordinates::split_wkt("POINT Z (1 2 3)").unwrap();
+        assert_eq!("POINT(1 2)", wkt);
+        assert!(ords.has_z && !ords.has_m);
+        assert_eq!(3.0, ords.z(Coord { x: 1.0, y: 2.0 }));
+
+        let (wkt, ords) = Ordinates::split_wkt("LineString(1 2 3 4, 5.5 6 -7 8)").unwrap();
+        assert_eq!("LINESTRING(1 2,5.5 6)", wkt);
+        assert!(ords.has_z && ords.has_m);
+        assert_eq!(-7.0, ords.z(Coord { x: 5.5, y: 6.0 }));
+        assert_eq!(8.0, ords.m(Coord { x: 5.5, y: 6.0 }));
+
+        let (wkt, ords) = Ordinates::split_wkt("POINTM (1 2 3)").unwrap();
+        assert_eq!("POINT(1 2)", wkt);
+        assert!(!ords.has_z && ords.has_m);
+
+        let (wkt, _) = Ordinates::split_wkt(
+            "GEOMETRYCOLLECTION (POINT Z (1 2 3), POLYGON Z ((0 0 1, 1 0 1, 1 1 1, 0 0 1)))",
+        )
+        .unwrap();
+        assert_eq!(
+            "GEOMETRYCOLLECTION(POINT(1 2),POLYGON((0 0,1 0,1 1,0 0)))",
+            wkt
+        );
+
+        assert!(Ordinates::split_wkt("POINT (1 2)").is_none());
+        assert!(Ordinates::split_wkt("POINT Z (1 2 3 4 5)").is_none());
+        assert!(Ordinates::split_wkt("pizza").is_none());
+    }
+
+    #[test]
+    fn test_writing_wkt() {
+        let (_, ords) = Ordinates::split_wkt("LINESTRING Z (1 2 3, 4 5 6, 7 8 9)").unwrap();
+        // e.g. after simplification removes a vertex
+        let geom = Geometry::LineString(LineString::from(vec![(1.0, 2.0), (7.0, 8.0)]));
+        assert_eq!("LINESTRING Z(1 2 3,7 8 9)", ords.wkt(&geom));
+    }
+
+    #[test]
+    fn test_profile() {
+        let (wkt, ords) =
+            Ordinates::split_wkt("LINESTRING Z (0 0 100, 0 0.001 130, 0 0.002 110)").unwrap();
+        let geom = wkt_geom(&wkt);
+        let p = profile(&geom, &ords).unwrap();
+        assert_eq!(30.0, p.gain);
+        assert_eq!(20.0, p.loss);
+        // 2 segments of ~111.2m horizontally
+        assert_eq!(228.0, p.length_3d.round());
+
+        assert!(profile(&Geometry::Point(Point::new(0.0, 0.0)), &ords).is_none());
+    }
+
+    #[test]
+    fn test_repeated_positions() {
+        let (wkt, ords) = Ordinates::split_wkt("LINESTRING Z (0 0 0, 0.001 0 10, 0 0 20)").unwrap();
+        let geom = wkt_geom(&wkt);
+        assert_eq!("LINESTRING Z(0 0 0,0.001 0 10,0 0 20)", ords.wkt(&geom));
+        let p = profile(&geom, &ords).unwrap();
+        assert_eq!(20.0, p.gain);
+        assert_eq!(0.0, p.loss);
+
+        let (_, ords) = Ordinates::split_wkt("POLYGON Z ((0 0 1, 1 0 2, 1 1 3, 0 0 4))").unwrap();
+        let ring = Geometry::LineString(LineString::from(vec![
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 0.0),
+        ]));
+        assert_eq!("LINESTRING Z(0 0 1,1 0 2,1 1 3,0 0 4)", ords.wkt(&ring));
+    }
+
+    #[test]
+    fn test_geojson() {
+        let value: geojson::Value = serde_json::from_value::<geojson::Geometry>(
+            json!({"type": "Point", "coordinates": [1, 2, 3]}),
+        )
+        .unwrap()
+        .value;
+        let ords = Ordinates::from_geojson(&value).unwrap();
+        let geom = Geometry::Point(Point::new(1.0, 2.0));
+        assert_eq!("POINT Z(1 2 3)", ords.wkt(&geom));
+        assert_eq!(value, ords.geojson_value(&geom));
+
+        let flat: geojson::Value = serde_json::from_value::<geojson::Geometry>(
+            json!({"type": "Point", "coordinates": [1, 2]}),
+        )
+        .unwrap()
+        .value;
+        assert!(Ordinates::from_geojson(&flat).is_none());
+    }
+}
@@ -2,7 +2,10 @@
 mod geoq;
 use geoq::commands;
 use geoq::error::Error;
+use geoq::input;
+use geoq::rejects;
 use geoq::text;
+use geoq::{buffer, distance, geodesic, orient};
 
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use std::process;
@@ -16,29 +19,123 @@ fn run(matches: ArgMatches) -> Result<(), Error> {
         ("map", Some(_)) => commands::map::run(),
         ("snip", Some(_)) => commands::snip::run(),
         ("filter", Some(m)) => commands::filter::run(m),
+        ("join", Some(m)) => commands::join::run(m),
         ("json", Some(m)) => commands::json::run(m),
-        ("centroid", Some(_)) => commands::centroid::run(),
+        ("csv", Some(m)) => commands::csv::run(m),
+        ("centroid", Some(m)) => commands::centroid::run(m),
         ("whereami", Some(_)) => commands::whereami::run(),
         ("simplify", Some(m)) => commands::simplify::run(m),
         ("measure", Some(m)) => commands::measure::run(m),
         ("bbox", Some(m)) => commands::bbox::run(m),
+        ("buffer", Some(m)) => commands::buffer::run(m),
+        ("clip", Some(m)) => commands::clip::run(m),
+        ("hull", Some(m)) => commands::hull::run(m),
+        ("overlay", Some(m)) => commands::overlay::run(m),
+        ("dissolve", Some(m)) => commands::dissolve::run(m),
         ("shp", Some(m)) => commands::shp::run(m),
         ("fgb", Some(m)) => commands::fgb::run(m),
         ("h3", Some(m)) => commands::h3::run(m),
+        ("validate", Some(m)) => commands::validate::run(m),
+        ("orient", Some(m)) => commands::orient::run(m),
+        ("nearest", Some(m)) => commands::nearest::run(m),
+        ("split-antimeridian", Some(_)) => commands::split_antimeridian::run(),
         _ => Err(Error::UnknownCommand),
     }
 }
 
+// Global args can be given either before or after a subcommand,
+// e.g. 'geoq --on-error skip wkt' OR 'geoq wkt --on-error skip'
+fn global_value<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    matches
+        .value_of(name)
+        .or_else(|| matches.subcommand().1.and_then(|m| global_value(m, name)))
+}
+
+fn configure(matches: &ArgMatches) -> Result<(), Error> {
+    if let Some(on_error) = global_value(matches, "on-error") {
+        rejects::set_policy(on_error.parse()?)?;
+    }
+    if let Some(format) = global_value(matches, "input-format") {
+        input::set_format(format.parse()?)?;
+    }
+    Ok(())
+}
+
+// Shared by the commands which can rewind polygon rings
+fn winding_arg() -> Arg<'static, 'static> {
+    Arg::with_name("winding")
+        .long("winding")
+        .takes_value(true)
+        .value_name("WINDING")
+        .possible_values(&orient::WINDINGS)
+        .help(text::WINDING_ARG_HELP)
+}
+
+fn geodesic_measure_command(
+    name: &'static str,
+    about: &'static str,
+    after_help: &'static str,
+) -> App<'static, 'static> {
+    SubCommand::with_name(name)
+        .about(about)
+        .after_help(after_help)
+        .arg(
+            Arg::with_name("units")
+                .long("units")
+                .short("u")
+                .takes_value(true)
+                .value_name("UNITS")
+                .possible_values(&geodesic::UNITS)
+                .help(text::MEASURE_UNITS_ARG_HELP),
+        )
+        .arg(
+            Arg::with_name("geojson")
+                .long("geojson")
+                .required(false)
+                .takes_value(false)
+                .help(text::MEASURE_COORDS_GEOJSON_ARG_HELP),
+        )
+}
+
+fn distance_method_arg() -> Arg<'static, 'static> {
+    Arg::with_name("method")
+        .long("method")
+        .short("m")
+        .takes_value(true)
+        .value_name("METHOD")
+        .possible_values(&distance::METHODS)
+        .help(text::DISTANCE_METHOD_ARG_HELP)
+}
+
+fn distance_units_arg() -> Arg<'static, 'static> {
+    Arg::with_name("units")
+        .long("units")
+        .short("u")
+        .takes_value(true)
+        .value_name("UNITS")
+        .possible_values(&geodesic::LENGTH_UNITS)
+        .help(text::DISTANCE_UNITS_ARG_HELP)
+}
+
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
 fn main() {
     let geojson = SubCommand::with_name("gj")
         .about("Output features as GeoJSON")
-        .subcommand(SubCommand::with_name("geom").about("Output entity as a GeoJSON geometry"))
-        .subcommand(SubCommand::with_name("f").about("Output entity as a GeoJSON Feature"))
+        .subcommand(
+            SubCommand::with_name("geom")
+                .about("Output entity as a GeoJSON geometry")
+                .arg(winding_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("f")
+                .about("Output entity as a GeoJSON Feature")
+                .arg(winding_arg()),
+        )
         .subcommand(
             SubCommand::with_name("fc")
-                .about("Collect all given entities into a GeoJSON Feature Collection"),
+                .about("Collect all given entities into a GeoJSON Feature Collection")
+                .arg(winding_arg()),
         );
 
     let geohash = SubCommand::with_name("gh")
@@ -76,6 +173,13 @@ fn main() {
                          .short("e")
                          .help("Exclude the given geohash from its neighbors.\nBy default it will be included in the output,\ngiving a 3x3 grid centered on the provided geohash.")));
 
+    let filter_predicate = |name, about| {
+        SubCommand::with_name(name).about(about).arg(
+            Arg::with_name("query")
+                .help("Entity to check.\nMust be Lat/Lon, Geohash, WKT, or GeoJSON.")
+                .index(1),
+        )
+    };
     let filter = SubCommand::with_name("filter")
         .about("Select features based on geospatial predicates")
         .after_help(text::FILTER_AFTER_HELP)
@@ -102,13 +206,13 @@ fn main() {
                 .about("Output only entities (from STDIN) which fall within a QUERY entity (as command-line ARG)")
                 .arg(
                     Arg::with_name("query")
-                        .help("Entity to check intersections.\nMust be Geohash, WKT, or GeoJSON.\nMust be a POLYGON or MULTIPOLYGON.")
+                        .help("Entity to check intersections.\nMust be Lat/Lon, Geohash, WKT, or GeoJSON.")
                         .index(1)
                 )
         )
         .subcommand(
             SubCommand::with_name("dwithin")
-                .about("Output only points (from STDIN) which fall within a QUERY entity (as command-line ARG)")
+                .about("Output only features (from STDIN) which fall within a given distance of a QUERY entity (as command-line ARG)")
                 .after_help(text::FILTER_DWITHIN_AFTER_HELP)
                 .arg(
                     Arg::with_name("query")
@@ -117,12 +221,126 @@ fn main() {
                 )
                 .arg(
                     Arg::with_name("radius")
-                        .help("Radius in meters")
+                        .help("Radius in meters, or in --units if given")
                         .takes_value(true)
                         .required(true)
                         .long("radius")
                         .short("r")
                 )
+                .arg(distance_method_arg())
+                .arg(distance_units_arg())
+        )
+        .subcommand(filter_predicate("within", "Output only entities (from STDIN) which contain a QUERY entity (as command-line ARG)"))
+        .subcommand(filter_predicate("touches", "Output only entities (from STDIN) which touch a QUERY entity, meeting it only at their boundaries"))
+        .subcommand(filter_predicate("crosses", "Output only entities (from STDIN) which cross a QUERY entity"))
+        .subcommand(filter_predicate("overlaps", "Output only entities (from STDIN) which overlap a QUERY entity of the same dimension"))
+        .subcommand(filter_predicate("disjoint", "Output only entities (from STDIN) which don't meet a QUERY entity at all"))
+        .subcommand(filter_predicate("equals", "Output only entities (from STDIN) which are topologically equal to a QUERY entity"))
+        .subcommand(
+            SubCommand::with_name("relate")
+                .about("Output only entities (from STDIN) whose DE-9IM relationship with a QUERY entity matches a PATTERN")
+                .after_help(text::FILTER_RELATE_AFTER_HELP)
+                .arg(
+                    Arg::with_name("pattern")
+                        .help("DE-9IM pattern, e.g. 'T*F**F***'")
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::with_name("query")
+                        .help("Entity to check.\nMust be Lat/Lon, Geohash, WKT, or GeoJSON.")
+                        .index(2)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("bbox")
+                .about("Output only entities (from STDIN) whose bounding boxes overlap a BBOX, with no QUERY entity needed")
+                .after_help(text::FILTER_BBOX_AFTER_HELP)
+                .setting(AppSettings::AllowLeadingHyphen)
+                .arg(
+                    Arg::with_name("bbox")
+                        .help("Comma-separated bounding box: minLon,minLat,maxLon,maxLat")
+                        .allow_hyphen_values(true)
+                        .required(true)
+                        .index(1)
+                )
+                .arg(
+                    Arg::with_name("strict")
+                        .help("Only output entities which fall entirely within the BBOX")
+                        .long("strict")
+                        .short("s")
+                )
+        );
+
+    let join_query_arg = || {
+        Arg::with_name("query")
+            .help("Feature(s) to join.\nMust be Lat/Lon, Geohash, WKT, or GeoJSON.")
+            .index(1)
+    };
+    let join = SubCommand::with_name("join")
+        .about(text::JOIN_ABOUT)
+        .after_help(text::JOIN_AFTER_HELP)
+        .arg(
+            Arg::with_name("query-file")
+                .help("Input file for reading query feature(s).")
+                .takes_value(true)
+                .global(true)
+                .long("query-file")
+                .alias("file")
+                .short("q"),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .help(text::JOIN_MODE_ARG_HELP)
+                .takes_value(true)
+                .global(true)
+                .possible_values(&commands::join::MODES)
+                .long("mode"),
+        )
+        .arg(
+            Arg::with_name("prefix")
+                .help("Prefix for property names added from query features")
+                .takes_value(true)
+                .global(true)
+                .long("prefix")
+                .short("p"),
+        )
+        .arg(
+            Arg::with_name("left")
+                .help("Also output inputs which match no query features, as in a SQL left join")
+                .global(true)
+                .long("left")
+                .short("l"),
+        )
+        .subcommand(
+            SubCommand::with_name("intersects")
+                .about("Join query features which intersect each input")
+                .arg(join_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("contains")
+                .about("Join query features which each input falls within")
+                .arg(join_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("within")
+                .about("Join query features which fall within each input")
+                .arg(join_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("dwithin")
+                .about("Join query features within a given distance of each input")
+                .arg(join_query_arg())
+                .arg(
+                    Arg::with_name("radius")
+                        .help("Radius in meters, or in --units if given")
+                        .takes_value(true)
+                        .required(true)
+                        .long("radius")
+                        .short("r"),
+                )
+                .arg(distance_method_arg())
+                .arg(distance_units_arg()),
         );
 
     let json = SubCommand::with_name("json")
@@ -133,13 +351,112 @@ fn main() {
                 .after_help(text::JSON_MUNGE_AFTER_HELP),
         );
 
+    let csv = SubCommand::with_name("csv")
+        .about("Work with CSV and TSV data")
+        .subcommand(
+            SubCommand::with_name("read")
+                .about(text::CSV_READ_ABOUT)
+                .after_help(text::CSV_READ_AFTER_HELP)
+                .arg(
+                    Arg::with_name("lat-col")
+                        .long("lat-col")
+                        .takes_value(true)
+                        .value_name("COLUMN")
+                        .requires("lon-col")
+                        .conflicts_with("wkt-col")
+                        .help("Name of the column containing latitudes"),
+                )
+                .arg(
+                    Arg::with_name("lon-col")
+                        .long("lon-col")
+                        .takes_value(true)
+                        .value_name("COLUMN")
+                        .requires("lat-col")
+                        .help("Name of the column containing longitudes"),
+                )
+                .arg(
+                    Arg::with_name("wkt-col")
+                        .long("wkt-col")
+                        .takes_value(true)
+                        .value_name("COLUMN")
+                        .conflicts_with("lon-col")
+                        .help("Name of the column containing WKT geometries"),
+                )
+                .arg(
+                    Arg::with_name("delimiter")
+                        .long("delimiter")
+                        .short("d")
+                        .takes_value(true)
+                        .help(text::CSV_DELIMITER_ARG_HELP),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("write")
+                .about(text::CSV_WRITE_ABOUT)
+                .after_help(text::CSV_WRITE_AFTER_HELP)
+                .arg(
+                    Arg::with_name("columns")
+                        .long("columns")
+                        .takes_value(true)
+                        .value_name("COLUMNS")
+                        .help("Comma-separated list of properties to write as columns"),
+                )
+                .arg(
+                    Arg::with_name("geometry")
+                        .long("geometry")
+                        .takes_value(true)
+                        .possible_values(&["wkt", "latlon"])
+                        .default_value("wkt")
+                        .help("Write geometries as a WKT column, or as lat and lon columns (Points only)"),
+                )
+                .arg(
+                    Arg::with_name("sample")
+                        .long("sample")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Number of inputs to check for property names when --columns isn't given (default 1000)"),
+                )
+                .arg(
+                    Arg::with_name("delimiter")
+                        .long("delimiter")
+                        .short("d")
+                        .takes_value(true)
+                        .help("Field delimiter: a single character, or 'tab'. Defaults to comma."),
+                ),
+        );
+
     let read = SubCommand::with_name("read")
         .about("Information about reading inputs with geoq")
         .after_help(text::READ_AFTER_HELP);
 
     let centroid = SubCommand::with_name("centroid")
         .about(text::CENTROID_ABOUT)
-        .after_help(text::CENTROID_AFTER_HELP);
+        .after_help(text::CENTROID_AFTER_HELP)
+        .arg(
+            Arg::with_name("point-on-surface")
+                .long("point-on-surface")
+                .short("p")
+                .conflicts_with_all(&["geodesic", "all"])
+                .help("Give a point guaranteed to fall on each geometry, e.g. for placing labels"),
+        )
+        .arg(
+            Arg::with_name("geodesic")
+                .long("geodesic")
+                .short("g")
+                .help(text::CENTROID_GEODESIC_ARG_HELP),
+        )
+        .arg(
+            Arg::with_name("all")
+                .long("all")
+                .short("a")
+                .help("Give a single centroid for all input geometries rather than 1 per input"),
+        )
+        .arg(
+            Arg::with_name("geojson")
+                .long("geojson")
+                .short("j")
+                .help("Output GeoJSON Features, keeping the properties of each input"),
+        );
 
     let whereami = SubCommand::with_name("whereami")
         .about(text::WHEREAMI_ABOUT)
@@ -156,6 +473,14 @@ fn main() {
                         .help(text::DISTANCE_QUERY_ARG_HELP)
                         .required(true)
                         .index(1),
+                )
+                .arg(distance_method_arg())
+                .arg(distance_units_arg())
+                .arg(
+                    Arg::with_name("segment")
+                        .long("segment")
+                        .short("s")
+                        .help(text::DISTANCE_SEGMENT_ARG_HELP),
                 ),
         )
         .subcommand(
@@ -168,7 +493,29 @@ fn main() {
                         .takes_value(false)
                         .help(text::MEASURE_COORDS_GEOJSON_ARG_HELP),
                 ),
-        );
+        )
+        .subcommand(
+            SubCommand::with_name("elevation")
+                .about(text::MEASURE_ELEVATION_ABOUT)
+                .after_help(text::MEASURE_ELEVATION_AFTER_HELP)
+                .arg(
+                    Arg::with_name("geojson")
+                        .long("geojson")
+                        .required(false)
+                        .takes_value(false)
+                        .help(text::MEASURE_ELEVATION_GEOJSON_ARG_HELP),
+                ),
+        )
+        .subcommand(geodesic_measure_command(
+            "area",
+            text::MEASURE_AREA_ABOUT,
+            text::MEASURE_AREA_AFTER_HELP,
+        ))
+        .subcommand(geodesic_measure_command(
+            "length",
+            text::MEASURE_LENGTH_ABOUT,
+            text::MEASURE_LENGTH_AFTER_HELP,
+        ));
 
     let simplify = SubCommand::with_name("simplify")
         .about(text::SIMPLIFY_ABOUT)
@@ -187,6 +534,132 @@ fn main() {
                 .help(text::SIMPLIFY_TO_COORD_COUNT_ARG_HELP),
         );
 
+    let buffer = SubCommand::with_name("buffer")
+        .about(text::BUFFER_ABOUT)
+        .after_help(text::BUFFER_AFTER_HELP)
+        .setting(AppSettings::AllowNegativeNumbers)
+        .arg(
+            Arg::with_name("distance")
+                .help("Distance to grow geometries by, in meters or --units. Negative distances shrink polygons.")
+                .required(true)
+                .allow_hyphen_values(true)
+                .index(1),
+        )
+        .arg(distance_units_arg())
+        .arg(
+            Arg::with_name("segments")
+                .long("segments")
+                .short("s")
+                .takes_value(true)
+                .value_name("SEGMENTS")
+                .help("Segments used to draw each quarter of a circle (default 8)"),
+        )
+        .arg(
+            Arg::with_name("cap")
+                .long("cap")
+                .takes_value(true)
+                .value_name("CAP")
+                .possible_values(&buffer::CAPS)
+                .help(text::BUFFER_CAP_ARG_HELP),
+        )
+        .arg(
+            Arg::with_name("join")
+                .long("join")
+                .takes_value(true)
+                .value_name("JOIN")
+                .possible_values(&buffer::JOINS)
+                .help(text::BUFFER_JOIN_ARG_HELP),
+        );
+
+    let overlay_query_arg = || {
+        Arg::with_name("query")
+            .help("Feature(s) to overlay.\nMust be Lat/Lon, Geohash, WKT, or GeoJSON.")
+            .index(1)
+    };
+    let overlay = SubCommand::with_name("overlay")
+        .about(text::OVERLAY_ABOUT)
+        .after_help(text::OVERLAY_AFTER_HELP)
+        .arg(
+            Arg::with_name("query-file")
+                .help("Input file for reading query feature(s).")
+                .takes_value(true)
+                .global(true)
+                .long("query-file")
+                .alias("file")
+                .short("q"),
+        )
+        .subcommand(
+            SubCommand::with_name("intersection")
+                .about("Keep the parts of each input covered by the query features")
+                .arg(overlay_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("difference")
+                .about("Remove the parts of each input covered by the query features")
+                .arg(overlay_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("union")
+                .about("Merge each input with the query features")
+                .arg(overlay_query_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("xor")
+                .about("Keep the parts covered by either each input or the query features, but not both")
+                .arg(overlay_query_arg()),
+        );
+
+    let dissolve = SubCommand::with_name("dissolve")
+        .about(text::DISSOLVE_ABOUT)
+        .after_help(text::DISSOLVE_AFTER_HELP)
+        .arg(
+            Arg::with_name("group-by")
+                .long("group-by")
+                .short("g")
+                .takes_value(true)
+                .value_name("PROPERTY")
+                .help("Merge inputs separately for each value of this GeoJSON property"),
+        );
+
+    let clip = SubCommand::with_name("clip")
+        .about(text::CLIP_ABOUT)
+        .after_help(text::CLIP_AFTER_HELP)
+        .setting(AppSettings::AllowLeadingHyphen)
+        .arg(
+            Arg::with_name("bbox")
+                .help("Comma-separated bounding box: minLon,minLat,maxLon,maxLat")
+                .allow_hyphen_values(true)
+                .required(true)
+                .index(1),
+        );
+
+    let hull = SubCommand::with_name("hull")
+        .about(text::HULL_ABOUT)
+        .after_help(text::HULL_AFTER_HELP)
+        .arg(
+            Arg::with_name("concave")
+                .long("concave")
+                .short("c")
+                .takes_value(true)
+                .value_name("RATIO")
+                .help(text::HULL_CONCAVE_ARG_HELP),
+        )
+        .arg(
+            Arg::with_name("all")
+                .long("all")
+                .short("a")
+                .help("Give a single hull for all input geometries rather than 1 hull per input"),
+        )
+        .arg(
+            Arg::with_name("group-by")
+                .long("group-by")
+                .short("g")
+                .takes_value(true)
+                .value_name("PROPERTY")
+                .conflicts_with("all")
+                .help("Give a single hull for all inputs with each value of this GeoJSON property"),
+        );
+
     let bbox = SubCommand::with_name("bbox")
         .about("Generate bounding boxes for geometries")
         .arg(Arg::with_name("embed").long("embed").short("e").help(
@@ -198,8 +671,23 @@ fn main() {
                 .short("a")
                 .help("Give a single bbox for all input geometries rather than 1 bbox per input"),
         )
+        .arg(
+            Arg::with_name("antimeridian")
+                .long("antimeridian")
+                .help(text::BBOX_ANTIMERIDIAN_ARG_HELP),
+        )
         .after_help(text::BBOX_AFTER_HELP);
 
+    let validate = SubCommand::with_name("validate")
+        .about(text::VALIDATE_ABOUT)
+        .after_help(text::VALIDATE_AFTER_HELP)
+        .arg(
+            Arg::with_name("fix")
+                .long("fix")
+                .takes_value(false)
+                .help(text::VALIDATE_FIX_ARG_HELP),
+        );
+
     let shp = SubCommand::with_name("shp")
         .about("Read a shapefile and convert to GeoJSON")
         .arg(
@@ -207,7 +695,51 @@ fn main() {
                 .help("output file, e.g. data.fgb")
                 .required(true)
                 .index(1),
-        );
+        )
+        .arg(winding_arg());
+
+    let orient = SubCommand::with_name("orient")
+        .about(text::ORIENT_ABOUT)
+        .after_help(text::ORIENT_AFTER_HELP)
+        .arg(winding_arg());
+
+    let nearest = SubCommand::with_name("nearest")
+        .about(text::NEAREST_ABOUT)
+        .after_help(text::NEAREST_AFTER_HELP)
+        .arg(
+            Arg::with_name("query")
+                .help(
+                    "Feature(s) to search for nearest matches.\nMust be Geohash, WKT, or GeoJSON.",
+                )
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("query-file")
+                .help("Input file for reading query feature(s). Also accepted as --file.")
+                .takes_value(true)
+                .long("query-file")
+                .alias("file")
+                .short("q"),
+        )
+        .arg(
+            Arg::with_name("count")
+                .help(text::NEAREST_COUNT_ARG_HELP)
+                .takes_value(true)
+                .long("count")
+                .short("k"),
+        )
+        .arg(
+            Arg::with_name("geojson")
+                .long("geojson")
+                .takes_value(false)
+                .help(text::NEAREST_GEOJSON_ARG_HELP),
+        )
+        .arg(distance_method_arg())
+        .arg(distance_units_arg());
+
+    let split_antimeridian = SubCommand::with_name("split-antimeridian")
+        .about(text::SPLIT_ANTIMERIDIAN_ABOUT)
+        .after_help(text::SPLIT_ANTIMERIDIAN_AFTER_HELP);
 
     let fgb = SubCommand::with_name("fgb")
         .about("Reading and Writing FlatGeoBuf")
@@ -306,25 +838,58 @@ fn main() {
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .about("geoq - GeoSpatial utility belt")
         .after_help(text::MAIN_AFTER_HELP)
+        .arg(
+            Arg::with_name("on-error")
+                .long("on-error")
+                .takes_value(true)
+                .global(true)
+                .value_name("POLICY")
+                .help(text::ON_ERROR_ARG_HELP),
+        )
+        .arg(
+            Arg::with_name("input-format")
+                .long("input-format")
+                .takes_value(true)
+                .global(true)
+                .value_name("FORMAT")
+                .possible_values(&input::INPUT_FORMATS)
+                .help(text::INPUT_FORMAT_ARG_HELP),
+        )
         .subcommand(SubCommand::with_name("wkt").about("Output features as Well-Known Text"))
         .subcommand(SubCommand::with_name("map").about("View features on a map using geojson.io"))
         .subcommand(read)
         .subcommand(geohash)
         .subcommand(geojson)
         .subcommand(json)
+        .subcommand(csv)
         .subcommand(filter)
+        .subcommand(join)
         .subcommand(centroid)
         .subcommand(whereami)
         .subcommand(measure)
         .subcommand(simplify)
         .subcommand(bbox)
+        .subcommand(buffer)
+        .subcommand(overlay)
+        .subcommand(dissolve)
+        .subcommand(clip)
+        .subcommand(hull)
         .subcommand(shp)
         .subcommand(fgb)
         .subcommand(h3)
+        .subcommand(validate)
+        .subcommand(orient)
+        .subcommand(split_antimeridian)
+        .subcommand(nearest)
         .get_matches();
 
-    if let Err(e) = run(matches) {
-        eprintln!("geoq exited with error: {:?}", e);
+    let res = configure(&matches).and_then(|_| run(matches));
+    if let Err(e) = rejects::summary() {
+        eprintln!("geoq exited with error: {}", e.report());
+        process::exit(1);
+    }
+    if let Err(e) = res {
+        eprintln!("geoq exited with error: {}", e.report());
         process::exit(1);
     }
 }
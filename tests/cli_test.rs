@@ -31,7 +31,7 @@ fn exits_on_invalid_input() {
         .with_args(&["read"])
         .stdin("pizza")
         .stderr()
-        .contains("Application error: InvalidInput(\"Unable to parse single-line input: pizza\")")
+        .contains("geoq exited with error: Invalid input on line 1: pizza [invalid-input]\n  caused by: Unable to parse single-line input: pizza")
         .fails()
         .unwrap();
 }
@@ -43,7 +43,7 @@ fn invalid_wkt() {
         .with_args(&["read"])
         .stdin(input)
         .stderr()
-        .is("Application error: InvalidWkt")
+        .contains("geoq exited with error: Invalid input on line 1: Polygon ((30 10, 10 30, 40 40, 30 10) [invalid-wkt]\n  caused by: Invalid WKT:")
         .fails()
         .unwrap();
 }
@@ -756,3 +756,972 @@ z
         .is(output)
         .unwrap();
 }
+
+#[test]
+fn reads_pretty_printed_geojson() {
+    let input = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "properties": {"a": "b"},
+      "geometry": {"type": "Point", "coordinates": [34.0, 12.0]}
+    },
+    {
+      "type": "Feature",
+      "properties": {},
+      "geometry": {"type": "Point", "coordinates": [78.0, 56.0]}
+    }
+  ]
+}
+{
+  "type": "LineString",
+  "coordinates": [[30, 10], [10, 30], [40, 40]]
+}
+9q5
+"#;
+
+    let output = r#"POINT(34 12)
+POINT(78 56)
+LINESTRING(30 10,10 30,40 40)
+POLYGON((-119.53125 33.75,-118.125 33.75,-118.125 35.15625,-119.53125 35.15625,-119.53125 33.75))
+"#;
+    Assert::main_binary()
+        .with_args(&["wkt"])
+        .stdin(input)
+        .stdout()
+        .is(output)
+        .unwrap();
+}
+
+#[test]
+fn on_error_skip() {
+    let input = "9q5\npizza\n12,34\n";
+    let output = r#"POLYGON((-119.53125 33.75,-118.125 33.75,-118.125 35.15625,-119.53125 35.15625,-119.53125 33.75))
+POINT(34 12)
+"#;
+    Assert::main_binary()
+        .with_args(&["wkt", "--on-error", "skip"])
+        .stdin(input)
+        .stdout()
+        .is(output)
+        .stderr()
+        .is("geoq skipped 1 invalid input(s)")
+        .unwrap();
+}
+
+#[test]
+fn on_error_skip_unterminated_object() {
+    let input = "12,34\n{\"type\": \"Point\",\n56,78\n";
+    Assert::main_binary()
+        .with_args(&["--on-error", "stderr", "wkt"])
+        .stdin(input)
+        .stdout()
+        .is("POINT(34 12)\nPOINT(78 56)\n")
+        .stderr()
+        .contains("line 2: {\"type\": \"Point\",")
+        .stderr()
+        .contains("geoq skipped 1 invalid input(s)")
+        .unwrap();
+}
+
+#[test]
+fn on_error_stderr() {
+    let input = "9q5\npizza\n12,34\n";
+    Assert::main_binary()
+        .with_args(&["--on-error", "stderr", "gh", "point", "3"])
+        .stdin(input)
+        .stdout()
+        .is("sf0\n")
+        .stderr()
+        .contains("line 1: 9q5")
+        .stderr()
+        .contains("line 2: pizza")
+        .stderr()
+        .contains("geoq skipped 2 invalid input(s)")
+        .unwrap();
+}
+
+#[test]
+fn on_error_reject_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rejects.txt");
+    let arg = format!("--on-error=reject-file={}", path.to_str().unwrap());
+    Assert::main_binary()
+        .with_args(&["wkt", &arg])
+        .stdin("pizza\n12,34\nLINESTRING (30 10\n")
+        .stdout()
+        .is("POINT(34 12)\n")
+        .unwrap();
+    let rejected = std::fs::read_to_string(path).unwrap();
+    assert_eq!("pizza\nLINESTRING (30 10\n", rejected);
+}
+
+#[test]
+fn on_error_invalid_policy() {
+    Assert::main_binary()
+        .with_args(&["wkt", "--on-error", "pizza"])
+        .stdin("12,34\n")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn input_format_geohash() {
+    Assert::main_binary()
+        .with_args(&["--input-format", "geohash", "read"])
+        .stdin("9bcdef012345678\n")
+        .stdout()
+        .is("Geohash: 9bcdef012345678\n")
+        .unwrap();
+}
+
+#[test]
+fn input_format_lonlat() {
+    Assert::main_binary()
+        .with_args(&["wkt", "--input-format", "lonlat"])
+        .stdin("-118,34\n-117.5\t33\n")
+        .stdout()
+        .is("POINT(-118 34)\nPOINT(-117.5 33)\n")
+        .unwrap();
+}
+
+#[test]
+fn input_format_rejects_other_formats() {
+    Assert::main_binary()
+        .with_args(&["--input-format", "wkt", "read"])
+        .stdin("12,34\n")
+        .stderr()
+        .contains("[invalid-wkt]")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn input_format_extra_columns() {
+    let input = "-118,34,2020-01-01T00:00:00Z,abc\n-117\t33\tdef\n";
+    let output = r#"{"geometry":{"coordinates":[-118.0,34.0],"type":"Point"},"properties":{"col3":"2020-01-01T00:00:00Z","col4":"abc"},"type":"Feature"}
+{"geometry":{"coordinates":[-117.0,33.0],"type":"Point"},"properties":{"col3":"def"},"type":"Feature"}
+"#;
+    Assert::main_binary()
+        .with_args(&["--input-format", "lonlat", "gj", "f"])
+        .stdin(input)
+        .stdout()
+        .is(output)
+        .unwrap();
+}
+
+#[test]
+fn detected_extra_columns() {
+    let input = "34.1,-118.2,2021-01-01,abc\n9q5\n";
+    Assert::main_binary()
+        .with_args(&["gj", "f"])
+        .stdin(input)
+        .stdout()
+        .contains(r#"{"geometry":{"coordinates":[-118.2,34.1],"type":"Point"},"properties":{"col3":"2021-01-01","col4":"abc"},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn csv_read_detects_geometry() {
+    let input = "Name,Latitude,Longitude,visits\npizza,34.0,-118.0,3\n\"tacos, etc\",35,-117,\n";
+    let output = r#"{"geometry":{"coordinates":[-118.0,34.0],"type":"Point"},"properties":{"Name":"pizza","visits":3},"type":"Feature"}
+{"geometry":{"coordinates":[-117.0,35.0],"type":"Point"},"properties":{"Name":"tacos, etc","visits":null},"type":"Feature"}
+"#;
+    Assert::main_binary()
+        .with_args(&["csv", "read"])
+        .stdin(input)
+        .stdout()
+        .is(output)
+        .unwrap();
+}
+
+#[test]
+fn csv_read_tsv_with_columns() {
+    let input = "id\ty\tx\n1\t34\t-118\n";
+    Assert::main_binary()
+        .with_args(&["csv", "read", "--lat-col", "y", "--lon-col", "x"])
+        .stdin(input)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[-118.0,34.0],"type":"Point"},"properties":{"id":1},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn csv_read_delimited_multiline_field() {
+    let input = "lat;name;lon\n1;\"a\nb\";2\n";
+    Assert::main_binary()
+        .with_args(&["csv", "read", "-d", ";"])
+        .stdin(input)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[2.0,1.0],"type":"Point"},"properties":{"name":"a\nb"},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn csv_read_invalid_row() {
+    let input = "lat,lon\n34,-118\npizza,-117\n";
+    Assert::main_binary()
+        .with_args(&["--on-error", "stderr", "csv", "read"])
+        .stdin(input)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[-118.0,34.0],"type":"Point"},"properties":{},"type":"Feature"}"#)
+        .stderr()
+        .contains("line 3: pizza,-117")
+        .unwrap();
+}
+
+#[test]
+fn csv_write() {
+    let input = r#"{"type":"Feature","properties":{"name":"pizza","visits":3},"geometry":{"type":"Point","coordinates":[-118,34]}}
+{"type":"Feature","properties":{"name":"tacos, etc","open":true},"geometry":{"type":"Point","coordinates":[-117,35]}}
+"#;
+    Assert::main_binary()
+        .with_args(&["csv", "write"])
+        .stdin(input)
+        .stdout()
+        .is("wkt,name,visits,open\nPOINT(-118 34),pizza,3,\nPOINT(-117 35),\"tacos, etc\",,true\n")
+        .unwrap();
+}
+
+#[test]
+fn csv_write_latlon_columns() {
+    let input = r#"{"type":"Feature","properties":{"name":"pizza","visits":3},"geometry":{"type":"Point","coordinates":[-118,34]}}"#;
+    Assert::main_binary()
+        .with_args(&[
+            "csv",
+            "write",
+            "--geometry",
+            "latlon",
+            "--columns",
+            "visits",
+            "-d",
+            "tab",
+        ])
+        .stdin(input)
+        .stdout()
+        .is("lat\tlon\tvisits\n34\t-118\t3\n")
+        .unwrap();
+}
+
+#[test]
+fn z_values_survive_wkt_and_geojson() {
+    Assert::main_binary()
+        .with_args(&["gj", "geom"])
+        .stdin("POINT Z (-118 34 100.5)\n")
+        .stdout()
+        .is(r#"{"coordinates":[-118.0,34.0,100.5],"type":"Point"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["wkt"])
+        .stdin(r#"{"type":"LineString","coordinates":[[-118,34,100],[-117,35,120.5]]}"#)
+        .stdout()
+        .is("LINESTRING Z(-118 34 100,-117 35 120.5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["wkt"])
+        .stdin("POINT ZM (1 2 3 4)\n")
+        .stdout()
+        .is("POINT ZM(1 2 3 4)")
+        .unwrap();
+}
+
+#[test]
+fn z_values_survive_fgb() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lines.fgb");
+    let path = path.to_str().unwrap();
+    Assert::main_binary()
+        .with_args(&["fgb", "write", path])
+        .stdin("LINESTRING Z (0 0 1, 1 1 2.5)\n")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["fgb", "read", path])
+        .stdout()
+        .contains(r#""coordinates": [[0,0,1],[1,1,2.5]]"#)
+        .unwrap();
+}
+
+#[test]
+fn simplify_keeps_z_values() {
+    Assert::main_binary()
+        .with_args(&["simplify", "0.1"])
+        .stdin("LINESTRING Z (0 0 1, 1 0.001 2, 2 0 3)\n")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[0.0,0.0,1.0],[2.0,0.0,3.0]],"type":"LineString"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn measure_elevation() {
+    Assert::main_binary()
+        .with_args(&["measure", "elevation"])
+        .stdin("LINESTRING Z (-118 34 100, -118 34.001 130, -118 34.002 110)\n")
+        .stdout()
+        .contains("30\t20\t228.15")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["measure", "elevation"])
+        .stdin("LINESTRING (-118 34, -118 34.001)\n")
+        .fails()
+        .stderr()
+        .contains("Z values")
+        .unwrap();
+}
+
+#[test]
+fn validate_reports_issues() {
+    let input = "POLYGON((0 0,2 2,2 0,0 2,0 0))\nPOLYGON((0 0,4 0,4 4,0 4,0 0))\n";
+    Assert::main_binary()
+        .with_args(&["validate"])
+        .stdin(input)
+        .stdout()
+        .is("invalid\tSelf-intersection at POINT(1 1)\tPOLYGON((0 0,2 2,2 0,0 2,0 0))\nvalid\t\tPOLYGON((0 0,4 0,4 4,0 4,0 0))")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["validate"])
+        .stdin(r#"{"type":"Polygon","coordinates":[[[0,0],[0,4],[4,4],[4,0]]]}"#)
+        .stdout()
+        .contains("invalid\tUnclosed ring at POINT(4 0); Wrong ring orientation at POINT(0 0)\t")
+        .unwrap();
+}
+
+#[test]
+fn validate_fix() {
+    Assert::main_binary()
+        .with_args(&["validate", "--fix"])
+        .stdin("POLYGON((0 0,2 2,2 0,0 2,0 0))\n")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[[1.0,1.0],[2.0,0.0],[2.0,2.0],[1.0,1.0]]],[[[0.0,0.0],[1.0,1.0],[0.0,2.0],[0.0,0.0]]]],"type":"MultiPolygon"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn orient_rewinds_rings() {
+    let input = r#"{"type":"Feature","properties":{"name":"square"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[0,4],[4,4],[4,0],[0,0]]]}}"#;
+    Assert::main_binary()
+        .with_args(&["orient"])
+        .stdin(input)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[0.0,0.0],[4.0,0.0],[4.0,4.0],[0.0,4.0],[0.0,0.0]]],"type":"Polygon"},"properties":{"name":"square"},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["orient", "--winding", "cw"])
+        .stdin(input)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[0.0,0.0],[0.0,4.0],[4.0,4.0],[4.0,0.0],[0.0,0.0]]],"type":"Polygon"},"properties":{"name":"square"},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn gj_winding_option() {
+    Assert::main_binary()
+        .with_args(&["gj", "geom", "--winding", "ccw"])
+        .stdin("POLYGON((0 0,0 4,4 4,4 0,0 0))")
+        .stdout()
+        .is(r#"{"coordinates":[[[0.0,0.0],[4.0,0.0],[4.0,4.0],[0.0,4.0],[0.0,0.0]]],"type":"Polygon"}"#)
+        .unwrap();
+}
+
+#[test]
+fn split_antimeridian() {
+    Assert::main_binary()
+        .with_args(&["split-antimeridian"])
+        .stdin("LINESTRING(170 0,-170 10)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[170.0,0.0],[180.0,5.0]],[[-180.0,5.0],[-170.0,10.0]]],"type":"MultiLineString"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn bbox_antimeridian() {
+    Assert::main_binary()
+        .with_args(&["bbox", "--antimeridian", "--embed"])
+        .stdin("LINESTRING(170 0,-170 10)")
+        .stdout()
+        .contains(r#""bbox":[170.0,0.0,-170.0,10.0]"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["bbox", "--antimeridian"])
+        .stdin("LINESTRING(170 0,-170 10)")
+        .stdout()
+        .contains(r#""type":"MultiPolygon""#)
+        .unwrap();
+}
+
+#[test]
+fn measure_area_and_length() {
+    Assert::main_binary()
+        .with_args(&["measure", "area", "--units", "km"])
+        .stdin("POLYGON((0 0,1 0,1 1,0 1,0 0))")
+        .stdout()
+        .contains("12308.778")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["measure", "length", "--units", "km", "--geojson"])
+        .stdin("LINESTRING(0 0,1 0)")
+        .stdout()
+        .contains(r#""properties":{"length":111.319"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["measure", "length", "--units", "acres"])
+        .stdin("LINESTRING(0 0,1 0)")
+        .fails()
+        .stderr()
+        .contains("can only be used for area")
+        .unwrap();
+}
+
+#[test]
+fn measure_distance_methods_and_units() {
+    Assert::main_binary()
+        .with_args(&[
+            "measure",
+            "distance",
+            "POINT(-118.2437 34.0522)",
+            "--units",
+            "km",
+        ])
+        .stdin("POINT(-74.0060 40.7128)")
+        .stdout()
+        .contains("3944.42")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["measure", "distance", "POINT(0 0)", "--method", "planar"])
+        .stdin("POINT(3 4)")
+        .stdout()
+        .is("5\tPOINT(3 4)")
+        .unwrap();
+
+    // Nearly antipodal points, where Vincenty's formulae don't converge
+    Assert::main_binary()
+        .with_args(&["measure", "distance", "POINT(0 0)"])
+        .stdin("POINT(179.7 0.5)")
+        .stdout()
+        .contains("19944127.4")
+        .unwrap();
+}
+
+#[test]
+fn filter_dwithin_units() {
+    Assert::main_binary()
+        .with_args(&[
+            "filter",
+            "dwithin",
+            "--radius",
+            "2",
+            "--units",
+            "mi",
+            "POINT(0 0)",
+        ])
+        .stdin("POINT(0 0.02)\nPOINT(0 0.04)")
+        .stdout()
+        .is("POINT(0 0.02)")
+        .unwrap();
+}
+
+#[test]
+fn filter_dwithin_across_antimeridian() {
+    let dir = tempfile::tempdir().unwrap();
+    let query_file = dir.path().join("queries.txt");
+    std::fs::write(&query_file, "0,170\n0,-179.9").unwrap();
+    Assert::main_binary()
+        .with_args(&[
+            "filter",
+            "dwithin",
+            "--radius",
+            "50000",
+            "--query-file",
+            query_file.to_str().unwrap(),
+        ])
+        .stdin("0,179.9\n0,160")
+        .stdout()
+        .is("0,179.9")
+        .unwrap();
+}
+
+#[test]
+fn measure_distance_between_geometries() {
+    Assert::main_binary()
+        .with_args(&[
+            "measure",
+            "distance",
+            "LINESTRING(0 0,0 0.5)",
+            "--method",
+            "planar",
+            "--segment",
+        ])
+        .stdin("POLYGON((1 1,2 1,2 2,1 2,1 1))")
+        .stdout()
+        .contains("LINESTRING(0 0.5,1 1)\tPOLYGON((1 1,2 1,2 2,1 2,1 1))")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&[
+            "filter",
+            "dwithin",
+            "--radius",
+            "1",
+            "--units",
+            "km",
+            "POLYGON((1 1,2 1,2 2,1 2,1 1))",
+        ])
+        .stdin("LINESTRING(0 1.5,0.995 1.5)\nLINESTRING(0 1.5,0.9 1.5)")
+        .stdout()
+        .is("LINESTRING(0 1.5,0.995 1.5)")
+        .unwrap();
+}
+
+#[test]
+fn nearest_query_features() {
+    Assert::main_binary()
+        .with_args(&[
+            "nearest",
+            "POINT(5 5)\nPOINT(0.3 0)\nPOINT(0 0)",
+            "--count",
+            "2",
+            "--units",
+            "km",
+        ])
+        .stdin("POINT(0 0.1)")
+        .stdout()
+        .contains("11.05742")
+        .stdout()
+        .contains("\tPOINT(0 0)\tPOINT(0 0.1)\n")
+        .stdout()
+        .contains("\tPOINT(0.3 0)\tPOINT(0 0.1)")
+        .unwrap();
+
+    let warehouses = r#"{"type":"Feature","properties":{"name":"north"},"geometry":{"type":"Point","coordinates":[0,1]}}
+{"type":"Feature","properties":{"name":"south"},"geometry":{"type":"Point","coordinates":[0,-3]}}"#;
+    Assert::main_binary()
+        .with_args(&["nearest", warehouses, "--geojson", "--method", "planar"])
+        .stdin("POINT(0 0)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[0.0,0.0],"type":"Point"},"properties":{"nearest":[{"distance":1.0,"name":"north"}]},"type":"Feature"}"#)
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let query_file = dir.path().join("warehouses.geojson");
+    std::fs::write(&query_file, warehouses).unwrap();
+    Assert::main_binary()
+        .with_args(&[
+            "nearest",
+            "--file",
+            query_file.to_str().unwrap(),
+            "--method",
+            "planar",
+        ])
+        .stdin("POINT(0 -2)")
+        .stdout()
+        .contains("\t{\"type\":\"Feature\",\"properties\":{\"name\":\"south\"}")
+        .unwrap();
+}
+
+#[test]
+fn nearest_across_antimeridian() {
+    Assert::main_binary()
+        .with_args(&["nearest", "0,170\n0,-179.9", "--units", "km"])
+        .stdin("0,179.9")
+        .stdout()
+        .is("22.263898155840188\t0,-179.9\t0,179.9")
+        .unwrap();
+}
+
+#[test]
+fn filter_many_queries() {
+    let queries: Vec<String> = (0..100)
+        .map(|i| format!("POLYGON(({} 0,{} 0,{} 1,{} 1,{} 0))", i, i + 1, i + 1, i, i))
+        .collect();
+    let dir = tempfile::tempdir().unwrap();
+    let query_file = dir.path().join("queries.txt");
+    std::fs::write(&query_file, queries.join("\n")).unwrap();
+    let path = query_file.to_str().unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "contains", "--query-file", path])
+        .stdin("POINT(50.5 0.5)\nPOINT(150.5 0.5)\nPOINT(99.5 0.2)")
+        .stdout()
+        .is("POINT(50.5 0.5)\nPOINT(99.5 0.2)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&[
+            "filter",
+            "dwithin",
+            "--query-file",
+            path,
+            "--radius",
+            "1",
+            "--method",
+            "planar",
+        ])
+        .stdin("POINT(50.5 3)\nPOINT(100.5 0.5)")
+        .stdout()
+        .is("POINT(100.5 0.5)")
+        .unwrap();
+}
+
+#[test]
+fn join_query_properties() {
+    let zones = r#"{"type":"Feature","properties":{"zone":"a"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[2,0],[2,2],[0,2],[0,0]]]}}
+{"type":"Feature","properties":{"zone":"b","code":7},"geometry":{"type":"Polygon","coordinates":[[[1,1],[3,1],[3,3],[1,3],[1,1]]]}}"#;
+
+    Assert::main_binary()
+        .with_args(&["join", "contains", zones, "--prefix", "z_"])
+        .stdin("POINT(0.5 0.5)\nPOINT(1.5 1.5)\nPOINT(5 5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[0.5,0.5],"type":"Point"},"properties":{"z_zone":"a"},"type":"Feature"}
+{"geometry":{"coordinates":[1.5,1.5],"type":"Point"},"properties":{"z_zone":"a"},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["join", "intersects", zones, "--mode", "all"])
+        .stdin("POINT(1.5 1.5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[1.5,1.5],"type":"Point"},"properties":{"code":[null,7],"zone":["a","b"]},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["join", "contains", zones, "--mode", "count", "--left"])
+        .stdin("POINT(1.5 1.5)\nPOINT(5 5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[1.5,1.5],"type":"Point"},"properties":{"count":2},"type":"Feature"}
+{"geometry":{"coordinates":[5.0,5.0],"type":"Point"},"properties":{"count":0},"type":"Feature"}"#)
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let query_file = dir.path().join("zones.geojson");
+    std::fs::write(&query_file, zones).unwrap();
+    Assert::main_binary()
+        .with_args(&[
+            "join",
+            "contains",
+            "--file",
+            query_file.to_str().unwrap(),
+            "--mode",
+            "count",
+        ])
+        .stdin("POINT(1.5 1.5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[1.5,1.5],"type":"Point"},"properties":{"count":2},"type":"Feature"}"#)
+        .unwrap();
+
+    // Options can also come before the predicate
+    Assert::main_binary()
+        .with_args(&[
+            "join",
+            "--file",
+            query_file.to_str().unwrap(),
+            "--mode",
+            "count",
+            "--left",
+            "contains",
+        ])
+        .stdin("POINT(1.5 1.5)\nPOINT(5 5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[1.5,1.5],"type":"Point"},"properties":{"count":2},"type":"Feature"}
+{"geometry":{"coordinates":[5.0,5.0],"type":"Point"},"properties":{"count":0},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["join", "--prefix", "z_", "contains", zones])
+        .stdin("POINT(0.5 0.5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[0.5,0.5],"type":"Point"},"properties":{"z_zone":"a"},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn filter_de9im_predicates() {
+    let square = "POLYGON((0 0,1 0,1 1,0 1,0 0))";
+    let inputs = "LINESTRING(0 0,2 0)\nLINESTRING(0.5 0.5,2 0.5)\nPOINT(0.5 0.5)\nPOLYGON((0 0,0 1,1 1,1 0,0 0))\nPOINT(5 5)";
+
+    Assert::main_binary()
+        .with_args(&["filter", "touches", square])
+        .stdin(inputs)
+        .stdout()
+        .is("LINESTRING(0 0,2 0)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "crosses", square])
+        .stdin(inputs)
+        .stdout()
+        .is("LINESTRING(0.5 0.5,2 0.5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "equals", square])
+        .stdin(inputs)
+        .stdout()
+        .is("POLYGON((0 0,0 1,1 1,1 0,0 0))")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "disjoint", square])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(5 5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "within", "POINT(0.5 0.5)"])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(0.5 0.5)\nPOLYGON((0 0,0 1,1 1,1 0,0 0))")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "contains", "LINESTRING(0 0,3 0)"])
+        .stdin(inputs)
+        .stdout()
+        .is("LINESTRING(0 0,2 0)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "relate", "T**FF*FF*", square])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(0.5 0.5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "relate", "T*F", square])
+        .stdin(inputs)
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn filter_bbox() {
+    let inputs = "POINT(1 1)\nLINESTRING(-1 -1,5 5)\nPOINT(10 10)\n9q5";
+    Assert::main_binary()
+        .with_args(&["filter", "bbox", "0,0,2,2"])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(1 1)\nLINESTRING(-1 -1,5 5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "bbox", "0,0,2,2", "--strict"])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(1 1)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "bbox", "-2,-2,-0.5,-0.5"])
+        .stdin(inputs)
+        .stdout()
+        .is("LINESTRING(-1 -1,5 5)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "bbox", "-125,32,-114,42", "--negate"])
+        .stdin(inputs)
+        .stdout()
+        .is("POINT(1 1)\nLINESTRING(-1 -1,5 5)\nPOINT(10 10)")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["filter", "bbox", "0,0,2"])
+        .stdin(inputs)
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn buffer_geometries() {
+    Assert::main_binary()
+        .with_args(&["buffer", "100", "--segments", "1"])
+        .stdin(r#"{"type":"Feature","properties":{"name":"a"},"geometry":{"type":"Point","coordinates":[0,0]}}"#)
+        .stdout()
+        .contains(r#""properties":{"name":"a"}"#)
+        .stdout()
+        .contains("[0.00089831528")
+        .stdout()
+        .contains(r#""type":"Polygon""#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["buffer", "-1", "--units", "km"])
+        .stdin("POLYGON((0 0,0.01 0,0.01 0.01,0 0.01,0 0))\nPOLYGON((0 0,0.1 0,0.1 0.1,0 0.1,0 0))\nLINESTRING(0 0,1 1)")
+        .stdout()
+        .contains(r#""type":"Polygon""#)
+        .stdout()
+        .contains("[0.0089")
+        .stdout()
+        .doesnt_contain("[0.0,0.0]")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["buffer", "10", "--cap", "pointy"])
+        .stdin("LINESTRING(0 0,1 1)")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn overlay_with_query_features() {
+    Assert::main_binary()
+        .with_args(&["overlay", "intersection", "POLYGON((1 -1,3 -1,3 1,1 1,1 -1))"])
+        .stdin(r#"{"type":"Feature","properties":{"name":"a"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[2,0],[2,2],[0,2],[0,0]]]}}"#)
+        .stdout()
+        .contains(r#""properties":{"name":"a"}"#)
+        .stdout()
+        .contains(r#""type":"Polygon""#)
+        .stdout()
+        .contains("[1.0,0.0]")
+        .stdout()
+        .doesnt_contain("[0.0,0.0]")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["overlay", "difference", "POLYGON((0 0,2 0,2 2,0 2,0 0))"])
+        .stdin("POINT(1 1)\nPOINT(5 5)\nLINESTRING(1 1,4 1)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[5.0,5.0],"type":"Point"},"properties":{},"type":"Feature"}
+{"geometry":{"coordinates":[[2.0,1.0],[4.0,1.0]],"type":"LineString"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let query_file = dir.path().join("other.wkt");
+    std::fs::write(&query_file, "POLYGON((0 0,2 0,2 2,0 2,0 0))").unwrap();
+    let path = query_file.to_str().unwrap();
+    for args in &[
+        ["overlay", "--file", path, "difference"],
+        ["overlay", "difference", "--file", path],
+    ] {
+        Assert::main_binary()
+            .with_args(args)
+            .stdin("POINT(1 1)\nPOINT(5 5)")
+            .stdout()
+            .is(r#"{"geometry":{"coordinates":[5.0,5.0],"type":"Point"},"properties":{},"type":"Feature"}"#)
+            .unwrap();
+    }
+
+    Assert::main_binary()
+        .with_args(&["overlay", "union"])
+        .stdin("POINT(1 1)")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn dissolve_polygons() {
+    Assert::main_binary()
+        .with_args(&["dissolve"])
+        .stdin("POLYGON((0 0,1 0,1 1,0 1,0 0))\nPOLYGON((1 0,2 0,2 1,1 1,1 0))\nPOINT(5 5)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[0.0,0.0],[2.0,0.0],[2.0,1.0],[0.0,1.0],[0.0,0.0]]],"type":"Polygon"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["dissolve", "--group-by", "state"])
+        .stdin(r#"{"type":"Feature","properties":{"state":"b"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,1],[0,0]]]}}
+{"type":"Feature","properties":{"state":"a"},"geometry":{"type":"Polygon","coordinates":[[[5,5],[6,5],[6,6],[5,6],[5,5]]]}}
+{"type":"Feature","properties":{"state":"b"},"geometry":{"type":"Polygon","coordinates":[[[1,0],[2,0],[2,1],[1,1],[1,0]]]}}"#)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[[0.0,0.0],[2.0,0.0],[2.0,1.0],[0.0,1.0],[0.0,0.0]]],"type":"Polygon"},"properties":{"state":"b"},"type":"Feature"}
+{"geometry":{"coordinates":[[[5.0,5.0],[6.0,5.0],[6.0,6.0],[5.0,6.0],[5.0,5.0]]],"type":"Polygon"},"properties":{"state":"a"},"type":"Feature"}"#)
+        .unwrap();
+}
+
+#[test]
+fn clip_to_bbox() {
+    Assert::main_binary()
+        .with_args(&["clip", "0,0,1,1"])
+        .stdin(r#"{"type":"Feature","properties":{"name":"a"},"geometry":{"type":"LineString","coordinates":[[-1,0.5],[0.5,0.5]]}}
+POINT(5 5)
+POLYGON((0.5 0.5,2 0.5,2 2,0.5 2,0.5 0.5))"#)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[0.0,0.5],[0.5,0.5]],"type":"LineString"},"properties":{"name":"a"},"type":"Feature"}
+{"geometry":{"coordinates":[[[0.5,0.5],[1.0,0.5],[1.0,1.0],[0.5,1.0],[0.5,0.5]]],"type":"Polygon"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["clip", "-125,32,-114,42"])
+        .stdin("LINESTRING(-130 35,-120 35)\nPOINT(0 0)")
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[[-125.0,35.0],[-120.0,35.0]],"type":"LineString"},"properties":{},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["clip", "0,0,1"])
+        .stdin("POINT(0 0)")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn hull_geometries() {
+    Assert::main_binary()
+        .with_args(&["hull"])
+        .stdin(r#"{"type":"Feature","properties":{"name":"a"},"geometry":{"type":"MultiPoint","coordinates":[[0,0],[2,0],[1,1],[0,2],[2,2]]}}"#)
+        .stdout()
+        .contains(r#""type":"Polygon""#)
+        .stdout()
+        .contains(r#""properties":{"name":"a"}"#)
+        .stdout()
+        .doesnt_contain("[1.0,1.0]")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["hull", "--group-by", "route"])
+        .stdin(r#"{"type":"Feature","properties":{"route":"b"},"geometry":{"type":"Point","coordinates":[1,1]}}
+{"type":"Feature","properties":{"route":"a"},"geometry":{"type":"Point","coordinates":[0,0]}}
+{"type":"Feature","properties":{"route":"b"},"geometry":{"type":"Point","coordinates":[1,1]}}
+{"type":"Feature","properties":{"route":"a"},"geometry":{"type":"Point","coordinates":[2,2]}}"#)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[1.0,1.0],"type":"Point"},"properties":{"route":"b"},"type":"Feature"}
+{"geometry":{"coordinates":[[0.0,0.0],[2.0,2.0]],"type":"LineString"},"properties":{"route":"a"},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["hull", "--concave", "0"])
+        .stdin("POINT(0 0)")
+        .fails()
+        .unwrap();
+}
+
+#[test]
+fn centroid_options() {
+    let c_shape = r#"{"type":"Feature","properties":{"name":"c"},"geometry":{"type":"Polygon","coordinates":[[[0,0],[3,0],[3,1],[1,1],[1,2],[3,2],[3,3],[0,3],[0,0]]]}}"#;
+    Assert::main_binary()
+        .with_args(&["centroid", "--point-on-surface", "--geojson"])
+        .stdin(c_shape)
+        .stdout()
+        .is(r#"{"geometry":{"coordinates":[0.5,1.5],"type":"Point"},"properties":{"name":"c"},"type":"Feature"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["centroid", "--all"])
+        .stdin("POINT(0 0)\nPOINT(2 4)")
+        .stdout()
+        .is(r#"{"coordinates":[1.0,2.0],"type":"Point"}"#)
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["centroid", "--geodesic"])
+        .stdin("MULTIPOINT((170 0),(-170 0))")
+        .stdout()
+        .contains("180.0")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["centroid", "--point-on-surface", "--all"])
+        .stdin("POINT(0 0)")
+        .fails()
+        .unwrap();
+}